@@ -0,0 +1,50 @@
+-- Add migration script here
+CREATE TABLE roles (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL UNIQUE,
+    description TEXT,
+    dt_created TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE TABLE permissions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    code TEXT NOT NULL UNIQUE, -- exemplo: 'courses:write', 'categories:delete'
+    description TEXT
+);
+
+CREATE TABLE role_permissions (
+    role_id UUID NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    permission_id UUID NOT NULL REFERENCES permissions(id) ON DELETE CASCADE,
+    PRIMARY KEY (role_id, permission_id)
+);
+
+CREATE TABLE user_roles (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role_id UUID NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    dt_created TIMESTAMP NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_id, role_id)
+);
+
+INSERT INTO roles (name, description) VALUES
+    ('admin', 'Administrador da plataforma'),
+    ('user', 'Usuário padrão');
+
+INSERT INTO permissions (code, description) VALUES
+    ('courses:write', 'Criar e atualizar cursos'),
+    ('courses:delete', 'Remover cursos'),
+    ('courses:sync', 'Reindexar cursos no Elasticsearch'),
+    ('categories:write', 'Criar e atualizar categorias'),
+    ('categories:delete', 'Remover categorias'),
+    ('roles:manage', 'Atribuir e remover papéis de usuários');
+
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id FROM roles r CROSS JOIN permissions p WHERE r.name = 'admin';
+
+-- Migra o antigo profiles.access_level para papéis
+INSERT INTO user_roles (user_id, role_id)
+SELECT u.id, r.id FROM users u CROSS JOIN roles r WHERE r.name = 'user';
+
+INSERT INTO user_roles (user_id, role_id)
+SELECT p.user_id, r.id FROM profiles p JOIN roles r ON r.name = p.access_level
+WHERE p.access_level <> 'user'
+ON CONFLICT DO NOTHING;
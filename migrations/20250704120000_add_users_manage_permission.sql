@@ -0,0 +1,7 @@
+-- Add migration script here
+INSERT INTO permissions (code, description) VALUES
+    ('users:manage', 'Provisionar e administrar usuários');
+
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id FROM roles r CROSS JOIN permissions p
+WHERE r.name = 'admin' AND p.code = 'users:manage';
@@ -0,0 +1,7 @@
+-- Add migration script here
+ALTER TABLE profiles
+    ADD COLUMN preferred_language TEXT NOT NULL DEFAULT 'pt-BR'
+        CHECK (preferred_language IN ('pt-BR', 'en'));
+
+-- parte em texto puro enviada junto com o HTML; nula nas mensagens antigas
+ALTER TABLE email_outbox ADD COLUMN text_body TEXT;
@@ -0,0 +1,16 @@
+-- Add migration script here
+-- Preferências por categoria; `unsubscribe` continua valendo como descadastro de tudo que não
+-- for transacional
+ALTER TABLE profiles
+    ADD COLUMN email_course_announcements BOOLEAN NOT NULL DEFAULT TRUE,
+    ADD COLUMN email_marketing BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE email_outbox
+    ADD COLUMN category TEXT NOT NULL DEFAULT 'transactional'
+        CHECK (category IN ('transactional', 'course_announcements', 'marketing')),
+    ADD COLUMN list_unsubscribe_url TEXT;
+
+-- `suppressed`: descartado na entrega porque o destinatário não aceita mais a categoria
+ALTER TABLE email_outbox DROP CONSTRAINT email_outbox_status_check;
+ALTER TABLE email_outbox ADD CONSTRAINT email_outbox_status_check
+    CHECK (status IN ('queued', 'sent', 'failed', 'suppressed'));
@@ -0,0 +1,3 @@
+-- Add migration script here
+-- Endereço pendente dos tokens `change_email`; a troca só acontece quando ele é confirmado
+ALTER TABLE user_tokens ADD COLUMN new_email TEXT;
@@ -0,0 +1,12 @@
+-- Add migration script here
+-- Os códigos passam a ser guardados como hash SHA-256; os links já enviados continuam válidos
+ALTER TABLE user_tokens RENAME COLUMN code TO code_hash;
+UPDATE user_tokens SET code_hash = encode(sha256(code_hash::bytea), 'hex');
+CREATE UNIQUE INDEX idx_user_tokens_code_hash ON user_tokens(code_hash);
+
+-- O consumo atômico usa `WHERE NOT consumed`
+UPDATE user_tokens SET consumed = FALSE WHERE consumed IS NULL;
+ALTER TABLE user_tokens ALTER COLUMN consumed SET NOT NULL;
+
+-- Usado pela limpeza periódica de tokens expirados
+CREATE INDEX idx_user_tokens_expires_at ON user_tokens(expires_at);
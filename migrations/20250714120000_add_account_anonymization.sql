@@ -0,0 +1,9 @@
+-- Add migration script here
+-- Contas excluídas (dt_deleted) ficam restauráveis durante o prazo de carência; depois disso os
+-- dados pessoais são anonimizados e a linha é mantida para preservar courses.author_id
+ALTER TABLE users
+ADD COLUMN dt_anonymized TIMESTAMP DEFAULT NULL;
+
+CREATE INDEX idx_users_pending_anonymization
+    ON users (dt_deleted)
+    WHERE dt_deleted IS NOT NULL AND dt_anonymized IS NULL;
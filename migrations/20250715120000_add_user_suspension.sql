@@ -0,0 +1,5 @@
+-- Add migration script here
+-- Conta suspensa por um administrador: login e tokens são recusados até a reativação
+ALTER TABLE users
+ADD COLUMN dt_suspended TIMESTAMP DEFAULT NULL,
+ADD COLUMN suspension_reason TEXT DEFAULT NULL;
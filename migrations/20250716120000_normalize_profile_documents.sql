@@ -0,0 +1,19 @@
+-- Add migration script here
+-- Documentos passam a ser gravados só com dígitos, acompanhados do tipo (CPF ou CNPJ)
+ALTER TABLE profiles
+ADD COLUMN document_type VARCHAR(10) DEFAULT NULL
+    CHECK (document_type IN ('cpf', 'cnpj'));
+
+UPDATE profiles
+SET document = NULLIF(regexp_replace(document, '\D', '', 'g'), '')
+WHERE document IS NOT NULL;
+
+UPDATE profiles
+SET document_type = CASE length(document)
+        WHEN 11 THEN 'cpf'
+        WHEN 14 THEN 'cnpj'
+    END
+WHERE document IS NOT NULL;
+
+-- Busca usada na verificação de unicidade (ACCOUNT_UNIQUE_DOCUMENT)
+CREATE INDEX idx_profiles_document ON profiles (document) WHERE document IS NOT NULL;
@@ -0,0 +1,24 @@
+-- Add migration script here
+-- Telefones passam a ser gravados em E.164 e podem ser verificados por SMS
+ALTER TABLE profiles
+ADD COLUMN phone_verified BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Remove a formatação dos telefones antigos; números brasileiros sem DDI (DDD + número)
+-- recebem o +55 da região padrão. Os demais são normalizados na próxima atualização do perfil.
+UPDATE profiles
+SET phone = NULLIF(regexp_replace(phone, '[^0-9+]', '', 'g'), '')
+WHERE phone IS NOT NULL;
+
+UPDATE profiles
+SET phone = '+55' || phone
+WHERE phone ~ '^[1-9][0-9]{9,10}$';
+
+-- Código de verificação pendente (um por usuário), ligado ao número para o qual foi enviado
+CREATE TABLE phone_verifications (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    phone TEXT NOT NULL,
+    code_hash TEXT NOT NULL,
+    attempts INT NOT NULL DEFAULT 0,
+    dt_expires TIMESTAMP NOT NULL,
+    dt_created TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,3 @@
+-- Add migration script here
+-- perfis públicos e checagem de disponibilidade comparam usernames sem diferenciar maiúsculas
+CREATE INDEX IF NOT EXISTS idx_users_username_lower ON users (LOWER(username));
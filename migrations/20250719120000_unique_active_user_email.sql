@@ -0,0 +1,5 @@
+-- Add migration script here
+-- O e-mail só precisa ser único entre as contas ativas: durante o prazo de restauração ele pode
+-- ser usado em um novo cadastro
+ALTER TABLE users DROP CONSTRAINT users_email_key;
+CREATE UNIQUE INDEX users_email_active_key ON users (email) WHERE dt_deleted IS NULL;
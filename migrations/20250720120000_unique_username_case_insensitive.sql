@@ -0,0 +1,14 @@
+-- Add migration script here
+-- Usernames são únicos sem diferenciar maiúsculas. Contas legadas que só diferem na caixa:
+-- a mais antiga mantém o nome e as demais ganham o início do id como sufixo
+UPDATE users u
+SET username = u.username || '_' || left(replace(u.id::text, '-', ''), 8)
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY LOWER(username) ORDER BY dt_created, id) AS position
+    FROM users
+) ranked
+WHERE ranked.id = u.id AND ranked.position > 1;
+
+DROP INDEX IF EXISTS idx_users_username_lower;
+ALTER TABLE users DROP CONSTRAINT users_username_key;
+CREATE UNIQUE INDEX users_username_lower_key ON users (LOWER(username));
@@ -0,0 +1,22 @@
+-- Add migration script here
+-- A normalização de documentos deixou sem `document_type` os legados que não têm 11 (CPF) nem
+-- 14 (CNPJ) dígitos. Eles saem do perfil, para que validação e mascaramento sempre vejam um
+-- documento tipado, e ficam guardados aqui até serem revisados com o titular.
+CREATE TABLE invalid_profile_documents (
+    profile_id UUID PRIMARY KEY REFERENCES profiles(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    document TEXT NOT NULL,
+    dt_created TIMESTAMP NOT NULL DEFAULT now()
+);
+
+INSERT INTO invalid_profile_documents (profile_id, user_id, document)
+SELECT id, user_id, document
+FROM profiles
+WHERE document IS NOT NULL AND document_type IS NULL;
+
+UPDATE profiles
+SET document = NULL
+WHERE document IS NOT NULL AND document_type IS NULL;
+
+ALTER TABLE profiles
+ADD CONSTRAINT profiles_document_typed CHECK ((document IS NULL) = (document_type IS NULL));
@@ -0,0 +1,11 @@
+-- Add migration script here
+-- Uso dos tokens de desafio de MFA (`jti`): cada desafio aceita um login e poucas tentativas
+CREATE TABLE mfa_challenges (
+    jti TEXT PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    failed_attempts INTEGER NOT NULL DEFAULT 0,
+    consumed_at TIMESTAMP,
+    expires_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX idx_mfa_challenges_expires_at ON mfa_challenges (expires_at);
@@ -1,4 +1,5 @@
 use clap::{Parser, Subcommand};
+use uuid::Uuid;
 
 #[derive(Parser)]
 #[command(name = "Rust Usecases CLI")]
@@ -19,4 +20,23 @@ pub enum Commands {
     Migrate,
     /// Rodar seeds
     Seed,
+    /// Renderiza um template de e-mail com dados de exemplo
+    PreviewEmail {
+        /// Nome do template (ex.: confirm_email, reset_password)
+        template: String,
+        /// Idioma do template (pt-BR, en)
+        #[arg(long, default_value = "pt-BR")]
+        language: String,
+        /// Mostra a parte em texto puro em vez do HTML
+        #[arg(long)]
+        text: bool,
+    },
+    /// Anuncia um curso por e-mail a quem aceita novidades sobre cursos
+    AnnounceCourse {
+        /// Id do curso
+        course_id: Uuid,
+        /// Destinatários enfileirados por transação
+        #[arg(long, default_value_t = 500)]
+        batch_size: i64,
+    },
 }
@@ -0,0 +1,35 @@
+use crate::cli_init::init_cli_environment;
+use rust_usecases::db::postgres::get_db_pool;
+use rust_usecases::repositories::course_repository;
+use rust_usecases::services::email_service;
+use uuid::Uuid;
+
+/// Enfileira o anúncio de um curso para quem aceita a categoria, em lotes
+pub async fn run(
+    course_id: Uuid,
+    batch_size: i64,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    init_cli_environment();
+
+    if batch_size < 1 {
+        return Err("--batch-size deve ser maior que zero".into());
+    }
+
+    let db = get_db_pool().await;
+    let course = course_repository::find_course_by_id(course_id, &db)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !course.is_active || course.dt_deleted.is_some() {
+        return Err(format!("Curso '{}' não está ativo", course.name).into());
+    }
+
+    let queued = email_service::queue_course_announcement(&course, batch_size, &db)
+        .await
+        .map_err(|e| e.to_string())?;
+    println!(
+        "✅ Anúncio do curso '{}' enfileirado para {} destinatários",
+        course.name, queued
+    );
+
+    Ok(())
+}
@@ -1,2 +1,4 @@
+pub mod announce_course;
 pub mod migrate;
+pub mod preview_email;
 pub mod sync_courses;
@@ -0,0 +1,74 @@
+use crate::cli_init::init_cli_environment;
+use rust_usecases::config::get_settings;
+use rust_usecases::mailer::{EmailTemplate, EmailTemplates};
+use tera::Context;
+
+/// Renderiza um template de e-mail com dados de exemplo e imprime no terminal
+pub async fn run(
+    template: &str,
+    language: &str,
+    text: bool,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    init_cli_environment();
+
+    let template = EmailTemplate::from_name(template).ok_or_else(|| {
+        let available: Vec<&str> = EmailTemplate::ALL.iter().map(|t| t.name()).collect();
+        format!(
+            "Template '{}' não existe. Disponíveis: {}",
+            template,
+            available.join(", ")
+        )
+    })?;
+
+    let settings = get_settings();
+    let templates = EmailTemplates::from_settings(&settings.email)?;
+
+    let link = match template {
+        EmailTemplate::ConfirmEmail => format!(
+            "{}/api/v1/confirm-email/CODIGO-DE-EXEMPLO/",
+            settings.email.app_url.trim_end_matches('/')
+        ),
+        EmailTemplate::ResetPassword => format!(
+            "{}/reset-password?token=CODIGO-DE-EXEMPLO",
+            settings.email.frontend_url.trim_end_matches('/')
+        ),
+        EmailTemplate::CourseAnnouncement => format!(
+            "{}/courses/00000000-0000-0000-0000-000000000000",
+            settings.email.frontend_url.trim_end_matches('/')
+        ),
+        EmailTemplate::ChangeEmail => format!(
+            "{}/api/v1/confirm-email-change/CODIGO-DE-EXEMPLO/",
+            settings.email.app_url.trim_end_matches('/')
+        ),
+        // o aviso ao endereço antigo não tem link
+        EmailTemplate::EmailChangeNotice => String::new(),
+        EmailTemplate::PasswordChanged => format!(
+            "{}/forgot-password",
+            settings.email.frontend_url.trim_end_matches('/')
+        ),
+    };
+
+    let mut ctx = Context::new();
+    ctx.insert("name", "Maria");
+    ctx.insert("link", &link);
+    ctx.insert("course_name", "Rust para Iniciantes");
+    ctx.insert("new_email", "maria.nova@example.com");
+    ctx.insert(
+        "unsubscribe_link",
+        &format!(
+            "{}/unsubscribe?token=TOKEN-DE-EXEMPLO",
+            settings.email.frontend_url.trim_end_matches('/')
+        ),
+    );
+
+    let rendered = templates.render(template, language, &ctx)?;
+
+    println!("Assunto: {}\n", rendered.subject);
+    if text {
+        println!("{}", rendered.text);
+    } else {
+        println!("{}", rendered.html);
+    }
+
+    Ok(())
+}
@@ -1,12 +1,23 @@
+use crate::mailer::EmailSender;
+use crate::sms::SmsSender;
+use crate::storage::BlobStore;
+use crate::stores::login_attempt_store::LoginAttemptStore;
+use crate::stores::rate_limit_store::RateLimitStore;
 use crate::websocket::server::WsServer;
 use actix::Addr;
 use elasticsearch::Elasticsearch;
 use mongodb::Database;
 use sqlx::PgPool;
+use std::sync::Arc;
 
 pub struct AppState {
     pub db: PgPool,
     pub mongo: Database,
     pub es: Elasticsearch,
     pub ws_server: Addr<WsServer>,
+    pub login_attempts: Arc<dyn LoginAttemptStore>,
+    pub rate_limits: Arc<dyn RateLimitStore>,
+    pub mailer: Arc<dyn EmailSender>,
+    pub blobs: Arc<dyn BlobStore>,
+    pub sms: Arc<dyn SmsSender>,
 }
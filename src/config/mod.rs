@@ -2,7 +2,12 @@ pub mod app_state;
 pub mod init_settings;
 mod settings;
 
-pub use settings::Settings;
+pub use settings::{
+    AccountSettings, AvatarSettings, EmailBackend, EmailOutboxSettings, EmailSettings,
+    JwtAlgorithm, JwtSettings, OidcProviderSettings, PasswordSettings, PhoneSettings,
+    RateLimitRule, S3Settings, Settings, SmsBackend, SmsSettings, SmtpSettings, StorageBackend,
+    StorageSettings, UserTokenSettings,
+};
 
 use std::sync::OnceLock;
 
@@ -1,3 +1,6 @@
+use crate::models::auth::protected_actions;
+use crate::utils::phone::is_valid_region;
+use ipnet::IpNet;
 use serde::Deserialize;
 use std::env;
 use std::net::IpAddr;
@@ -26,6 +29,490 @@ pub struct JwtSettings {
         message = "JWT_EXPIRES_IN deve estar entre 300 e 86400 segundos"
     ))]
     pub expires_in: u64,
+    #[validate(range(
+        min = 3600,
+        max = 7776000,
+        message = "JWT_REFRESH_EXPIRES_IN deve estar entre 3600 e 7776000 segundos"
+    ))]
+    pub refresh_expires_in: u64,
+    pub algorithm: JwtAlgorithm,
+    /// `kid` da chave que assina os novos tokens
+    #[validate(length(min = 1, message = "JWT_KEY_ID não pode estar vazio"))]
+    pub key_id: String,
+    /// Chave privada (PEM PKCS#8) usada com RS256/EdDSA
+    pub private_key_path: Option<String>,
+    /// Chaves públicas aceitas na verificação (`kid`, arquivo PEM), incluindo a ativa.
+    /// Manter a chave anterior aqui durante a rotação mantém válidos os tokens já emitidos.
+    pub public_keys: Vec<(String, String)>,
+    /// Liga a limpeza periódica de `refresh_tokens` e `revoked_tokens` expirados
+    pub purge_enabled: bool,
+    #[validate(range(
+        min = 60,
+        max = 86400,
+        message = "JWT_PURGE_INTERVAL deve estar entre 60 e 86400 segundos"
+    ))]
+    pub purge_interval_seconds: u64,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+pub enum JwtAlgorithm {
+    HS256,
+    RS256,
+    EdDSA,
+}
+
+impl FromStr for JwtAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "HS256" => Ok(JwtAlgorithm::HS256),
+            "RS256" => Ok(JwtAlgorithm::RS256),
+            "EDDSA" => Ok(JwtAlgorithm::EdDSA),
+            _ => Err(format!("Algoritmo JWT inválido: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct MfaSettings {
+    #[validate(length(min = 1, message = "MFA_ISSUER não pode estar vazio"))]
+    pub issuer: String,
+    #[validate(range(
+        min = 60,
+        max = 900,
+        message = "MFA_CHALLENGE_EXPIRES_IN deve estar entre 60 e 900 segundos"
+    ))]
+    pub challenge_expires_in: u64,
+}
+
+/// Política de senhas e custo do Argon2id
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct PasswordSettings {
+    #[validate(range(
+        min = 6,
+        max = 64,
+        message = "PASSWORD_MIN_LENGTH deve estar entre 6 e 64"
+    ))]
+    pub min_length: usize,
+    #[validate(range(
+        min = 16,
+        max = 1024,
+        message = "PASSWORD_MAX_LENGTH deve estar entre 16 e 1024"
+    ))]
+    pub max_length: usize,
+    pub require_letter: bool,
+    pub require_uppercase: bool,
+    pub require_lowercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    /// Rejeita senhas da lista de senhas comuns embutida no binário
+    pub deny_common: bool,
+    /// Quantas senhas anteriores não podem ser reutilizadas (0 desliga)
+    #[validate(range(
+        min = 0,
+        max = 24,
+        message = "PASSWORD_HISTORY_SIZE deve estar entre 0 e 24"
+    ))]
+    pub history_size: i64,
+    #[validate(range(
+        min = 8192,
+        max = 1048576,
+        message = "PASSWORD_ARGON2_MEMORY_KIB deve estar entre 8192 e 1048576"
+    ))]
+    pub argon2_memory_kib: u32,
+    #[validate(range(
+        min = 1,
+        max = 10,
+        message = "PASSWORD_ARGON2_ITERATIONS deve estar entre 1 e 10"
+    ))]
+    pub argon2_iterations: u32,
+    #[validate(range(
+        min = 1,
+        max = 16,
+        message = "PASSWORD_ARGON2_PARALLELISM deve estar entre 1 e 16"
+    ))]
+    pub argon2_parallelism: u32,
+}
+
+/// Regras de conta do usuário
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct AccountSettings {
+    /// Gera o `username` a partir do novo e-mail ao concluir a troca; senão mantém o atual
+    pub email_change_regenerates_username: bool,
+    /// Ações bloqueadas até o e-mail ser confirmado (ver `models::auth::protected_actions`)
+    pub require_confirmed_email_for: Vec<String>,
+    /// Impede que o mesmo CPF/CNPJ seja usado em mais de uma conta ativa
+    pub unique_document: bool,
+    /// Dias em que uma conta excluída ainda pode ser restaurada antes de ser anonimizada
+    #[validate(range(
+        min = 0,
+        max = 365,
+        message = "ACCOUNT_DELETION_GRACE_DAYS deve estar entre 0 e 365"
+    ))]
+    pub deletion_grace_days: i64,
+    /// Liga a anonimização periódica das contas com o prazo de restauração encerrado
+    pub deletion_purge_enabled: bool,
+    #[validate(range(
+        min = 60,
+        max = 86400,
+        message = "ACCOUNT_DELETION_PURGE_INTERVAL deve estar entre 60 e 86400 segundos"
+    ))]
+    pub deletion_purge_interval_seconds: u64,
+}
+
+/// Tokens de uso único enviados por e-mail (`user_tokens`)
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct UserTokenSettings {
+    #[validate(range(
+        min = 1,
+        max = 43200,
+        message = "USER_TOKEN_CONFIRM_EMAIL_EXPIRES_MINUTES deve estar entre 1 e 43200"
+    ))]
+    pub confirm_email_expires_minutes: i64,
+    #[validate(range(
+        min = 1,
+        max = 43200,
+        message = "USER_TOKEN_CHANGE_PASSWORD_EXPIRES_MINUTES deve estar entre 1 e 43200"
+    ))]
+    pub change_password_expires_minutes: i64,
+    #[validate(range(
+        min = 1,
+        max = 43200,
+        message = "USER_TOKEN_CHANGE_EMAIL_EXPIRES_MINUTES deve estar entre 1 e 43200"
+    ))]
+    pub change_email_expires_minutes: i64,
+    /// Intervalo mínimo entre dois envios do link de confirmação para a mesma conta
+    #[validate(range(
+        min = 0,
+        max = 3600,
+        message = "USER_TOKEN_CONFIRM_EMAIL_RESEND_SECONDS deve estar entre 0 e 3600"
+    ))]
+    pub confirm_email_resend_seconds: i64,
+    /// Liga a limpeza periódica dos tokens expirados
+    pub purge_enabled: bool,
+    #[validate(range(
+        min = 60,
+        max = 86400,
+        message = "USER_TOKEN_PURGE_INTERVAL deve estar entre 60 e 86400 segundos"
+    ))]
+    pub purge_interval_seconds: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RedisSettings {
+    /// Sem URL, os contadores de segurança ficam em memória (apenas uma instância)
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct LoginThrottleSettings {
+    #[validate(range(
+        min = 1,
+        max = 100,
+        message = "LOGIN_MAX_ATTEMPTS_PER_EMAIL deve estar entre 1 e 100"
+    ))]
+    pub max_attempts_per_email: u32,
+    #[validate(range(
+        min = 1,
+        max = 10000,
+        message = "LOGIN_MAX_ATTEMPTS_PER_IP deve estar entre 1 e 10000"
+    ))]
+    pub max_attempts_per_ip: u32,
+    #[validate(range(
+        min = 60,
+        max = 86400,
+        message = "LOGIN_ATTEMPT_WINDOW deve estar entre 60 e 86400 segundos"
+    ))]
+    pub window_seconds: u64,
+    #[validate(range(
+        min = 1,
+        max = 86400,
+        message = "LOGIN_LOCKOUT_SECONDS deve estar entre 1 e 86400 segundos"
+    ))]
+    pub lockout_seconds: u64,
+    #[validate(range(
+        min = 1,
+        max = 604800,
+        message = "LOGIN_MAX_LOCKOUT_SECONDS deve estar entre 1 e 604800 segundos"
+    ))]
+    pub max_lockout_seconds: u64,
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct RateLimitRule {
+    #[validate(range(
+        min = 1,
+        max = 100000,
+        message = "O limite de requisições deve estar entre 1 e 100000"
+    ))]
+    pub requests: u32,
+    #[validate(range(
+        min = 1,
+        max = 86400,
+        message = "O período do rate limit deve estar entre 1 e 86400 segundos"
+    ))]
+    pub period_seconds: u64,
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct RateLimitSettings {
+    pub enabled: bool,
+    /// Rotas sensíveis de autenticação (login, cadastro, senha), por IP
+    #[validate]
+    pub auth: RateLimitRule,
+    /// Demais rotas anônimas, por IP
+    #[validate]
+    pub public: RateLimitRule,
+    /// Rotas autenticadas, por usuário (`Claims.sub`)
+    #[validate]
+    pub user: RateLimitRule,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OidcProviderSettings {
+    /// Nome usado na rota (`/auth/{provider}/start/`)
+    pub name: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub authorization_url: String,
+    pub token_url: String,
+    pub userinfo_url: String,
+    pub redirect_uri: String,
+    pub scopes: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct OidcSettings {
+    pub providers: Vec<OidcProviderSettings>,
+    #[validate(range(
+        min = 60,
+        max = 3600,
+        message = "OIDC_STATE_EXPIRES_IN deve estar entre 60 e 3600 segundos"
+    ))]
+    pub state_expires_in: u64,
+}
+
+impl OidcSettings {
+    pub fn provider(&self, name: &str) -> Option<&OidcProviderSettings> {
+        self.providers.iter().find(|provider| provider.name == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+pub enum EmailBackend {
+    Ses,
+    Smtp,
+    /// Grava cada mensagem em `EMAIL_FILE_DIR` (desenvolvimento)
+    File,
+    /// Apenas guarda as mensagens em memória (testes)
+    Memory,
+}
+
+impl FromStr for EmailBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ses" => Ok(EmailBackend::Ses),
+            "smtp" => Ok(EmailBackend::Smtp),
+            "file" => Ok(EmailBackend::File),
+            "memory" => Ok(EmailBackend::Memory),
+            _ => Err(format!("EMAIL_BACKEND inválido: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct SmtpSettings {
+    pub host: String,
+    #[validate(range(min = 1, max = 65535, message = "SMTP_PORT deve estar entre 1 e 65535"))]
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Usa STARTTLS; desligado apenas para servidores locais (MailHog, Mailpit)
+    pub starttls: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct EmailOutboxSettings {
+    /// Liga o worker de entrega; com ele desligado os e-mails apenas se acumulam na fila
+    pub worker_enabled: bool,
+    #[validate(range(
+        min = 1,
+        max = 3600,
+        message = "EMAIL_OUTBOX_POLL_INTERVAL deve estar entre 1 e 3600 segundos"
+    ))]
+    pub poll_interval_seconds: u64,
+    #[validate(range(
+        min = 1,
+        max = 1000,
+        message = "EMAIL_OUTBOX_BATCH_SIZE deve estar entre 1 e 1000"
+    ))]
+    pub batch_size: i64,
+    #[validate(range(
+        min = 1,
+        max = 50,
+        message = "EMAIL_OUTBOX_MAX_ATTEMPTS deve estar entre 1 e 50"
+    ))]
+    pub max_attempts: i32,
+    #[validate(range(
+        min = 1,
+        max = 86400,
+        message = "EMAIL_OUTBOX_RETRY_BASE_SECONDS deve estar entre 1 e 86400 segundos"
+    ))]
+    pub retry_base_seconds: u64,
+    #[validate(range(
+        min = 1,
+        max = 604800,
+        message = "EMAIL_OUTBOX_RETRY_MAX_SECONDS deve estar entre 1 e 604800 segundos"
+    ))]
+    pub retry_max_seconds: u64,
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct EmailSettings {
+    pub backend: EmailBackend,
+    #[validate(email(message = "DEFAULT_FROM_EMAIL inválido"))]
+    pub from_email: String,
+    #[validate]
+    pub smtp: SmtpSettings,
+    pub file_dir: String,
+    /// Diretório com os templates `<idioma>/<nome>.{html,txt}`. Sem ele, usa os templates
+    /// embutidos no binário.
+    pub templates_dir: Option<String>,
+    /// URL pública da API, usada no link de confirmação de e-mail
+    #[validate(url(message = "APP_URL inválida"))]
+    pub app_url: String,
+    /// URL do front-end, usada no link de redefinição de senha
+    #[validate(url(message = "FRONTEND_URL inválida"))]
+    pub frontend_url: String,
+    #[validate]
+    pub outbox: EmailOutboxSettings,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+pub enum StorageBackend {
+    /// Grava os objetos em `STORAGE_LOCAL_DIR` e os serve pela própria API
+    Local,
+    /// Qualquer serviço compatível com S3 (AWS, MinIO, R2...)
+    S3,
+    /// Apenas guarda os objetos em memória (testes)
+    Memory,
+}
+
+impl FromStr for StorageBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "local" => Ok(StorageBackend::Local),
+            "s3" => Ok(StorageBackend::S3),
+            "memory" => Ok(StorageBackend::Memory),
+            _ => Err(format!("STORAGE_BACKEND inválido: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct S3Settings {
+    pub bucket: String,
+    pub region: String,
+    /// Endpoint de um serviço compatível (ex: `http://localhost:9000` para o MinIO)
+    pub endpoint: Option<String>,
+    /// Sem as chaves, usa `AWS_ACCESS_KEY_ID` e `AWS_SECRET_ACCESS_KEY`
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+    /// Endereça o bucket no caminho (`endpoint/bucket/chave`), exigido pelo MinIO
+    pub force_path_style: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct StorageSettings {
+    pub backend: StorageBackend,
+    pub local_dir: String,
+    /// Prefixo das URLs públicas dos objetos. Sem ele, o backend local usa
+    /// `{APP_URL}/api/v1/media` e o S3 usa o endereço do próprio bucket.
+    #[validate(url(message = "STORAGE_PUBLIC_URL inválida"))]
+    pub public_url: Option<String>,
+    pub s3: S3Settings,
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct AvatarSettings {
+    #[validate(range(
+        min = 1024,
+        max = 20971520,
+        message = "AVATAR_MAX_BYTES deve estar entre 1024 e 20971520"
+    ))]
+    pub max_bytes: usize,
+    /// Lados, em pixels, das miniaturas quadradas geradas a cada envio
+    #[validate(length(min = 1, message = "AVATAR_SIZES não pode estar vazia"))]
+    pub sizes: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+pub enum SmsBackend {
+    /// Apenas registra a mensagem no log (desenvolvimento)
+    Log,
+    /// Grava cada mensagem em `SMS_FILE_DIR` (desenvolvimento)
+    File,
+    /// Gateway HTTP do provedor (`SMS_HTTP_URL`)
+    Http,
+    /// Apenas guarda as mensagens em memória (testes)
+    Memory,
+}
+
+impl FromStr for SmsBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "log" => Ok(SmsBackend::Log),
+            "file" => Ok(SmsBackend::File),
+            "http" => Ok(SmsBackend::Http),
+            "memory" => Ok(SmsBackend::Memory),
+            _ => Err(format!("SMS_BACKEND inválido: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct SmsSettings {
+    pub backend: SmsBackend,
+    /// Remetente informado ao provedor (número ou nome alfanumérico)
+    pub from: String,
+    pub file_dir: String,
+    #[validate(url(message = "SMS_HTTP_URL inválida"))]
+    pub http_url: Option<String>,
+    pub http_token: Option<String>,
+}
+
+/// Telefones do perfil e a verificação por SMS
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct PhoneSettings {
+    /// Região (ISO 3166, ex: `BR`) assumida para números sem DDI
+    pub default_region: String,
+    #[validate(range(
+        min = 1,
+        max = 60,
+        message = "PHONE_VERIFICATION_EXPIRES_MINUTES deve estar entre 1 e 60"
+    ))]
+    pub verification_expires_minutes: i64,
+    /// Tentativas com código errado antes de o código deixar de valer
+    #[validate(range(
+        min = 1,
+        max = 10,
+        message = "PHONE_VERIFICATION_MAX_ATTEMPTS deve estar entre 1 e 10"
+    ))]
+    pub verification_max_attempts: i32,
+    /// Intervalo mínimo entre dois envios para o mesmo número
+    #[validate(range(
+        min = 0,
+        max = 3600,
+        message = "PHONE_VERIFICATION_RESEND_SECONDS deve estar entre 0 e 3600"
+    ))]
+    pub verification_resend_seconds: i64,
 }
 
 #[derive(Debug, Clone, Deserialize, Validate)]
@@ -34,6 +521,9 @@ pub struct ServerSettings {
     pub host: IpAddr,
     #[validate(range(min = 1, max = 65535, message = "Porta deve estar entre 1 e 65535"))]
     pub port: u16,
+    /// Proxies reversos (IPs ou CIDRs) autorizados a informar o IP do cliente em
+    /// `X-Forwarded-For`; vazio = o header é ignorado e vale o IP da conexão
+    pub trusted_proxies: Vec<IpNet>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -50,6 +540,31 @@ pub struct Settings {
     #[validate]
     pub jwt: JwtSettings,
     #[validate]
+    pub mfa: MfaSettings,
+    #[validate]
+    pub login_throttle: LoginThrottleSettings,
+    pub redis: RedisSettings,
+    #[validate]
+    pub rate_limit: RateLimitSettings,
+    #[validate]
+    pub oidc: OidcSettings,
+    #[validate]
+    pub email: EmailSettings,
+    #[validate]
+    pub account: AccountSettings,
+    #[validate]
+    pub user_tokens: UserTokenSettings,
+    #[validate]
+    pub password: PasswordSettings,
+    #[validate]
+    pub storage: StorageSettings,
+    #[validate]
+    pub avatar: AvatarSettings,
+    #[validate]
+    pub sms: SmsSettings,
+    #[validate]
+    pub phone: PhoneSettings,
+    #[validate]
     pub server: ServerSettings,
     pub environment: Environment,
 }
@@ -74,6 +589,21 @@ impl FromStr for Environment {
     }
 }
 
+/// Lista separada por vírgulas; IPs sem máscara valem só para o próprio endereço
+fn parse_trusted_proxies(value: &str) -> Result<Vec<IpNet>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            entry
+                .parse::<IpNet>()
+                .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+                .map_err(|_| format!("TRUSTED_PROXIES inválido: {}", entry))
+        })
+        .collect()
+}
+
 fn validate_ip(ip: &IpAddr) -> Result<(), validator::ValidationError> {
     if ip.is_unspecified() {
         let mut err = validator::ValidationError::new("invalid_ip");
@@ -83,6 +613,133 @@ fn validate_ip(ip: &IpAddr) -> Result<(), validator::ValidationError> {
     Ok(())
 }
 
+fn load_rate_limit_rule(
+    requests_var: &str,
+    period_var: &str,
+    default_requests: u32,
+    default_period: u64,
+) -> Result<RateLimitRule, String> {
+    Ok(RateLimitRule {
+        requests: match env::var(requests_var) {
+            Ok(value) => value
+                .parse()
+                .map_err(|_| format!("{} deve ser um número", requests_var))?,
+            Err(_) => default_requests,
+        },
+        period_seconds: match env::var(period_var) {
+            Ok(value) => value
+                .parse()
+                .map_err(|_| format!("{} deve ser um número", period_var))?,
+            Err(_) => default_period,
+        },
+    })
+}
+
+/// Formato: `kid=caminho.pem,kid_anterior=caminho_anterior.pem`
+fn parse_jwt_public_keys(value: &str) -> Result<Vec<(String, String)>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            entry
+                .split_once('=')
+                .map(|(kid, path)| (kid.trim().to_string(), path.trim().to_string()))
+                .ok_or_else(|| format!("JWT_PUBLIC_KEYS inválida: {}", entry))
+        })
+        .collect()
+}
+
+/// Endpoints conhecidos, usados quando as URLs do provedor não são informadas
+fn default_oidc_endpoints(name: &str) -> Option<(&'static str, &'static str, &'static str)> {
+    match name {
+        "google" => Some((
+            "https://accounts.google.com/o/oauth2/v2/auth",
+            "https://oauth2.googleapis.com/token",
+            "https://openidconnect.googleapis.com/v1/userinfo",
+        )),
+        "github" => Some((
+            "https://github.com/login/oauth/authorize",
+            "https://github.com/login/oauth/access_token",
+            "https://api.github.com/user",
+        )),
+        _ => None,
+    }
+}
+
+/// Lê os provedores listados em `OIDC_PROVIDERS` (ex: `google,keycloak`), cada um
+/// configurado pelas variáveis `OIDC_<NOME>_*`
+/// Formato: `create_course,websocket`
+fn parse_protected_actions(value: &str) -> Result<Vec<String>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|action| !action.is_empty())
+        .map(|action| {
+            if protected_actions::ALL.contains(&action) {
+                Ok(action.to_string())
+            } else {
+                Err(format!(
+                    "REQUIRE_CONFIRMED_EMAIL_FOR: ação '{}' inválida. Use: {}",
+                    action,
+                    protected_actions::ALL.join(", ")
+                ))
+            }
+        })
+        .collect()
+}
+
+/// Formato: `512,256,64`
+fn parse_avatar_sizes(value: &str) -> Result<Vec<u32>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|size| !size.is_empty())
+        .map(|size| match size.parse() {
+            Ok(size @ 16..=2048) => Ok(size),
+            _ => Err(format!(
+                "AVATAR_SIZES: tamanho '{}' inválido. Use valores entre 16 e 2048",
+                size
+            )),
+        })
+        .collect()
+}
+
+fn load_oidc_providers() -> Result<Vec<OidcProviderSettings>, String> {
+    let names = env::var("OIDC_PROVIDERS").unwrap_or_default();
+
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            let prefix = format!("OIDC_{}", name.to_uppercase());
+            let var = |suffix: &str| env::var(format!("{}_{}", prefix, suffix));
+            let required = |suffix: &str| {
+                var(suffix).map_err(|_| format!("{}_{} não definida", prefix, suffix))
+            };
+            let endpoint = |suffix: &str, default: Option<&str>| {
+                var(suffix)
+                    .ok()
+                    .or(default.map(str::to_string))
+                    .ok_or_else(|| format!("{}_{} não definida", prefix, suffix))
+            };
+            let defaults = default_oidc_endpoints(name);
+
+            Ok(OidcProviderSettings {
+                name: name.to_string(),
+                client_id: required("CLIENT_ID")?,
+                client_secret: required("CLIENT_SECRET")?,
+                authorization_url: endpoint("AUTHORIZATION_URL", defaults.map(|d| d.0))?,
+                token_url: endpoint("TOKEN_URL", defaults.map(|d| d.1))?,
+                userinfo_url: endpoint("USERINFO_URL", defaults.map(|d| d.2))?,
+                redirect_uri: required("REDIRECT_URI")?,
+                scopes: var("SCOPES").unwrap_or_else(|_| "openid email profile".to_string()),
+            })
+        })
+        .collect()
+}
+
 #[allow(dead_code)]
 impl Settings {
     pub fn load() -> Result<Self, String> {
@@ -109,6 +766,299 @@ impl Settings {
                     .unwrap_or_else(|_| "86400".to_string())
                     .parse()
                     .map_err(|_| "JWT_EXPIRES_IN deve ser um número")?,
+                refresh_expires_in: env::var("JWT_REFRESH_EXPIRES_IN")
+                    .unwrap_or_else(|_| "2592000".to_string())
+                    .parse()
+                    .map_err(|_| "JWT_REFRESH_EXPIRES_IN deve ser um número")?,
+                algorithm: env::var("JWT_ALGORITHM")
+                    .unwrap_or_else(|_| "HS256".to_string())
+                    .parse()?,
+                key_id: env::var("JWT_KEY_ID").unwrap_or_else(|_| "default".to_string()),
+                private_key_path: env::var("JWT_PRIVATE_KEY_PATH").ok(),
+                public_keys: parse_jwt_public_keys(
+                    &env::var("JWT_PUBLIC_KEYS").unwrap_or_default(),
+                )?,
+                purge_enabled: env::var("JWT_PURGE_ENABLED")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()
+                    .map_err(|_| "JWT_PURGE_ENABLED deve ser true ou false")?,
+                purge_interval_seconds: env::var("JWT_PURGE_INTERVAL")
+                    .unwrap_or_else(|_| "3600".to_string())
+                    .parse()
+                    .map_err(|_| "JWT_PURGE_INTERVAL deve ser um número")?,
+            },
+            mfa: MfaSettings {
+                issuer: env::var("MFA_ISSUER").unwrap_or_else(|_| "rust-usecases".to_string()),
+                challenge_expires_in: env::var("MFA_CHALLENGE_EXPIRES_IN")
+                    .unwrap_or_else(|_| "300".to_string())
+                    .parse()
+                    .map_err(|_| "MFA_CHALLENGE_EXPIRES_IN deve ser um número")?,
+            },
+            login_throttle: LoginThrottleSettings {
+                max_attempts_per_email: env::var("LOGIN_MAX_ATTEMPTS_PER_EMAIL")
+                    .unwrap_or_else(|_| "5".to_string())
+                    .parse()
+                    .map_err(|_| "LOGIN_MAX_ATTEMPTS_PER_EMAIL deve ser um número")?,
+                max_attempts_per_ip: env::var("LOGIN_MAX_ATTEMPTS_PER_IP")
+                    .unwrap_or_else(|_| "20".to_string())
+                    .parse()
+                    .map_err(|_| "LOGIN_MAX_ATTEMPTS_PER_IP deve ser um número")?,
+                window_seconds: env::var("LOGIN_ATTEMPT_WINDOW")
+                    .unwrap_or_else(|_| "900".to_string())
+                    .parse()
+                    .map_err(|_| "LOGIN_ATTEMPT_WINDOW deve ser um número")?,
+                lockout_seconds: env::var("LOGIN_LOCKOUT_SECONDS")
+                    .unwrap_or_else(|_| "60".to_string())
+                    .parse()
+                    .map_err(|_| "LOGIN_LOCKOUT_SECONDS deve ser um número")?,
+                max_lockout_seconds: env::var("LOGIN_MAX_LOCKOUT_SECONDS")
+                    .unwrap_or_else(|_| "3600".to_string())
+                    .parse()
+                    .map_err(|_| "LOGIN_MAX_LOCKOUT_SECONDS deve ser um número")?,
+            },
+            redis: RedisSettings {
+                url: env::var("REDIS_URL").ok().filter(|url| !url.is_empty()),
+            },
+            rate_limit: RateLimitSettings {
+                enabled: env::var("RATE_LIMIT_ENABLED")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()
+                    .map_err(|_| "RATE_LIMIT_ENABLED deve ser true ou false")?,
+                auth: load_rate_limit_rule(
+                    "RATE_LIMIT_AUTH_REQUESTS",
+                    "RATE_LIMIT_AUTH_PERIOD",
+                    30,
+                    60,
+                )?,
+                public: load_rate_limit_rule(
+                    "RATE_LIMIT_PUBLIC_REQUESTS",
+                    "RATE_LIMIT_PUBLIC_PERIOD",
+                    60,
+                    60,
+                )?,
+                user: load_rate_limit_rule("RATE_LIMIT_REQUESTS", "RATE_LIMIT_PERIOD", 100, 60)?,
+            },
+            oidc: OidcSettings {
+                providers: load_oidc_providers()?,
+                state_expires_in: env::var("OIDC_STATE_EXPIRES_IN")
+                    .unwrap_or_else(|_| "600".to_string())
+                    .parse()
+                    .map_err(|_| "OIDC_STATE_EXPIRES_IN deve ser um número")?,
+            },
+            email: EmailSettings {
+                backend: env::var("EMAIL_BACKEND")
+                    .unwrap_or_else(|_| "file".to_string())
+                    .parse()?,
+                from_email: env::var("DEFAULT_FROM_EMAIL")
+                    .unwrap_or_else(|_| "noreply@localhost.com".to_string()),
+                smtp: SmtpSettings {
+                    host: env::var("SMTP_HOST").unwrap_or_else(|_| "localhost".to_string()),
+                    port: env::var("SMTP_PORT")
+                        .unwrap_or_else(|_| "587".to_string())
+                        .parse()
+                        .map_err(|_| "SMTP_PORT deve ser um número")?,
+                    username: env::var("SMTP_USERNAME").ok().filter(|v| !v.is_empty()),
+                    password: env::var("SMTP_PASSWORD").ok().filter(|v| !v.is_empty()),
+                    starttls: env::var("SMTP_STARTTLS")
+                        .unwrap_or_else(|_| "true".to_string())
+                        .parse()
+                        .map_err(|_| "SMTP_STARTTLS deve ser true ou false")?,
+                },
+                file_dir: env::var("EMAIL_FILE_DIR").unwrap_or_else(|_| "tmp/emails".to_string()),
+                templates_dir: env::var("EMAIL_TEMPLATES_DIR")
+                    .ok()
+                    .filter(|dir| !dir.is_empty()),
+                app_url: env::var("APP_URL")
+                    .unwrap_or_else(|_| "http://localhost:8080".to_string()),
+                frontend_url: env::var("FRONTEND_URL")
+                    .unwrap_or_else(|_| "http://localhost:3000".to_string()),
+                outbox: EmailOutboxSettings {
+                    worker_enabled: env::var("EMAIL_OUTBOX_WORKER_ENABLED")
+                        .unwrap_or_else(|_| "true".to_string())
+                        .parse()
+                        .map_err(|_| "EMAIL_OUTBOX_WORKER_ENABLED deve ser true ou false")?,
+                    poll_interval_seconds: env::var("EMAIL_OUTBOX_POLL_INTERVAL")
+                        .unwrap_or_else(|_| "5".to_string())
+                        .parse()
+                        .map_err(|_| "EMAIL_OUTBOX_POLL_INTERVAL deve ser um número")?,
+                    batch_size: env::var("EMAIL_OUTBOX_BATCH_SIZE")
+                        .unwrap_or_else(|_| "20".to_string())
+                        .parse()
+                        .map_err(|_| "EMAIL_OUTBOX_BATCH_SIZE deve ser um número")?,
+                    max_attempts: env::var("EMAIL_OUTBOX_MAX_ATTEMPTS")
+                        .unwrap_or_else(|_| "5".to_string())
+                        .parse()
+                        .map_err(|_| "EMAIL_OUTBOX_MAX_ATTEMPTS deve ser um número")?,
+                    retry_base_seconds: env::var("EMAIL_OUTBOX_RETRY_BASE_SECONDS")
+                        .unwrap_or_else(|_| "30".to_string())
+                        .parse()
+                        .map_err(|_| "EMAIL_OUTBOX_RETRY_BASE_SECONDS deve ser um número")?,
+                    retry_max_seconds: env::var("EMAIL_OUTBOX_RETRY_MAX_SECONDS")
+                        .unwrap_or_else(|_| "3600".to_string())
+                        .parse()
+                        .map_err(|_| "EMAIL_OUTBOX_RETRY_MAX_SECONDS deve ser um número")?,
+                },
+            },
+            account: AccountSettings {
+                email_change_regenerates_username: env::var("EMAIL_CHANGE_REGENERATE_USERNAME")
+                    .unwrap_or_else(|_| "false".to_string())
+                    .parse()
+                    .map_err(|_| "EMAIL_CHANGE_REGENERATE_USERNAME deve ser true ou false")?,
+                require_confirmed_email_for: parse_protected_actions(
+                    &env::var("REQUIRE_CONFIRMED_EMAIL_FOR").unwrap_or_default(),
+                )?,
+                unique_document: env::var("ACCOUNT_UNIQUE_DOCUMENT")
+                    .unwrap_or_else(|_| "false".to_string())
+                    .parse()
+                    .map_err(|_| "ACCOUNT_UNIQUE_DOCUMENT deve ser true ou false")?,
+                deletion_grace_days: env::var("ACCOUNT_DELETION_GRACE_DAYS")
+                    .unwrap_or_else(|_| "30".to_string())
+                    .parse()
+                    .map_err(|_| "ACCOUNT_DELETION_GRACE_DAYS deve ser um número")?,
+                deletion_purge_enabled: env::var("ACCOUNT_DELETION_PURGE_ENABLED")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()
+                    .map_err(|_| "ACCOUNT_DELETION_PURGE_ENABLED deve ser true ou false")?,
+                deletion_purge_interval_seconds: env::var("ACCOUNT_DELETION_PURGE_INTERVAL")
+                    .unwrap_or_else(|_| "3600".to_string())
+                    .parse()
+                    .map_err(|_| "ACCOUNT_DELETION_PURGE_INTERVAL deve ser um número")?,
+            },
+            user_tokens: UserTokenSettings {
+                confirm_email_expires_minutes: env::var("USER_TOKEN_CONFIRM_EMAIL_EXPIRES_MINUTES")
+                    .unwrap_or_else(|_| "180".to_string())
+                    .parse()
+                    .map_err(|_| "USER_TOKEN_CONFIRM_EMAIL_EXPIRES_MINUTES deve ser um número")?,
+                change_password_expires_minutes: env::var(
+                    "USER_TOKEN_CHANGE_PASSWORD_EXPIRES_MINUTES",
+                )
+                .unwrap_or_else(|_| "180".to_string())
+                .parse()
+                .map_err(|_| "USER_TOKEN_CHANGE_PASSWORD_EXPIRES_MINUTES deve ser um número")?,
+                change_email_expires_minutes: env::var("USER_TOKEN_CHANGE_EMAIL_EXPIRES_MINUTES")
+                    .unwrap_or_else(|_| "180".to_string())
+                    .parse()
+                    .map_err(|_| "USER_TOKEN_CHANGE_EMAIL_EXPIRES_MINUTES deve ser um número")?,
+                confirm_email_resend_seconds: env::var("USER_TOKEN_CONFIRM_EMAIL_RESEND_SECONDS")
+                    .unwrap_or_else(|_| "60".to_string())
+                    .parse()
+                    .map_err(|_| "USER_TOKEN_CONFIRM_EMAIL_RESEND_SECONDS deve ser um número")?,
+                purge_enabled: env::var("USER_TOKEN_PURGE_ENABLED")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()
+                    .map_err(|_| "USER_TOKEN_PURGE_ENABLED deve ser true ou false")?,
+                purge_interval_seconds: env::var("USER_TOKEN_PURGE_INTERVAL")
+                    .unwrap_or_else(|_| "3600".to_string())
+                    .parse()
+                    .map_err(|_| "USER_TOKEN_PURGE_INTERVAL deve ser um número")?,
+            },
+            password: PasswordSettings {
+                min_length: env::var("PASSWORD_MIN_LENGTH")
+                    .unwrap_or_else(|_| "8".to_string())
+                    .parse()
+                    .map_err(|_| "PASSWORD_MIN_LENGTH deve ser um número")?,
+                max_length: env::var("PASSWORD_MAX_LENGTH")
+                    .unwrap_or_else(|_| "128".to_string())
+                    .parse()
+                    .map_err(|_| "PASSWORD_MAX_LENGTH deve ser um número")?,
+                require_letter: env::var("PASSWORD_REQUIRE_LETTER")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()
+                    .map_err(|_| "PASSWORD_REQUIRE_LETTER deve ser true ou false")?,
+                require_uppercase: env::var("PASSWORD_REQUIRE_UPPERCASE")
+                    .unwrap_or_else(|_| "false".to_string())
+                    .parse()
+                    .map_err(|_| "PASSWORD_REQUIRE_UPPERCASE deve ser true ou false")?,
+                require_lowercase: env::var("PASSWORD_REQUIRE_LOWERCASE")
+                    .unwrap_or_else(|_| "false".to_string())
+                    .parse()
+                    .map_err(|_| "PASSWORD_REQUIRE_LOWERCASE deve ser true ou false")?,
+                require_digit: env::var("PASSWORD_REQUIRE_DIGIT")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()
+                    .map_err(|_| "PASSWORD_REQUIRE_DIGIT deve ser true ou false")?,
+                require_symbol: env::var("PASSWORD_REQUIRE_SYMBOL")
+                    .unwrap_or_else(|_| "false".to_string())
+                    .parse()
+                    .map_err(|_| "PASSWORD_REQUIRE_SYMBOL deve ser true ou false")?,
+                deny_common: env::var("PASSWORD_DENY_COMMON")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()
+                    .map_err(|_| "PASSWORD_DENY_COMMON deve ser true ou false")?,
+                history_size: env::var("PASSWORD_HISTORY_SIZE")
+                    .unwrap_or_else(|_| "5".to_string())
+                    .parse()
+                    .map_err(|_| "PASSWORD_HISTORY_SIZE deve ser um número")?,
+                argon2_memory_kib: env::var("PASSWORD_ARGON2_MEMORY_KIB")
+                    .unwrap_or_else(|_| "19456".to_string())
+                    .parse()
+                    .map_err(|_| "PASSWORD_ARGON2_MEMORY_KIB deve ser um número")?,
+                argon2_iterations: env::var("PASSWORD_ARGON2_ITERATIONS")
+                    .unwrap_or_else(|_| "2".to_string())
+                    .parse()
+                    .map_err(|_| "PASSWORD_ARGON2_ITERATIONS deve ser um número")?,
+                argon2_parallelism: env::var("PASSWORD_ARGON2_PARALLELISM")
+                    .unwrap_or_else(|_| "1".to_string())
+                    .parse()
+                    .map_err(|_| "PASSWORD_ARGON2_PARALLELISM deve ser um número")?,
+            },
+            storage: StorageSettings {
+                backend: env::var("STORAGE_BACKEND")
+                    .unwrap_or_else(|_| "local".to_string())
+                    .parse()?,
+                local_dir: env::var("STORAGE_LOCAL_DIR").unwrap_or_else(|_| "uploads".to_string()),
+                public_url: env::var("STORAGE_PUBLIC_URL")
+                    .ok()
+                    .filter(|url| !url.is_empty())
+                    .map(|url| url.trim_end_matches('/').to_string()),
+                s3: S3Settings {
+                    bucket: env::var("S3_BUCKET").unwrap_or_default(),
+                    region: env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+                    endpoint: env::var("S3_ENDPOINT").ok().filter(|url| !url.is_empty()),
+                    access_key_id: env::var("S3_ACCESS_KEY_ID").ok().filter(|v| !v.is_empty()),
+                    secret_access_key: env::var("S3_SECRET_ACCESS_KEY")
+                        .ok()
+                        .filter(|v| !v.is_empty()),
+                    force_path_style: env::var("S3_FORCE_PATH_STYLE")
+                        .unwrap_or_else(|_| "false".to_string())
+                        .parse()
+                        .map_err(|_| "S3_FORCE_PATH_STYLE deve ser true ou false")?,
+                },
+            },
+            avatar: AvatarSettings {
+                max_bytes: env::var("AVATAR_MAX_BYTES")
+                    .unwrap_or_else(|_| "5242880".to_string())
+                    .parse()
+                    .map_err(|_| "AVATAR_MAX_BYTES deve ser um número")?,
+                sizes: parse_avatar_sizes(
+                    &env::var("AVATAR_SIZES").unwrap_or_else(|_| "512,256,64".to_string()),
+                )?,
+            },
+            sms: SmsSettings {
+                backend: env::var("SMS_BACKEND")
+                    .unwrap_or_else(|_| "log".to_string())
+                    .parse()?,
+                from: env::var("SMS_FROM").unwrap_or_else(|_| "rust-usecases".to_string()),
+                file_dir: env::var("SMS_FILE_DIR").unwrap_or_else(|_| "tmp/sms".to_string()),
+                http_url: env::var("SMS_HTTP_URL").ok().filter(|url| !url.is_empty()),
+                http_token: env::var("SMS_HTTP_TOKEN").ok().filter(|v| !v.is_empty()),
+            },
+            phone: PhoneSettings {
+                default_region: env::var("PHONE_DEFAULT_REGION")
+                    .unwrap_or_else(|_| "BR".to_string())
+                    .to_uppercase(),
+                verification_expires_minutes: env::var("PHONE_VERIFICATION_EXPIRES_MINUTES")
+                    .unwrap_or_else(|_| "10".to_string())
+                    .parse()
+                    .map_err(|_| "PHONE_VERIFICATION_EXPIRES_MINUTES deve ser um número")?,
+                verification_max_attempts: env::var("PHONE_VERIFICATION_MAX_ATTEMPTS")
+                    .unwrap_or_else(|_| "5".to_string())
+                    .parse()
+                    .map_err(|_| "PHONE_VERIFICATION_MAX_ATTEMPTS deve ser um número")?,
+                verification_resend_seconds: env::var("PHONE_VERIFICATION_RESEND_SECONDS")
+                    .unwrap_or_else(|_| "60".to_string())
+                    .parse()
+                    .map_err(|_| "PHONE_VERIFICATION_RESEND_SECONDS deve ser um número")?,
             },
             server: ServerSettings {
                 host: env::var("SERVER_HOST")
@@ -119,6 +1069,9 @@ impl Settings {
                     .unwrap_or_else(|_| "8080".to_string())
                     .parse()
                     .map_err(|_| "SERVER_PORT deve ser um número")?,
+                trusted_proxies: parse_trusted_proxies(
+                    &env::var("TRUSTED_PROXIES").unwrap_or_default(),
+                )?,
             },
             environment,
         };
@@ -126,6 +1079,21 @@ impl Settings {
         settings
             .validate()
             .map_err(|e| format!("Configurações inválidas: {}", e))?;
+        if settings.password.min_length > settings.password.max_length {
+            return Err("PASSWORD_MIN_LENGTH não pode ser maior que PASSWORD_MAX_LENGTH".into());
+        }
+        if settings.sms.backend == SmsBackend::Http && settings.sms.http_url.is_none() {
+            return Err("SMS_HTTP_URL não definida".into());
+        }
+        if !is_valid_region(&settings.phone.default_region) {
+            return Err(format!(
+                "PHONE_DEFAULT_REGION inválida: {}",
+                settings.phone.default_region
+            ));
+        }
+        if settings.storage.backend == StorageBackend::S3 && settings.storage.s3.bucket.is_empty() {
+            return Err("S3_BUCKET não definida".into());
+        }
         Ok(settings)
     }
 
@@ -1,4 +1,5 @@
 pub mod elasticsearch;
 pub mod mongo;
 pub mod postgres;
+pub mod redis;
 pub mod test_db;
\ No newline at end of file
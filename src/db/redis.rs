@@ -0,0 +1,6 @@
+use redis::aio::ConnectionManager;
+
+pub async fn init_redis(url: &str) -> redis::RedisResult<ConnectionManager> {
+    let client = redis::Client::open(url)?;
+    ConnectionManager::new(client).await
+}
@@ -1,5 +1,8 @@
 use sqlx::PgPool;
 use std::env;
+use tokio::sync::OnceCell;
+
+static TEST_DB_READY: OnceCell<()> = OnceCell::const_new();
 
 pub async fn get_test_db_pool() -> PgPool {
     let db_url = env::var("DATABASE_URL_TEST")
@@ -26,7 +29,13 @@ pub async fn clean_test_db(db: &PgPool) {
 
 pub async fn setup_test_db() -> PgPool {
     let pool = get_test_db_pool().await;
-    run_migrations(&pool).await;
-    clean_test_db(&pool).await;
+    // Migrations e limpeza rodam uma vez por processo, para que um teste
+    // não trunque as tabelas enquanto outro ainda está rodando
+    TEST_DB_READY
+        .get_or_init(|| async {
+            run_migrations(&pool).await;
+            clean_test_db(&pool).await;
+        })
+        .await;
     pool
 }
\ No newline at end of file
@@ -1,3 +1,4 @@
+use actix_web::http::{StatusCode, header};
 use actix_web::{HttpResponse, ResponseError};
 use derive_more::Display; // essa macro implementa Display por você!
 // use std::fmt::{self, Display, Formatter};
@@ -17,9 +18,27 @@ pub enum AppError {
     #[display(fmt = "Não autorizado")]
     Unauthorized(Option<String>),
 
+    #[display(fmt = "Permissão negada")]
+    Forbidden(Option<String>),
+
     #[display(fmt = "Requisição inválida")]
     BadRequest(Option<String>),
 
+    #[display(fmt = "Conteúdo grande demais")]
+    PayloadTooLarge(Option<String>),
+
+    /// Ação que exige e-mail confirmado (`REQUIRE_CONFIRMED_EMAIL_FOR`)
+    #[display(fmt = "E-mail não confirmado")]
+    EmailNotConfirmed(Option<String>),
+
+    /// Mensagem e segundos até a próxima tentativa (header `Retry-After`)
+    #[display(fmt = "Muitas requisições")]
+    TooManyRequests(Option<String>, u64),
+
+    /// Recurso (ex: conta) temporariamente bloqueado; o segundo campo alimenta o `Retry-After`
+    #[display(fmt = "Recurso bloqueado")]
+    Locked(Option<String>, u64),
+
     #[display(fmt = "Erro interno do servidor")]
     InternalError(Option<String>),
 }
@@ -38,10 +57,30 @@ impl AppError {
         AppError::Unauthorized(Some(msg.into()))
     }
 
+    pub fn forbidden<S: Into<String>>(msg: S) -> Self {
+        AppError::Forbidden(Some(msg.into()))
+    }
+
     pub fn bad_request<S: Into<String>>(msg: S) -> Self {
         AppError::BadRequest(Some(msg.into()))
     }
 
+    pub fn payload_too_large<S: Into<String>>(msg: S) -> Self {
+        AppError::PayloadTooLarge(Some(msg.into()))
+    }
+
+    pub fn email_not_confirmed<S: Into<String>>(msg: S) -> Self {
+        AppError::EmailNotConfirmed(Some(msg.into()))
+    }
+
+    pub fn too_many_requests<S: Into<String>>(msg: S, retry_after: u64) -> Self {
+        AppError::TooManyRequests(Some(msg.into()), retry_after)
+    }
+
+    pub fn locked<S: Into<String>>(msg: S, retry_after: u64) -> Self {
+        AppError::Locked(Some(msg.into()), retry_after)
+    }
+
     pub fn internal<S: Into<String>>(msg: S) -> Self {
         AppError::InternalError(Some(msg.into()))
     }
@@ -61,9 +100,24 @@ impl ResponseError for AppError {
             AppError::Unauthorized(msg) => {
                 HttpResponse::Unauthorized().json(msg.as_deref().unwrap_or("Não autorizado"))
             }
+            AppError::Forbidden(msg) => {
+                HttpResponse::Forbidden().json(msg.as_deref().unwrap_or("Permissão negada"))
+            }
             AppError::BadRequest(msg) => {
                 HttpResponse::BadRequest().json(msg.as_deref().unwrap_or("Requisição inválida"))
             }
+            AppError::PayloadTooLarge(msg) => HttpResponse::PayloadTooLarge()
+                .json(msg.as_deref().unwrap_or("Conteúdo grande demais")),
+            AppError::EmailNotConfirmed(msg) => HttpResponse::Forbidden().json(
+                msg.as_deref()
+                    .unwrap_or("Confirme seu e-mail para realizar esta ação"),
+            ),
+            AppError::TooManyRequests(msg, retry_after) => HttpResponse::TooManyRequests()
+                .insert_header((header::RETRY_AFTER, retry_after.to_string()))
+                .json(msg.as_deref().unwrap_or("Muitas requisições")),
+            AppError::Locked(msg, retry_after) => HttpResponse::build(StatusCode::LOCKED)
+                .insert_header((header::RETRY_AFTER, retry_after.to_string()))
+                .json(msg.as_deref().unwrap_or("Recurso bloqueado")),
             AppError::InternalError(msg) => HttpResponse::InternalServerError()
                 .json(msg.as_deref().unwrap_or("Erro interno do servidor")),
         }
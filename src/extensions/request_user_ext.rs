@@ -6,7 +6,10 @@ use uuid::Uuid;
 
 pub trait RequestUserExt {
     fn user_id(&self) -> Result<Uuid, AppError>;
-    fn access_level(&self) -> Result<String, AppError>;
+    fn roles(&self) -> Result<Vec<String>, AppError>;
+    fn has_permission(&self, permission: &str) -> bool;
+    fn require_permission(&self, permission: &str) -> Result<(), AppError>;
+    fn session_id(&self) -> Result<Uuid, AppError>;
 }
 
 impl RequestUserExt for HttpRequest {
@@ -18,10 +21,30 @@ impl RequestUserExt for HttpRequest {
 
         Uuid::parse_str(&claims.sub).map_err(|_| AppError::Unauthorized(None))
     }
-    fn access_level(&self) -> Result<String, AppError> {
+    fn roles(&self) -> Result<Vec<String>, AppError> {
         self.extensions()
             .get::<Claims>()
-            .map(|claims| claims.access_level.clone())
+            .map(|claims| claims.roles.clone())
             .ok_or(AppError::Unauthorized(Some("Token inválido".into())))
     }
+    fn has_permission(&self, permission: &str) -> bool {
+        self.extensions()
+            .get::<Claims>()
+            .is_some_and(|claims| claims.permissions.iter().any(|p| p == permission))
+    }
+    fn require_permission(&self, permission: &str) -> Result<(), AppError> {
+        if self.has_permission(permission) {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden(Some("Permissão negada".into())))
+        }
+    }
+    fn session_id(&self) -> Result<Uuid, AppError> {
+        let extensions = self.extensions();
+        let claims = extensions
+            .get::<Claims>()
+            .ok_or(AppError::Unauthorized(None))?;
+
+        Uuid::parse_str(&claims.sid).map_err(|_| AppError::Unauthorized(None))
+    }
 }
@@ -3,12 +3,16 @@ pub mod db;
 pub mod errors;
 pub mod extensions;
 pub mod logs;
+pub mod mailer;
 pub mod middleware;
 pub mod models;
 pub mod repositories;
 pub mod routes;
 pub mod server;
 pub mod services;
+pub mod sms;
+pub mod storage;
+pub mod stores;
 pub mod utils;
 pub mod websocket;
 
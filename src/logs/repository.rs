@@ -1,7 +1,9 @@
 use crate::logs::model::{LogEntry, LogQuery};
 use futures::StreamExt;
 use mongodb::Database;
-use mongodb::{bson::doc, options::FindOptions};
+use mongodb::bson::{Bson, doc, to_bson};
+use mongodb::options::FindOptions;
+use uuid::Uuid;
 
 pub async fn find_logs(
     db: &Database,
@@ -29,3 +31,35 @@ pub async fn find_logs(
 
     Ok(logs)
 }
+
+pub async fn find_logs_by_user(
+    db: &Database,
+    user_id: Uuid,
+) -> Result<Vec<LogEntry>, mongodb::error::Error> {
+    let collection = db.collection::<LogEntry>("logs");
+    let filter = doc! { "user_id": user_filter(user_id)? };
+    let options = FindOptions::builder()
+        .sort(doc! { "timestamp": -1 })
+        .build();
+
+    let cursor = collection.find(filter, options).await?;
+
+    Ok(cursor.filter_map(|doc| async { doc.ok() }).collect().await)
+}
+
+pub async fn delete_logs_by_user(
+    db: &Database,
+    user_id: Uuid,
+) -> Result<u64, mongodb::error::Error> {
+    let result = db
+        .collection::<LogEntry>("logs")
+        .delete_many(doc! { "user_id": user_filter(user_id)? }, None)
+        .await?;
+
+    Ok(result.deleted_count)
+}
+
+/// `user_id` é gravado pelo serializer do `LogEntry`; o filtro usa a mesma representação
+fn user_filter(user_id: Uuid) -> Result<Bson, mongodb::error::Error> {
+    to_bson(&user_id).map_err(|e| mongodb::error::Error::custom(e.to_string()))
+}
@@ -0,0 +1,40 @@
+use super::{EmailMessage, EmailSender, build_mime_message};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::path::PathBuf;
+use tracing::info;
+use uuid::Uuid;
+
+/// Grava cada mensagem como `.eml` em um diretório, para abrir em qualquer cliente de e-mail
+pub struct FileEmailSender {
+    dir: PathBuf,
+    from: String,
+}
+
+impl FileEmailSender {
+    pub fn new(dir: &str, from: &str) -> Self {
+        Self {
+            dir: PathBuf::from(dir),
+            from: from.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl EmailSender for FileEmailSender {
+    async fn send(&self, message: &EmailMessage) -> anyhow::Result<String> {
+        let mime = build_mime_message(&self.from, message)?;
+
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let file_name = format!(
+            "{}-{}.eml",
+            Utc::now().format("%Y%m%d%H%M%S"),
+            Uuid::new_v4()
+        );
+        let path = self.dir.join(&file_name);
+        tokio::fs::write(&path, mime.formatted()).await?;
+
+        info!(to = %message.to, path = %path.display(), "E-mail gravado em arquivo");
+        Ok(file_name)
+    }
+}
@@ -0,0 +1,39 @@
+use super::{EmailMessage, EmailSender};
+use async_trait::async_trait;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Guarda as mensagens enviadas para que os testes possam inspecioná-las
+#[derive(Default)]
+pub struct InMemoryEmailSender {
+    messages: Mutex<Vec<EmailMessage>>,
+}
+
+impl InMemoryEmailSender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn messages(&self) -> Vec<EmailMessage> {
+        self.messages.lock().unwrap().clone()
+    }
+
+    /// Mensagens enviadas para um destinatário, na ordem de envio
+    pub fn messages_to(&self, to: &str) -> Vec<EmailMessage> {
+        self.messages
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|message| message.to == to)
+            .cloned()
+            .collect()
+    }
+}
+
+#[async_trait]
+impl EmailSender for InMemoryEmailSender {
+    async fn send(&self, message: &EmailMessage) -> anyhow::Result<String> {
+        self.messages.lock().unwrap().push(message.clone());
+        Ok(Uuid::new_v4().to_string())
+    }
+}
@@ -0,0 +1,91 @@
+pub mod file;
+pub mod memory;
+pub mod ses;
+pub mod smtp;
+pub mod templates;
+
+use crate::config::{EmailBackend, EmailSettings};
+use async_trait::async_trait;
+use lettre::message::header::{ContentType, HeaderName, HeaderValue};
+use lettre::message::{Mailbox, MultiPart};
+use serde::Serialize;
+use std::sync::Arc;
+
+pub use file::FileEmailSender;
+pub use memory::InMemoryEmailSender;
+pub use ses::SesEmailSender;
+pub use smtp::SmtpEmailSender;
+pub use templates::{EmailTemplate, EmailTemplates, RenderedEmail};
+
+/// Mensagem já renderizada, pronta para entrega
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub html: String,
+    /// Alternativa em texto puro para clientes que não exibem HTML
+    pub text: Option<String>,
+    /// URL de descadastro em um clique (RFC 8058), só em e-mails não transacionais
+    pub list_unsubscribe: Option<String>,
+}
+
+/// Cabeçalhos `List-Unsubscribe` e `List-Unsubscribe-Post` da RFC 8058
+pub(crate) fn list_unsubscribe_headers(url: &str) -> [(&'static str, String); 2] {
+    [
+        ("List-Unsubscribe", format!("<{}>", url)),
+        (
+            "List-Unsubscribe-Post",
+            "List-Unsubscribe=One-Click".to_string(),
+        ),
+    ]
+}
+
+#[async_trait]
+pub trait EmailSender: Send + Sync {
+    /// Entrega a mensagem e retorna o identificador atribuído pelo provedor
+    async fn send(&self, message: &EmailMessage) -> anyhow::Result<String>;
+}
+
+/// Monta a mensagem MIME usada pelos backends SMTP e de arquivo
+pub(crate) fn build_mime_message(
+    from: &str,
+    message: &EmailMessage,
+) -> anyhow::Result<lettre::Message> {
+    let from: Mailbox = from.parse()?;
+    let to: Mailbox = message.to.parse()?;
+
+    let mut builder = lettre::Message::builder()
+        .from(from)
+        .to(to)
+        .subject(&message.subject);
+    if let Some(url) = &message.list_unsubscribe {
+        for (name, value) in list_unsubscribe_headers(url) {
+            builder = builder.raw_header(HeaderValue::new(
+                HeaderName::new_from_ascii_str(name),
+                value,
+            ));
+        }
+    }
+
+    Ok(match &message.text {
+        Some(text) => builder.multipart(MultiPart::alternative_plain_html(
+            text.clone(),
+            message.html.clone(),
+        ))?,
+        None => builder
+            .header(ContentType::TEXT_HTML)
+            .body(message.html.clone())?,
+    })
+}
+
+pub fn build_email_sender(settings: &EmailSettings) -> anyhow::Result<Arc<dyn EmailSender>> {
+    Ok(match settings.backend {
+        EmailBackend::Ses => Arc::new(SesEmailSender::from_env(&settings.from_email)?),
+        EmailBackend::Smtp => Arc::new(SmtpEmailSender::new(&settings.smtp, &settings.from_email)?),
+        EmailBackend::File => Arc::new(FileEmailSender::new(
+            &settings.file_dir,
+            &settings.from_email,
+        )),
+        EmailBackend::Memory => Arc::new(InMemoryEmailSender::new()),
+    })
+}
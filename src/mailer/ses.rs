@@ -0,0 +1,95 @@
+use super::{EmailMessage, EmailSender, list_unsubscribe_headers};
+use anyhow::Context;
+use async_trait::async_trait;
+use aws_sdk_sesv2::Client;
+use aws_sdk_sesv2::config::Credentials;
+use aws_sdk_sesv2::config::{BehaviorVersion, Config};
+use aws_sdk_sesv2::types::{Body, Content, Destination, EmailContent, Message, MessageHeader};
+use std::env;
+
+pub struct SesEmailSender {
+    ses_client: Client,
+    sender_email: String,
+}
+
+impl SesEmailSender {
+    /// Credenciais lidas de `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` e `AWS_REGION`
+    pub fn from_env(sender_email: &str) -> anyhow::Result<Self> {
+        let access_key = env::var("AWS_ACCESS_KEY_ID").context("AWS_ACCESS_KEY_ID não definida")?;
+        let secret_key =
+            env::var("AWS_SECRET_ACCESS_KEY").context("AWS_SECRET_ACCESS_KEY não definida")?;
+        let region = env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+
+        let credentials = Credentials::new(&access_key, &secret_key, None, None, "static");
+
+        let config = Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .credentials_provider(credentials)
+            .region(aws_sdk_sesv2::config::Region::new(region))
+            .build();
+
+        Ok(Self {
+            ses_client: Client::from_conf(config),
+            sender_email: sender_email.to_string(),
+        })
+    }
+}
+
+fn utf8_content(data: &str) -> anyhow::Result<Content> {
+    Content::builder()
+        .data(data)
+        .charset("UTF-8")
+        .build()
+        .context("Falha ao preparar o email")
+}
+
+#[async_trait]
+impl EmailSender for SesEmailSender {
+    async fn send(&self, message: &EmailMessage) -> anyhow::Result<String> {
+        let destination = Destination::builder().to_addresses(&message.to).build();
+
+        let headers = message
+            .list_unsubscribe
+            .as_deref()
+            .map(|url| {
+                list_unsubscribe_headers(url)
+                    .into_iter()
+                    .map(|(name, value)| {
+                        MessageHeader::builder()
+                            .name(name)
+                            .value(value)
+                            .build()
+                            .context("Falha ao preparar o email")
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()
+            })
+            .transpose()?;
+
+        let content = EmailContent::builder()
+            .simple(
+                Message::builder()
+                    .subject(utf8_content(&message.subject)?)
+                    .body(
+                        Body::builder()
+                            .html(utf8_content(&message.html)?)
+                            .set_text(message.text.as_deref().map(utf8_content).transpose()?)
+                            .build(),
+                    )
+                    .set_headers(headers)
+                    .build(),
+            )
+            .build();
+
+        let output = self
+            .ses_client
+            .send_email()
+            .from_email_address(&self.sender_email)
+            .destination(destination)
+            .content(content)
+            .send()
+            .await
+            .context("Erro ao enviar e-mail pelo SES")?;
+
+        Ok(output.message_id().unwrap_or_default().to_string())
+    }
+}
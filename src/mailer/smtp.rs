@@ -0,0 +1,40 @@
+use super::{EmailMessage, EmailSender, build_mime_message};
+use crate::config::SmtpSettings;
+use async_trait::async_trait;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+
+pub struct SmtpEmailSender {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: String,
+}
+
+impl SmtpEmailSender {
+    pub fn new(settings: &SmtpSettings, from: &str) -> anyhow::Result<Self> {
+        let mut builder = if settings.starttls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.host)?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.host)
+        }
+        .port(settings.port);
+
+        if let (Some(username), Some(password)) = (&settings.username, &settings.password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: from.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl EmailSender for SmtpEmailSender {
+    async fn send(&self, message: &EmailMessage) -> anyhow::Result<String> {
+        let mime = build_mime_message(&self.from, message)?;
+        let response = self.transport.send(mime).await?;
+        // a resposta do servidor costuma trazer o id da fila (ex: "2.0.0 Ok: queued as 4F2A1")
+        Ok(response.message().collect::<Vec<_>>().join(" "))
+    }
+}
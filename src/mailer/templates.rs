@@ -0,0 +1,257 @@
+use crate::config::EmailSettings;
+use crate::models::email_preferences::email_categories;
+use crate::models::profile::languages;
+use anyhow::{Context as _, bail};
+use tera::{Context, Tera};
+
+/// E-mails enviados pela aplicação. Cada um tem `<idioma>/<nome>.html` e `<idioma>/<nome>.txt`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailTemplate {
+    ConfirmEmail,
+    ResetPassword,
+    CourseAnnouncement,
+    ChangeEmail,
+    EmailChangeNotice,
+    PasswordChanged,
+}
+
+impl EmailTemplate {
+    pub const ALL: [EmailTemplate; 6] = [
+        EmailTemplate::ConfirmEmail,
+        EmailTemplate::ResetPassword,
+        EmailTemplate::CourseAnnouncement,
+        EmailTemplate::ChangeEmail,
+        EmailTemplate::EmailChangeNotice,
+        EmailTemplate::PasswordChanged,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EmailTemplate::ConfirmEmail => "confirm_email",
+            EmailTemplate::ResetPassword => "reset_password",
+            EmailTemplate::CourseAnnouncement => "course_announcement",
+            EmailTemplate::ChangeEmail => "change_email",
+            EmailTemplate::EmailChangeNotice => "email_change_notice",
+            EmailTemplate::PasswordChanged => "password_changed",
+        }
+    }
+
+    /// Categoria usada para respeitar as preferências de e-mail do destinatário
+    pub fn category(&self) -> &'static str {
+        match self {
+            EmailTemplate::ConfirmEmail
+            | EmailTemplate::ResetPassword
+            | EmailTemplate::ChangeEmail
+            | EmailTemplate::EmailChangeNotice
+            | EmailTemplate::PasswordChanged => email_categories::TRANSACTIONAL,
+            EmailTemplate::CourseAnnouncement => email_categories::COURSE_ANNOUNCEMENTS,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|template| template.name() == name)
+    }
+
+    fn subject(&self, language: &str) -> &'static str {
+        match (self, language) {
+            (EmailTemplate::ConfirmEmail, languages::EN) => "✅ Confirm your e-mail",
+            (EmailTemplate::ConfirmEmail, _) => "✅ Confirme seu e-mail",
+            (EmailTemplate::ResetPassword, languages::EN) => "🔐 Reset your password",
+            (EmailTemplate::ResetPassword, _) => "🔐 Redefina sua senha",
+            (EmailTemplate::CourseAnnouncement, languages::EN) => "📚 New course available",
+            (EmailTemplate::CourseAnnouncement, _) => "📚 Novo curso disponível",
+            (EmailTemplate::ChangeEmail, languages::EN) => "✉️ Confirm your new e-mail",
+            (EmailTemplate::ChangeEmail, _) => "✉️ Confirme seu novo e-mail",
+            (EmailTemplate::EmailChangeNotice, languages::EN) => {
+                "⚠️ Your account e-mail is changing"
+            }
+            (EmailTemplate::EmailChangeNotice, _) => "⚠️ O e-mail da sua conta está sendo alterado",
+            (EmailTemplate::PasswordChanged, languages::EN) => "🔐 Your password was changed",
+            (EmailTemplate::PasswordChanged, _) => "🔐 Sua senha foi alterada",
+        }
+    }
+}
+
+/// Assunto e corpos renderizados de um e-mail
+#[derive(Debug, Clone)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+/// Templates compilados no binário, para o servidor funcionar a partir de qualquer diretório
+const EMBEDDED_TEMPLATES: &[(&str, &str)] = &[
+    (
+        "pt-BR/confirm_email.html",
+        include_str!("../templates/emails/pt-BR/confirm_email.html"),
+    ),
+    (
+        "pt-BR/confirm_email.txt",
+        include_str!("../templates/emails/pt-BR/confirm_email.txt"),
+    ),
+    (
+        "pt-BR/reset_password.html",
+        include_str!("../templates/emails/pt-BR/reset_password.html"),
+    ),
+    (
+        "pt-BR/reset_password.txt",
+        include_str!("../templates/emails/pt-BR/reset_password.txt"),
+    ),
+    (
+        "pt-BR/course_announcement.html",
+        include_str!("../templates/emails/pt-BR/course_announcement.html"),
+    ),
+    (
+        "pt-BR/course_announcement.txt",
+        include_str!("../templates/emails/pt-BR/course_announcement.txt"),
+    ),
+    (
+        "pt-BR/change_email.html",
+        include_str!("../templates/emails/pt-BR/change_email.html"),
+    ),
+    (
+        "pt-BR/change_email.txt",
+        include_str!("../templates/emails/pt-BR/change_email.txt"),
+    ),
+    (
+        "pt-BR/email_change_notice.html",
+        include_str!("../templates/emails/pt-BR/email_change_notice.html"),
+    ),
+    (
+        "pt-BR/email_change_notice.txt",
+        include_str!("../templates/emails/pt-BR/email_change_notice.txt"),
+    ),
+    (
+        "pt-BR/password_changed.html",
+        include_str!("../templates/emails/pt-BR/password_changed.html"),
+    ),
+    (
+        "pt-BR/password_changed.txt",
+        include_str!("../templates/emails/pt-BR/password_changed.txt"),
+    ),
+    (
+        "en/confirm_email.html",
+        include_str!("../templates/emails/en/confirm_email.html"),
+    ),
+    (
+        "en/confirm_email.txt",
+        include_str!("../templates/emails/en/confirm_email.txt"),
+    ),
+    (
+        "en/reset_password.html",
+        include_str!("../templates/emails/en/reset_password.html"),
+    ),
+    (
+        "en/reset_password.txt",
+        include_str!("../templates/emails/en/reset_password.txt"),
+    ),
+    (
+        "en/course_announcement.html",
+        include_str!("../templates/emails/en/course_announcement.html"),
+    ),
+    (
+        "en/course_announcement.txt",
+        include_str!("../templates/emails/en/course_announcement.txt"),
+    ),
+    (
+        "en/change_email.html",
+        include_str!("../templates/emails/en/change_email.html"),
+    ),
+    (
+        "en/change_email.txt",
+        include_str!("../templates/emails/en/change_email.txt"),
+    ),
+    (
+        "en/email_change_notice.html",
+        include_str!("../templates/emails/en/email_change_notice.html"),
+    ),
+    (
+        "en/email_change_notice.txt",
+        include_str!("../templates/emails/en/email_change_notice.txt"),
+    ),
+    (
+        "en/password_changed.html",
+        include_str!("../templates/emails/en/password_changed.html"),
+    ),
+    (
+        "en/password_changed.txt",
+        include_str!("../templates/emails/en/password_changed.txt"),
+    ),
+];
+
+pub struct EmailTemplates {
+    tera: Tera,
+}
+
+impl EmailTemplates {
+    pub fn embedded() -> anyhow::Result<Self> {
+        let mut tera = Tera::default();
+        tera.add_raw_templates(EMBEDDED_TEMPLATES.iter().copied())
+            .context("Erro ao carregar os templates de e-mail embutidos")?;
+
+        Self::checked(tera)
+    }
+
+    /// Carrega os templates de `dir` (ex.: `src/templates/emails`), permitindo editá-los sem
+    /// recompilar
+    pub fn from_dir(dir: &str) -> anyhow::Result<Self> {
+        let glob = format!("{}/**/*", dir.trim_end_matches('/'));
+        let tera = Tera::new(&glob)
+            .with_context(|| format!("Erro ao carregar os templates de e-mail de '{}'", dir))?;
+
+        Self::checked(tera)
+    }
+
+    pub fn from_settings(settings: &EmailSettings) -> anyhow::Result<Self> {
+        match &settings.templates_dir {
+            Some(dir) => Self::from_dir(dir),
+            None => Self::embedded(),
+        }
+    }
+
+    /// O idioma padrão precisa ter todos os templates, pois é o fallback dos demais
+    fn checked(tera: Tera) -> anyhow::Result<Self> {
+        let loaded: Vec<&str> = tera.get_template_names().collect();
+        for template in EmailTemplate::ALL {
+            for extension in ["html", "txt"] {
+                let name = format!("{}/{}.{}", languages::PT_BR, template.name(), extension);
+                if !loaded.contains(&name.as_str()) {
+                    bail!("Template de e-mail '{}' não encontrado", name);
+                }
+            }
+        }
+
+        Ok(Self { tera })
+    }
+
+    /// Renderiza no idioma pedido, caindo para pt-BR quando o idioma não tem o template
+    pub fn render(
+        &self,
+        template: EmailTemplate,
+        language: &str,
+        ctx: &Context,
+    ) -> anyhow::Result<RenderedEmail> {
+        let html_name = format!("{}/{}.html", language, template.name());
+        let language = if self.tera.get_template_names().any(|name| name == html_name) {
+            language
+        } else {
+            languages::PT_BR
+        };
+
+        let render = |extension: &str| {
+            let name = format!("{}/{}.{}", language, template.name(), extension);
+            self.tera
+                .render(&name, ctx)
+                .with_context(|| format!("Erro ao renderizar o template '{}'", name))
+        };
+
+        Ok(RenderedEmail {
+            subject: template.subject(language).to_string(),
+            html: render("html")?,
+            text: render("txt")?,
+        })
+    }
+}
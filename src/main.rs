@@ -4,7 +4,7 @@ mod commands;
 
 use clap::Parser;
 use cli::{Cli, Commands};
-use commands::{migrate, sync_courses};
+use commands::{announce_course, migrate, preview_email, sync_courses};
 use dotenvy::dotenv;
 use rust_usecases::server::start_server;
 
@@ -27,6 +27,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         Commands::Seed => {
             println!("(TODO) Seed ainda não implementado.");
         }
+        Commands::PreviewEmail {
+            template,
+            language,
+            text,
+        } => {
+            preview_email::run(&template, &language, text).await?;
+        }
+        Commands::AnnounceCourse {
+            course_id,
+            batch_size,
+        } => {
+            announce_course::run(course_id, batch_size).await?;
+        }
     }
 
     Ok(())
@@ -1,15 +1,39 @@
-use crate::config::get_settings;
-use crate::models::auth::Claims;
+use crate::config::app_state::AppState;
+use crate::errors::app_error::AppError;
+use crate::services::{account_policy_service, api_key_service, auth_service};
 use actix_web::body::BoxBody;
 use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
-use actix_web::{Error, HttpMessage, HttpResponse};
+use actix_web::{Error, HttpMessage, HttpResponse, ResponseError, web};
 use futures::future::{LocalBoxFuture, Ready, ok};
-use jsonwebtoken::{DecodingKey, Validation, decode};
 use std::rc::Rc;
 use tracing::{error, info, warn};
+use uuid::Uuid;
 
 pub struct AuthMiddleware;
 
+enum Credential {
+    Jwt(String),
+    ApiKey(String),
+}
+
+impl Credential {
+    /// `Token <jwt>` para usuários, `ApiKey <chave>` para integrações
+    fn from_header(header: &str) -> Option<Self> {
+        let credential = if let Some(token) = header.strip_prefix("Token ") {
+            Credential::Jwt(token.to_string())
+        } else if let Some(key) = header.strip_prefix("ApiKey ") {
+            Credential::ApiKey(key.to_string())
+        } else {
+            return None;
+        };
+
+        match &credential {
+            Credential::Jwt(value) | Credential::ApiKey(value) if value.is_empty() => None,
+            _ => Some(credential),
+        }
+    }
+}
+
 impl<S> Transform<S, ServiceRequest> for AuthMiddleware
 where
     S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = Error> + 'static,
@@ -48,43 +72,61 @@ where
             .and_then(|h| h.to_str().ok())
             .unwrap_or("");
 
-        let token = auth_header.strip_prefix("Token ").unwrap_or("");
-
-        if token.is_empty() {
+        let Some(credential) = Credential::from_header(auth_header) else {
             warn!("Tentativa de acesso sem token");
             let response = HttpResponse::Unauthorized()
                 .body("Token não fornecido")
                 .map_into_boxed_body();
             return Box::pin(async { Ok(req.into_response(response)) });
-        }
+        };
 
-        let settings = get_settings();
-        let jwt_secret = &settings.jwt.secret;
+        let state = req.app_data::<web::Data<AppState>>().cloned();
+        let service = Rc::clone(&self.service);
 
-        match decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(jwt_secret.as_bytes()),
-            &Validation::default(),
-        ) {
-            Ok(token_data) => {
-                info!(
-                    user_id = %token_data.claims.sub,
-                    "Token válido"
-                );
-                req.extensions_mut().insert(token_data.claims);
-                let fut = self.service.call(req);
-                Box::pin(async move { fut.await })
-            }
-            Err(err) => {
-                error!(
-                    error = %err,
-                    "Token inválido"
-                );
-                let response = HttpResponse::Unauthorized()
-                    .body("Token inválido")
+        Box::pin(async move {
+            let Some(state) = state else {
+                error!("AppState não configurado no App");
+                let response = HttpResponse::InternalServerError()
+                    .body("Erro interno do servidor")
                     .map_into_boxed_body();
-                Box::pin(async { Ok(req.into_response(response)) })
+                return Ok(req.into_response(response));
+            };
+
+            let result = match &credential {
+                Credential::Jwt(token) => {
+                    auth_service::validate_access_token(token, &state.db).await
+                }
+                Credential::ApiKey(key) => api_key_service::authenticate(key, &state.db).await,
+            };
+            // a suspensão vale também para tokens e chaves emitidos antes dela
+            let result = match result {
+                Ok(claims) => match Uuid::parse_str(&claims.sub) {
+                    Ok(user_id) => account_policy_service::ensure_not_suspended(user_id, &state.db)
+                        .await
+                        .map(|_| claims),
+                    Err(_) => Err(AppError::Unauthorized(Some("Token inválido".into()))),
+                },
+                Err(err) => Err(err),
+            };
+
+            match result {
+                Ok(claims) => {
+                    info!(
+                        user_id = %claims.sub,
+                        "Token válido"
+                    );
+                    req.extensions_mut().insert(claims);
+                    service.call(req).await
+                }
+                Err(err) => {
+                    error!(
+                        error = ?err,
+                        "Token inválido"
+                    );
+                    let response = err.error_response().map_into_boxed_body();
+                    Ok(req.into_response(response))
+                }
             }
-        }
+        })
     }
 }
@@ -0,0 +1,142 @@
+use crate::errors::app_error::AppError;
+use crate::models::auth::Claims;
+use actix_web::body::BoxBody;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::{Error, HttpMessage, ResponseError};
+use futures::future::{LocalBoxFuture, Ready, ok};
+use std::rc::Rc;
+use tracing::warn;
+
+/// Exige que o usuário autenticado tenha a permissão informada.
+/// Deve rodar dentro do `AuthMiddleware`, que injeta as `Claims` na request.
+///
+/// ```text
+/// #[post("/courses/", wrap = "RequirePermission::new(permissions::COURSES_WRITE)")]
+/// ```
+pub struct RequirePermission {
+    permission: &'static str,
+}
+
+impl RequirePermission {
+    pub fn new(permission: &'static str) -> Self {
+        Self { permission }
+    }
+}
+
+impl<S> Transform<S, ServiceRequest> for RequirePermission
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = Error> + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequirePermissionService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequirePermissionService {
+            service: Rc::new(service),
+            permission: self.permission,
+        })
+    }
+}
+
+pub struct RequirePermissionService<S> {
+    service: Rc<S>,
+    permission: &'static str,
+}
+
+impl<S> Service<ServiceRequest> for RequirePermissionService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = Error> + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let allowed = req
+            .extensions()
+            .get::<Claims>()
+            .map(|claims| claims.permissions.iter().any(|p| p == self.permission));
+
+        match allowed {
+            Some(true) => Box::pin(self.service.call(req)),
+            Some(false) => {
+                warn!(permission = self.permission, "Permissão negada");
+                let response =
+                    AppError::Forbidden(Some("Permissão negada".into())).error_response();
+                Box::pin(async { Ok(req.into_response(response)) })
+            }
+            None => {
+                let response = AppError::Unauthorized(None).error_response();
+                Box::pin(async { Ok(req.into_response(response)) })
+            }
+        }
+    }
+}
+
+/// Recusa chaves de API. Vale para rotas da própria conta e de credenciais (troca de e-mail e
+/// senha, exclusão, exportação, gestão de chaves), que não têm permissão própria a conferir e
+/// não devem ficar ao alcance de uma chave vazada.
+///
+/// ```text
+/// #[post("/me/password/", wrap = "RejectApiKey")]
+/// ```
+pub struct RejectApiKey;
+
+impl<S> Transform<S, ServiceRequest> for RejectApiKey
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = Error> + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RejectApiKeyService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RejectApiKeyService {
+            service: Rc::new(service),
+        })
+    }
+}
+
+pub struct RejectApiKeyService<S> {
+    service: Rc<S>,
+}
+
+impl<S> Service<ServiceRequest> for RejectApiKeyService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = Error> + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let api_key = req
+            .extensions()
+            .get::<Claims>()
+            .map(|claims| claims.api_key);
+
+        match api_key {
+            Some(false) => Box::pin(self.service.call(req)),
+            Some(true) => {
+                warn!(path = req.path(), "Chave de API recusada em rota da conta");
+                let response =
+                    AppError::Forbidden(Some("Chaves de API não podem acessar esta rota".into()))
+                        .error_response();
+                Box::pin(async { Ok(req.into_response(response)) })
+            }
+            None => {
+                let response = AppError::Unauthorized(None).error_response();
+                Box::pin(async { Ok(req.into_response(response)) })
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use crate::config::app_state::AppState;
+use crate::config::{RateLimitRule, get_settings};
+use crate::errors::app_error::AppError;
+use crate::models::auth::Claims;
+use crate::stores::rate_limit_store::RateLimitDecision;
+use crate::utils::client_ip::client_ip;
+use actix_web::body::BoxBody;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::http::header::{HeaderMap, HeaderName, HeaderValue};
+use actix_web::{Error, HttpMessage, ResponseError, web};
+use futures::future::{LocalBoxFuture, Ready, ok};
+use std::rc::Rc;
+use tracing::{error, warn};
+
+/// Grupos de rotas com limites próprios, configurados em `Settings.rate_limit`
+#[derive(Debug, Clone, Copy)]
+pub enum RateLimitGroup {
+    Auth,
+    Public,
+    User,
+}
+
+impl RateLimitGroup {
+    fn name(&self) -> &'static str {
+        match self {
+            RateLimitGroup::Auth => "auth",
+            RateLimitGroup::Public => "public",
+            RateLimitGroup::User => "user",
+        }
+    }
+
+    fn rule(&self) -> &'static RateLimitRule {
+        let settings = &get_settings().rate_limit;
+        match self {
+            RateLimitGroup::Auth => &settings.auth,
+            RateLimitGroup::Public => &settings.public,
+            RateLimitGroup::User => &settings.user,
+        }
+    }
+}
+
+/// Rate limiter (token bucket) por grupo de rotas. A chave é o `Claims.sub`
+/// quando a request já passou pelo `AuthMiddleware` e o IP do cliente caso contrário.
+///
+/// ```text
+/// #[post("/login/", wrap = "RateLimiter::new(RateLimitGroup::Auth)")]
+/// ```
+pub struct RateLimiter {
+    group: RateLimitGroup,
+    rule: Option<RateLimitRule>,
+}
+
+impl RateLimiter {
+    pub fn new(group: RateLimitGroup) -> Self {
+        Self { group, rule: None }
+    }
+
+    /// Usa um limite fixo em vez do configurado para o grupo
+    pub fn with_rule(group: RateLimitGroup, rule: RateLimitRule) -> Self {
+        Self {
+            group,
+            rule: Some(rule),
+        }
+    }
+}
+
+impl<S> Transform<S, ServiceRequest> for RateLimiter
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = Error> + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RateLimiterService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RateLimiterService {
+            service: Rc::new(service),
+            group: self.group,
+            rule: self.rule.clone(),
+        })
+    }
+}
+
+pub struct RateLimiterService<S> {
+    service: Rc<S>,
+    group: RateLimitGroup,
+    rule: Option<RateLimitRule>,
+}
+
+impl<S> Service<ServiceRequest> for RateLimiterService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = Error> + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        let rule = match &self.rule {
+            Some(rule) => rule.clone(),
+            None if get_settings().rate_limit.enabled => self.group.rule().clone(),
+            None => return Box::pin(service.call(req)),
+        };
+
+        let user_sub = req
+            .extensions()
+            .get::<Claims>()
+            .map(|claims| claims.sub.clone());
+        let subject = match user_sub {
+            Some(sub) => format!("user:{}", sub),
+            None => format!(
+                "ip:{}",
+                client_ip(req.request()).unwrap_or_else(|| "unknown".to_string())
+            ),
+        };
+        let key = format!("rate_limit:{}:{}", self.group.name(), subject);
+        let state = req.app_data::<web::Data<AppState>>().cloned();
+
+        Box::pin(async move {
+            let Some(state) = state else {
+                error!("AppState não configurado no App");
+                return service.call(req).await;
+            };
+
+            let refill_per_second = rule.requests as f64 / rule.period_seconds as f64;
+            let decision = match state
+                .rate_limits
+                .take(&key, rule.requests, refill_per_second)
+                .await
+            {
+                Ok(decision) => decision,
+                Err(err) => {
+                    // Falha no backend não derruba a API
+                    error!(error = %err, "Erro ao consultar o rate limit");
+                    return service.call(req).await;
+                }
+            };
+
+            if !decision.allowed {
+                warn!(key = %key, "Limite de requisições excedido");
+                let mut response = AppError::too_many_requests(
+                    "Limite de requisições excedido. Tente novamente mais tarde",
+                    decision.retry_after,
+                )
+                .error_response();
+                insert_rate_limit_headers(response.headers_mut(), &decision);
+                return Ok(req.into_response(response));
+            }
+
+            let mut res = service.call(req).await?;
+            insert_rate_limit_headers(res.headers_mut(), &decision);
+            Ok(res)
+        })
+    }
+}
+
+fn insert_rate_limit_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    headers.insert(
+        HeaderName::from_static("x-ratelimit-limit"),
+        HeaderValue::from(decision.limit),
+    );
+    headers.insert(
+        HeaderName::from_static("x-ratelimit-remaining"),
+        HeaderValue::from(decision.remaining),
+    );
+    headers.insert(
+        HeaderName::from_static("x-ratelimit-reset"),
+        HeaderValue::from(decision.reset_after),
+    );
+}
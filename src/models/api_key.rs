@@ -0,0 +1,47 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Prefixo fixo das chaves, para que scanners de segredos consigam reconhecê-las
+pub const API_KEY_PREFIX: &str = "rk";
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<NaiveDateTime>,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>,
+    pub created_by: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ApiKeyRequest {
+    #[validate(length(min = 1, max = 100, message = "Nome deve ter entre 1 e 100 caracteres"))]
+    pub name: String,
+    /// Permissões concedidas à chave; precisam estar entre as permissões do dono
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiKeyExpiryRequest {
+    /// `null` remove a expiração
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+/// Resposta da criação: a chave completa só é exibida neste momento
+#[derive(Debug, Serialize)]
+pub struct ApiKeyCreatedResponse {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
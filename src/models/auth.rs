@@ -3,11 +3,27 @@ use crate::utils::validation::validate_password;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
+/// Ações que podem exigir e-mail confirmado (`REQUIRE_CONFIRMED_EMAIL_FOR`)
+pub mod protected_actions {
+    pub const CREATE_COURSE: &str = "create_course";
+    pub const WEBSOCKET: &str = "websocket";
+    pub const ALL: [&str; 2] = [CREATE_COURSE, WEBSOCKET];
+}
+
+/// Audiência (`aud`) dos tokens de acesso; os demais tokens assinados pela mesma chave usam outra
+pub const ACCESS_TOKEN_AUDIENCE: &str = "access";
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String, // ID do usuário ou email
     pub exp: usize,  // timestamp de expiração
-    pub access_level: String,
+    pub roles: Vec<String>,       // papéis do usuário (ex: "admin", "user")
+    pub permissions: Vec<String>, // permissões derivadas dos papéis (ex: "courses:write")
+    pub jti: String, // ID único do token, usado na lista de revogação
+    pub sid: String, // ID da sessão (dispositivo) que emitiu o token
+    /// Credencial é uma chave de API; nunca vem de um JWT, só de `api_key_service::authenticate`
+    #[serde(skip)]
+    pub api_key: bool,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -15,7 +31,8 @@ pub struct LoginRequest {
     #[validate(email)]
     pub email: String,
 
-    #[validate(custom = "validate_password")]
+    // a política vale para senhas novas; senhas antigas continuam entrando
+    #[validate(length(min = 1, message = "Informe a senha"))]
     pub password: String,
 }
 
@@ -25,6 +42,21 @@ pub struct ForgotPasswordRequest {
     pub email: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ResendConfirmationRequest {
+    #[validate(email)]
+    pub email: String,
+}
+
+/// Restauração de uma conta excluída, ainda dentro do prazo de carência
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct RestoreAccountRequest {
+    #[validate(email)]
+    pub email: String,
+    #[validate(length(min = 1, message = "Informe a senha"))]
+    pub password: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct ChangePasswordRequest {
     pub code: String,
@@ -19,6 +19,17 @@ pub struct Course {
     pub dt_deleted: Option<NaiveDateTime>,
 }
 
+/// Curso ativo como aparece no perfil público do autor
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PublicCourse {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub price: f64,
+    pub month_duration: i32,
+    pub dt_start: NaiveDate,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CreateCourseRequest {
     pub name: String,
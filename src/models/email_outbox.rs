@@ -0,0 +1,42 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Situação de uma mensagem na fila de saída
+pub mod email_status {
+    pub const QUEUED: &str = "queued";
+    pub const SENT: &str = "sent";
+    /// Esgotou as tentativas (dead letter); só volta à fila por um administrador
+    pub const FAILED: &str = "failed";
+    /// Descartado na entrega: o destinatário não aceita mais a categoria
+    pub const SUPPRESSED: &str = "suppressed";
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct OutboxEmail {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub to_email: String,
+    pub subject: String,
+    #[serde(skip_serializing)]
+    pub html_body: String,
+    #[serde(skip_serializing)]
+    pub text_body: Option<String>,
+    pub category: String,
+    pub list_unsubscribe_url: Option<String>,
+    pub status: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub next_attempt_at: NaiveDateTime,
+    pub last_error: Option<String>,
+    pub provider_message_id: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub sent_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OutboxEmailFilter {
+    pub status: Option<String>,
+    pub to_email: Option<String>,
+}
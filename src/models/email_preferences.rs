@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Categorias de e-mail. Só as não transacionais podem ser desativadas.
+pub mod email_categories {
+    pub const TRANSACTIONAL: &str = "transactional";
+    pub const COURSE_ANNOUNCEMENTS: &str = "course_announcements";
+    pub const MARKETING: &str = "marketing";
+}
+
+/// Audiência (`aud`) do token de descadastro, para que ele não seja aceito em outro contexto
+pub const UNSUBSCRIBE_AUDIENCE: &str = "unsubscribe";
+
+/// Validade dos links de descadastro enviados nos e-mails
+pub const UNSUBSCRIBE_TOKEN_EXPIRES_IN: u64 = 365 * 24 * 60 * 60;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnsubscribeClaims {
+    pub sub: String,
+    pub exp: usize,
+    pub category: String,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct EmailPreferences {
+    /// Sempre ativo: confirmação de e-mail, redefinição de senha etc.
+    pub transactional: bool,
+    pub course_announcements: bool,
+    pub marketing: bool,
+    /// Descadastro de todas as categorias não transacionais
+    pub unsubscribe: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateEmailPreferencesRequest {
+    pub transactional: Option<bool>,
+    pub course_announcements: Option<bool>,
+    pub marketing: Option<bool>,
+    pub unsubscribe: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnsubscribeQuery {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnsubscribeResponse {
+    pub category: String,
+    pub unsubscribed: bool,
+}
+
+/// Destinatário de um envio em massa, já com o idioma do perfil
+#[derive(Debug, sqlx::FromRow)]
+pub struct EmailRecipient {
+    pub user_id: Uuid,
+    pub email: String,
+    pub first_name: String,
+    pub preferred_language: String,
+}
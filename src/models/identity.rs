@@ -0,0 +1,84 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct UserIdentity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub provider: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_login_at: Option<NaiveDateTime>,
+}
+
+/// Parâmetros devolvidos pelo provedor no redirecionamento para o callback
+#[derive(Debug, Deserialize)]
+pub struct OidcCallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OidcTokenResponse {
+    pub access_token: String,
+}
+
+/// Dados do usuário normalizados a partir do endpoint userinfo do provedor
+#[derive(Debug, Clone)]
+pub struct OidcUserInfo {
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+}
+
+impl OidcUserInfo {
+    /// Aceita o formato OIDC (`sub`, `email_verified`, `given_name`) e o do GitHub (`id`, `name`)
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        let subject = match &value["sub"] {
+            serde_json::Value::String(sub) => sub.clone(),
+            _ => match &value["id"] {
+                serde_json::Value::String(id) => id.clone(),
+                serde_json::Value::Number(id) => id.to_string(),
+                _ => return None,
+            },
+        };
+
+        let email_verified = match &value["email_verified"] {
+            serde_json::Value::Bool(verified) => *verified,
+            serde_json::Value::String(verified) => verified == "true",
+            _ => false,
+        };
+
+        let text = |field: &str| {
+            value[field]
+                .as_str()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+
+        let (first_name, last_name) = match (text("given_name"), text("family_name")) {
+            (Some(first), last) => (Some(first), last),
+            (None, _) => match text("name") {
+                Some(name) => match name.split_once(' ') {
+                    Some((first, last)) => (Some(first.to_string()), Some(last.to_string())),
+                    None => (Some(name), None),
+                },
+                None => (None, None),
+            },
+        };
+
+        Some(Self {
+            subject,
+            email: text("email").map(|email| email.to_lowercase()),
+            email_verified,
+            first_name,
+            last_name,
+        })
+    }
+}
@@ -0,0 +1,67 @@
+use crate::models::user::UserResponse;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Audiência (`aud`) do token de desafio, para que ele não seja aceito em outro contexto
+pub const MFA_CHALLENGE_AUDIENCE: &str = "mfa_challenge";
+
+#[derive(Debug, sqlx::FromRow)]
+#[allow(dead_code)]
+pub struct UserTotp {
+    pub user_id: Uuid,
+    pub secret: String,
+    pub enabled: bool,
+    pub last_used_step: Option<i64>,
+    pub enabled_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MfaChallengeClaims {
+    pub sub: String,
+    pub exp: usize,
+    /// Identifica o desafio em `mfa_challenges`, que impede o reuso do token
+    pub jti: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TotpEnrollmentResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TotpCodeRequest {
+    #[validate(length(min = 6, max = 32, message = "Código inválido"))]
+    pub code: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MfaChallengeResponse {
+    pub mfa_required: bool,
+    pub mfa_token: String,
+    pub expires_in: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct MfaLoginRequest {
+    #[validate(length(min = 1, message = "mfa_token é obrigatório"))]
+    pub mfa_token: String,
+    #[validate(length(min = 6, max = 32, message = "Código inválido"))]
+    pub code: String,
+}
+
+/// Resposta do login: autenticado diretamente ou aguardando o segundo fator
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Authenticated(Box<UserResponse>),
+    MfaRequired(MfaChallengeResponse),
+}
@@ -1,7 +1,16 @@
+pub mod api_key;
 pub mod auth;
 pub mod category;
 pub mod course;
+pub mod email_outbox;
+pub mod email_preferences;
+pub mod identity;
+pub mod mfa;
 pub mod notification;
+pub mod phone_verification;
 pub mod profile;
+pub mod refresh_token;
+pub mod role;
+pub mod session;
 pub mod token;
 pub mod user;
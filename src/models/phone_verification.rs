@@ -0,0 +1,34 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Tamanho do código enviado por SMS
+pub const CODE_LENGTH: usize = 6;
+
+/// Código pendente; cada usuário tem no máximo um
+#[derive(Debug, sqlx::FromRow)]
+#[allow(dead_code)]
+pub struct PhoneVerification {
+    pub user_id: Uuid,
+    /// Telefone (E.164) que recebeu o código
+    pub phone: String,
+    /// SHA-256 (hex) do código; o código em si não é guardado
+    pub code_hash: String,
+    pub attempts: i32,
+    pub dt_expires: NaiveDateTime,
+    pub dt_created: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ConfirmPhoneRequest {
+    #[validate(length(equal = 6, message = "O código deve ter 6 dígitos"))]
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PhoneVerificationSent {
+    pub phone: String,
+    /// Segundos até o código expirar
+    pub expires_in: i64,
+}
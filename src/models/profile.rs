@@ -1,8 +1,19 @@
+use crate::config::get_settings;
 use crate::extensions::has_any_field::HasAnyField;
 use crate::models::user::ProfileRequest;
+use crate::utils::document::{DocumentType, mask_document, parse_document};
+use crate::utils::phone::normalize_phone;
 use chrono::{NaiveDate, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use validator::Validate;
+
+/// Idiomas com templates de e-mail disponíveis
+pub mod languages {
+    pub const PT_BR: &str = "pt-BR";
+    pub const EN: &str = "en";
+    pub const SUPPORTED: [&str; 2] = [PT_BR, EN];
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
 pub struct Profile {
@@ -10,13 +21,20 @@ pub struct Profile {
     pub user_id: Uuid,
     pub bio: Option<String>,
     pub birth_date: Option<chrono::NaiveDate>,
+    /// Em E.164 (`+5511987654321`)
     pub phone: Option<String>,
+    pub phone_verified: bool,
+    /// Apenas dígitos; o tipo fica em `document_type`
     pub document: Option<String>,
+    pub document_type: Option<String>,
     pub profession: Option<String>,
     pub avatar: Option<String>,
     pub confirm_email: bool,
     pub unsubscribe: bool,
     pub access_level: String,
+    pub preferred_language: String,
+    pub email_course_announcements: bool,
+    pub email_marketing: bool,
     pub dt_updated: NaiveDateTime,
     pub dt_created: NaiveDateTime,
 }
@@ -32,12 +50,17 @@ impl Profile {
             bio: None,
             birth_date: None,
             phone: None,
+            phone_verified: false,
             document: None,
+            document_type: None,
             profession: None,
             avatar: None,
             confirm_email: false,
             unsubscribe: false,
             access_level: "user".to_string(),
+            preferred_language: languages::PT_BR.to_string(),
+            email_course_announcements: true,
+            email_marketing: false,
             dt_created: now,
             dt_updated: now,
         }
@@ -46,6 +69,8 @@ impl Profile {
         let now = Utc::now().naive_utc();
 
         if let Some(profile) = req {
+            // documento e telefone já passaram por `validate_document` e `validate_phone`
+            let document = profile.document.as_deref().and_then(parse_document);
             Self {
                 id: Uuid::new_v4(),
                 user_id,
@@ -53,13 +78,24 @@ impl Profile {
                 birth_date: profile
                     .birth_date
                     .and_then(|s| NaiveDate::parse_from_str(&s, "%Y-%m-%d").ok()),
-                phone: profile.phone,
-                document: profile.document,
+                phone: profile
+                    .phone
+                    .as_deref()
+                    .and_then(|p| normalize_phone(p, &get_settings().phone.default_region)),
+                phone_verified: false,
+                document: document.as_ref().map(|d| d.number.clone()),
+                document_type: document.map(|d| d.kind.as_str().to_string()),
                 profession: profile.profession,
                 avatar: profile.avatar,
-                confirm_email: profile.confirm_email.unwrap_or(false),
-                unsubscribe: profile.unsubscribe.unwrap_or(false),
-                access_level: profile.access_level.unwrap_or_else(|| "user".to_string()),
+                // Campos privilegiados só podem ser definidos via API administrativa
+                confirm_email: false,
+                unsubscribe: false,
+                access_level: "user".to_string(),
+                preferred_language: profile
+                    .preferred_language
+                    .unwrap_or_else(|| languages::PT_BR.to_string()),
+                email_course_announcements: true,
+                email_marketing: false,
                 dt_created: now,
                 dt_updated: now,
             }
@@ -71,27 +107,56 @@ impl Profile {
                 bio: None,
                 birth_date: None,
                 phone: None,
+                phone_verified: false,
                 document: None,
+                document_type: None,
                 profession: None,
                 avatar: None,
                 confirm_email: false,
                 unsubscribe: false,
                 access_level: "user".to_string(),
+                preferred_language: languages::PT_BR.to_string(),
+                email_course_announcements: true,
+                email_marketing: false,
                 dt_created: now,
                 dt_updated: now,
             }
         }
     }
+
+    /// Documento completo só para o titular ou para quem administra usuários (`users:manage`)
+    pub fn apply_viewer_visibility(&mut self, viewer_id: Option<Uuid>, can_manage_users: bool) {
+        if viewer_id != Some(self.user_id) && !can_manage_users {
+            self.mask_document();
+        }
+    }
+
+    /// Esconde o início e os dígitos verificadores do documento
+    pub fn mask_document(&mut self) {
+        let kind = self
+            .document_type
+            .as_deref()
+            .and_then(DocumentType::from_db);
+        if let (Some(document), Some(kind)) = (&self.document, kind) {
+            self.document = Some(mask_document(kind, document));
+        } else if self.document.is_some() {
+            self.document = Some("***".to_string());
+        }
+    }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpdateProfileRequest {
     pub bio: Option<String>,
+    #[validate(custom = "crate::utils::validation::validate_phone")]
     pub phone: Option<String>,
     pub birth_date: Option<NaiveDate>,
     pub profession: Option<String>,
+    #[validate(custom = "crate::utils::validation::validate_document")]
     pub document: Option<String>,
     pub avatar: Option<String>,
+    #[validate(custom = "crate::utils::validation::validate_language")]
+    pub preferred_language: Option<String>,
 }
 
 impl HasAnyField for UpdateProfileRequest {
@@ -102,5 +167,19 @@ impl HasAnyField for UpdateProfileRequest {
             || self.profession.is_some()
             || self.avatar.is_some()
             || self.document.is_some()
+            || self.preferred_language.is_some()
     }
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AvatarThumbnail {
+    pub size: u32,
+    pub url: String,
+}
+
+/// URLs do avatar recém-enviado; `avatar` é a maior miniatura, gravada no perfil
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AvatarResponse {
+    pub avatar: String,
+    pub thumbnails: Vec<AvatarThumbnail>,
+}
@@ -0,0 +1,43 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Códigos de permissão usados nas rotas (`RequirePermission`)
+pub mod permissions {
+    pub const COURSES_WRITE: &str = "courses:write";
+    pub const COURSES_DELETE: &str = "courses:delete";
+    pub const COURSES_SYNC: &str = "courses:sync";
+    pub const CATEGORIES_WRITE: &str = "categories:write";
+    pub const CATEGORIES_DELETE: &str = "categories:delete";
+    pub const ROLES_MANAGE: &str = "roles:manage";
+    pub const USERS_MANAGE: &str = "users:manage";
+    pub const EMAILS_MANAGE: &str = "emails:manage";
+}
+
+pub const DEFAULT_ROLE: &str = "user";
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Role {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub dt_created: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoleWithPermissions {
+    #[serde(flatten)]
+    pub role: Role,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssignRoleRequest {
+    pub role: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserRolesResponse {
+    pub user_id: Uuid,
+    pub roles: Vec<String>,
+}
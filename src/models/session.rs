@@ -0,0 +1,45 @@
+use crate::utils::client_ip::client_ip;
+use actix_web::HttpRequest;
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct UserSession {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_seen_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+    #[serde(skip_serializing)]
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionResponse {
+    #[serde(flatten)]
+    pub session: UserSession,
+    pub current: bool,
+}
+
+/// Dados do dispositivo que abriu a sessão
+#[derive(Debug, Clone, Default)]
+pub struct SessionInfo {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl SessionInfo {
+    pub fn from_request(req: &HttpRequest) -> Self {
+        Self {
+            user_agent: req
+                .headers()
+                .get("User-Agent")
+                .and_then(|h| h.to_str().ok())
+                .map(|s| s.to_string()),
+            ip_address: client_ip(req),
+        }
+    }
+}
@@ -1,14 +1,24 @@
 use chrono::NaiveDateTime;
 use uuid::Uuid;
 
+/// Tipos de `user_tokens`; cada um tem sua validade em `UserTokenSettings`
+pub mod token_types {
+    pub const CONFIRM_EMAIL: &str = "confirm_email";
+    pub const CHANGE_PASSWORD: &str = "change_password";
+    pub const CHANGE_EMAIL: &str = "change_email";
+}
+
 #[derive(Debug, sqlx::FromRow)]
 #[allow(dead_code)]
 pub struct UserToken {
     pub id: Uuid,
     pub user_id: Uuid,
-    pub code: String,
+    /// SHA-256 (hex) do código enviado por e-mail; o código em si não é guardado
+    pub code_hash: String,
     pub token_type: String,
     pub expires_at: NaiveDateTime,
     pub created_at: NaiveDateTime,
-    pub consumed: Option<bool>,
+    pub consumed: bool,
+    /// Endereço pendente de um token `change_email`
+    pub new_email: Option<String>,
 }
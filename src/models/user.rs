@@ -1,8 +1,12 @@
-use crate::models::profile::Profile;
+use crate::logs::model::LogEntry;
+use crate::models::course::PublicCourse;
+use crate::models::notification::Notification;
+use crate::models::profile::{Profile, UpdateProfileRequest};
+use crate::models::refresh_token::TokenPairResponse;
+use crate::utils::password::{self, PasswordMatch};
 use crate::utils::validation::{validate_email, validate_password};
-use bcrypt::{DEFAULT_COST, hash, verify};
-use chrono::NaiveDateTime;
 use chrono::Utc;
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
@@ -23,14 +27,14 @@ pub struct User {
 }
 
 impl User {
+    /// `password_hash` já deve vir de `utils::password::hash_password`
     pub fn new(
         username: &str,
         email: &str,
         first_name: &str,
         last_name: &str,
-        password: &str,
+        password_hash: String,
     ) -> Self {
-        let hashed = hash(password, DEFAULT_COST).expect("Erro ao hashear");
         let now: NaiveDateTime = Utc::now().naive_utc();
 
         Self {
@@ -39,15 +43,15 @@ impl User {
             email: email.to_string(),
             first_name: first_name.to_string(),
             last_name: last_name.to_string(),
-            password: hashed,
+            password: password_hash,
             dt_created: now,
             dt_updated: now,
             dt_deleted: None,
         }
     }
 
-    pub fn verify_password(&self, input: &str) -> bool {
-        verify(input, &self.password).unwrap_or(false)
+    pub async fn verify_password(&self, input: &str) -> PasswordMatch {
+        password::verify_password(input, &self.password).await
     }
 }
 
@@ -79,19 +83,38 @@ pub struct ProfileRequest {
     #[validate(url(message = "URL do avatar inválida"))]
     pub avatar: Option<String>,
 
-    pub confirm_email: Option<bool>,
-    pub unsubscribe: Option<bool>,
+    #[validate(custom = "crate::utils::validation::validate_language")]
+    pub preferred_language: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct UserRequest {
+    #[validate(custom = "validate_email")]
+    pub email: String,
+
+    #[validate(length(min = 2, max = 50, message = "O nome deve ter entre 2 e 50 caracteres"))]
+    pub first_name: String,
 
     #[validate(length(
         min = 2,
         max = 50,
-        message = "O nível de acesso deve ter entre 2 e 50 caracteres"
+        message = "O sobrenome deve ter entre 2 e 50 caracteres"
     ))]
-    pub access_level: Option<String>,
+    pub last_name: String,
+
+    #[validate(custom = "validate_password")]
+    pub password: String,
+
+    #[validate]
+    pub profile: Option<ProfileRequest>,
 }
 
+/// Cadastro feito por um administrador: permite definir os campos privilegiados
+/// que o cadastro público (`UserRequest`) não aceita. As permissões vêm só de `roles`;
+/// campos desconhecidos (como o antigo `access_level`) são recusados.
 #[derive(Debug, Deserialize, Serialize, Validate)]
-pub struct UserRequest {
+#[serde(deny_unknown_fields)]
+pub struct AdminUserRequest {
     #[validate(custom = "validate_email")]
     pub email: String,
 
@@ -110,6 +133,115 @@ pub struct UserRequest {
 
     #[validate]
     pub profile: Option<ProfileRequest>,
+
+    pub confirm_email: Option<bool>,
+    pub unsubscribe: Option<bool>,
+
+    pub roles: Option<Vec<String>>,
+}
+
+/// Filtros de `GET /admin/users/`; filtros ausentes não restringem a busca
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AdminUserQuery {
+    /// Trecho do e-mail
+    pub email: Option<String>,
+    /// Trecho do nome completo
+    pub name: Option<String>,
+    /// Nome do papel (`admin`, `user`, ...)
+    pub role: Option<String>,
+    pub confirmed: Option<bool>,
+    pub deleted: Option<bool>,
+    pub suspended: Option<bool>,
+    #[serde(default = "crate::utils::pagination::default_limit")]
+    pub limit: i64,
+    #[serde(default = "crate::utils::pagination::default_offset")]
+    pub offset: i64,
+}
+
+/// Usuário como visto pelos administradores, incluindo contas excluídas e suspensas
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct AdminUserSummary {
+    pub id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub roles: Vec<String>,
+    pub confirm_email: bool,
+    pub dt_created: NaiveDateTime,
+    pub dt_deleted: Option<NaiveDateTime>,
+    pub dt_suspended: Option<NaiveDateTime>,
+    pub suspension_reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminUserDetail {
+    #[serde(flatten)]
+    pub user: AdminUserSummary,
+    pub profile: Profile,
+}
+
+/// Ação sobre a redefinição de senha do usuário
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PasswordResetAction {
+    /// Envia um novo link de redefinição, invalidando os anteriores
+    Send,
+    /// Invalida os links de redefinição pendentes
+    Cancel,
+}
+
+/// Papéis são alterados em `/admin/users/{id}/roles/`; campos desconhecidos
+/// (como o antigo `access_level`) são recusados em vez de ignorados
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct AdminUpdateUserRequest {
+    #[validate]
+    pub profile: Option<UpdateProfileRequest>,
+
+    pub password_reset: Option<PasswordResetAction>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SuspendUserRequest {
+    #[validate(length(max = 500, message = "O motivo deve ter no máximo 500 caracteres"))]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangeUsernameRequest {
+    pub username: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UsernameResponse {
+    pub username: String,
+}
+
+/// Dados de uma conta ativa que podem aparecer no perfil público
+#[derive(Debug, sqlx::FromRow)]
+pub struct PublicUser {
+    pub id: Uuid,
+    pub username: String,
+    pub first_name: String,
+    pub bio: Option<String>,
+    pub profession: Option<String>,
+    pub avatar: Option<String>,
+    pub dt_created: NaiveDateTime,
+}
+
+/// Perfil público (`GET /users/{username}/`): sem e-mail, sobrenome, telefone, documento ou
+/// qualquer dado da conta
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublicProfile {
+    pub username: String,
+    pub first_name: String,
+    pub bio: Option<String>,
+    pub profession: Option<String>,
+    pub avatar: Option<String>,
+    pub member_since: NaiveDate,
+    pub courses: Vec<PublicCourse>,
 }
 
 #[derive(Serialize)]
@@ -127,6 +259,8 @@ pub struct UserResponse {
     pub user: UserWithProfile,
     pub expires_in: String,
     pub token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
 }
 
 impl UserWithProfile {
@@ -159,6 +293,16 @@ impl UserResponse {
             user: user_with_profile,
             token,
             expires_in,
+            refresh_token: None,
+        }
+    }
+
+    pub fn from_token_pair(user_with_profile: UserWithProfile, pair: TokenPairResponse) -> Self {
+        Self {
+            user: user_with_profile,
+            token: pair.token,
+            expires_in: pair.expires_in,
+            refresh_token: Some(pair.refresh_token),
         }
     }
 }
@@ -168,3 +312,30 @@ pub struct UpdateUserRequest {
     pub first_name: Option<String>,
     pub last_name: Option<String>,
 }
+
+/// Pedido de troca de e-mail: a senha atual é exigida e o novo endereço precisa ser confirmado
+#[derive(Debug, Deserialize, Validate)]
+pub struct ChangeEmailRequest {
+    #[validate(custom = "validate_email")]
+    pub new_email: String,
+    pub password: String,
+}
+
+/// Troca de senha de quem está logado: exige a senha atual
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdatePasswordRequest {
+    pub current_password: String,
+    #[validate(custom = "validate_password")]
+    pub new_password: String,
+}
+
+/// Dados pessoais do titular (`GET /me/export/`)
+#[derive(Debug, Serialize)]
+pub struct PersonalDataExport {
+    pub exported_at: NaiveDateTime,
+    pub user: User,
+    pub profile: Profile,
+    pub notifications: Vec<Notification>,
+    /// `None` quando o MongoDB não respondeu e os logs não puderam ser incluídos
+    pub logs: Option<Vec<LogEntry>>,
+}
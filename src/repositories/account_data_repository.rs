@@ -0,0 +1,67 @@
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Remove os dados ligados a uma conta anonimizada: credenciais, sessões, vínculos externos,
+/// códigos de verificação, documentos em revisão, e-mails enfileirados e notificações pessoais. `revoked_tokens` é
+/// mantida para que access tokens ainda não expirados continuem recusados.
+pub async fn delete_account_data_in_tx(
+    user_id: Uuid,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!("DELETE FROM user_identities WHERE user_id = $1", user_id)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query!("DELETE FROM api_keys WHERE user_id = $1", user_id)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query!("DELETE FROM user_totp WHERE user_id = $1", user_id)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query!(
+        "DELETE FROM user_recovery_codes WHERE user_id = $1",
+        user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query!("DELETE FROM mfa_challenges WHERE user_id = $1", user_id)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query!("DELETE FROM user_tokens WHERE user_id = $1", user_id)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query!("DELETE FROM password_history WHERE user_id = $1", user_id)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query!("DELETE FROM refresh_tokens WHERE user_id = $1", user_id)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query!("DELETE FROM user_sessions WHERE user_id = $1", user_id)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query!("DELETE FROM user_roles WHERE user_id = $1", user_id)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query!(
+        "DELETE FROM phone_verifications WHERE user_id = $1",
+        user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM invalid_profile_documents WHERE user_id = $1",
+        user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query!("DELETE FROM email_outbox WHERE user_id = $1", user_id)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query!(
+        "DELETE FROM notifications WHERE obj_code = 'user' AND obj_id = $1",
+        user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use crate::models::api_key::ApiKey;
+use chrono::NaiveDateTime;
+use sqlx::{PgPool, Result};
+use uuid::Uuid;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn create_api_key(
+    user_id: Uuid,
+    name: &str,
+    prefix: &str,
+    key_hash: &str,
+    scopes: &[String],
+    expires_at: Option<NaiveDateTime>,
+    created_by: Uuid,
+    db: &PgPool,
+) -> Result<ApiKey> {
+    sqlx::query_as!(
+        ApiKey,
+        r#"
+        INSERT INTO api_keys (id, user_id, name, prefix, key_hash, scopes, expires_at, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, user_id, name, prefix, key_hash, scopes, expires_at, last_used_at,
+                  revoked_at, created_by, created_at
+        "#,
+        Uuid::new_v4(),
+        user_id,
+        name,
+        prefix,
+        key_hash,
+        scopes,
+        expires_at,
+        created_by
+    )
+    .fetch_one(db)
+    .await
+}
+
+pub async fn list_api_keys_by_user(user_id: Uuid, db: &PgPool) -> Result<Vec<ApiKey>> {
+    sqlx::query_as!(
+        ApiKey,
+        r#"
+        SELECT id, user_id, name, prefix, key_hash, scopes, expires_at, last_used_at,
+               revoked_at, created_by, created_at
+        FROM api_keys
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(db)
+    .await
+}
+
+pub async fn revoke_api_key(id: Uuid, user_id: Uuid, db: &PgPool) -> Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE api_keys SET revoked_at = NOW()
+        WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+        "#,
+        id,
+        user_id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn update_api_key_expiry(
+    id: Uuid,
+    user_id: Uuid,
+    expires_at: Option<NaiveDateTime>,
+    db: &PgPool,
+) -> Result<Option<ApiKey>> {
+    sqlx::query_as!(
+        ApiKey,
+        r#"
+        UPDATE api_keys SET expires_at = $3
+        WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+        RETURNING id, user_id, name, prefix, key_hash, scopes, expires_at, last_used_at,
+                  revoked_at, created_by, created_at
+        "#,
+        id,
+        user_id,
+        expires_at
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Busca uma chave ativa (não revogada, não expirada e de um usuário não excluído)
+/// e já registra o uso em `last_used_at`
+pub async fn touch_active_api_key(key_hash: &str, db: &PgPool) -> Result<Option<ApiKey>> {
+    sqlx::query_as!(
+        ApiKey,
+        r#"
+        UPDATE api_keys k SET last_used_at = NOW()
+        FROM users u
+        WHERE k.key_hash = $1
+          AND k.revoked_at IS NULL
+          AND (k.expires_at IS NULL OR k.expires_at > NOW())
+          AND u.id = k.user_id
+          AND u.dt_deleted IS NULL
+        RETURNING k.id, k.user_id, k.name, k.prefix, k.key_hash, k.scopes, k.expires_at,
+                  k.last_used_at, k.revoked_at, k.created_by, k.created_at
+        "#,
+        key_hash
+    )
+    .fetch_optional(db)
+    .await
+}
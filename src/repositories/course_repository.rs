@@ -1,6 +1,6 @@
 use crate::errors::app_error::AppError;
 use crate::models::category::CategorySimple;
-use crate::models::course::{Course, CourseCategory, UpdateCourseRequest};
+use crate::models::course::{Course, CourseCategory, PublicCourse, UpdateCourseRequest};
 use sqlx::{Error, PgPool, Postgres, Transaction, types::chrono::Utc};
 use uuid::Uuid;
 
@@ -204,3 +204,21 @@ pub async fn get_all_active_courses(db: &PgPool) -> Result<Vec<Course>, sqlx::Er
 
     Ok(rows)
 }
+
+pub async fn list_public_courses_by_author(
+    author_id: Uuid,
+    db: &PgPool,
+) -> Result<Vec<PublicCourse>, sqlx::Error> {
+    sqlx::query_as!(
+        PublicCourse,
+        r#"
+        SELECT id, name, description, price, month_duration, dt_start
+        FROM courses
+        WHERE author_id = $1 AND is_active = true AND dt_deleted IS NULL
+        ORDER BY dt_start DESC, name
+        "#,
+        author_id
+    )
+    .fetch_all(db)
+    .await
+}
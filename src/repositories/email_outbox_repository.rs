@@ -0,0 +1,266 @@
+use crate::mailer::EmailMessage;
+use crate::models::email_outbox::OutboxEmail;
+use chrono::NaiveDateTime;
+use sqlx::{PgPool, Postgres, Result, Transaction};
+use uuid::Uuid;
+
+pub async fn enqueue_in_tx(
+    message: &EmailMessage,
+    category: &str,
+    user_id: Option<Uuid>,
+    max_attempts: i32,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<Uuid> {
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO email_outbox (
+            id, user_id, to_email, subject, html_body, text_body, category,
+            list_unsubscribe_url, max_attempts
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id
+        "#,
+        Uuid::new_v4(),
+        user_id,
+        message.to,
+        message.subject,
+        message.html,
+        message.text,
+        category,
+        message.list_unsubscribe,
+        max_attempts
+    )
+    .fetch_one(&mut **tx)
+    .await
+}
+
+/// Reserva até `limit` mensagens vencidas, contando a tentativa e empurrando `next_attempt_at`
+/// para o fim do `lease`. Se o worker cair no meio da entrega, a mensagem volta sozinha à fila.
+/// `SKIP LOCKED` permite vários workers em paralelo sem entregar a mesma mensagem duas vezes.
+pub async fn claim_due(
+    limit: i64,
+    lease_until: NaiveDateTime,
+    db: &PgPool,
+) -> Result<Vec<OutboxEmail>> {
+    sqlx::query_as!(
+        OutboxEmail,
+        r#"
+        UPDATE email_outbox
+        SET attempts = attempts + 1, next_attempt_at = $2, updated_at = NOW()
+        WHERE id IN (
+            SELECT id FROM email_outbox
+            WHERE status = 'queued' AND next_attempt_at <= NOW()
+            ORDER BY next_attempt_at
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, user_id, to_email, subject, html_body, text_body, category,
+                  list_unsubscribe_url, status, attempts, max_attempts,
+                  next_attempt_at, last_error, provider_message_id, created_at, updated_at,
+                  sent_at
+        "#,
+        limit,
+        lease_until
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// Mesma reserva de `claim_due`, para uma mensagem específica
+pub async fn claim_by_id(
+    id: Uuid,
+    lease_until: NaiveDateTime,
+    db: &PgPool,
+) -> Result<Option<OutboxEmail>> {
+    sqlx::query_as!(
+        OutboxEmail,
+        r#"
+        UPDATE email_outbox
+        SET attempts = attempts + 1, next_attempt_at = $2, updated_at = NOW()
+        WHERE id = (
+            SELECT id FROM email_outbox
+            WHERE id = $1 AND status = 'queued' AND next_attempt_at <= NOW()
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, user_id, to_email, subject, html_body, text_body, category,
+                  list_unsubscribe_url, status, attempts, max_attempts,
+                  next_attempt_at, last_error, provider_message_id, created_at, updated_at,
+                  sent_at
+        "#,
+        id,
+        lease_until
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Marca como entregue e apaga o corpo, que pode conter links e códigos de uso único
+pub async fn mark_sent(id: Uuid, provider_message_id: &str, db: &PgPool) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE email_outbox
+        SET status = 'sent', provider_message_id = $2, last_error = NULL,
+            html_body = '', text_body = NULL, sent_at = NOW(), updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id,
+        provider_message_id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn schedule_retry(
+    id: Uuid,
+    error: &str,
+    next_attempt_at: NaiveDateTime,
+    db: &PgPool,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE email_outbox
+        SET last_error = $2, next_attempt_at = $3, updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id,
+        error,
+        next_attempt_at
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn mark_failed(id: Uuid, error: &str, db: &PgPool) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE email_outbox
+        SET status = 'failed', last_error = $2, updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id,
+        error
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Apaga o corpo das mensagens em dead letter paradas desde antes de `before`;
+/// depois disso elas não podem mais voltar à fila
+pub async fn clear_failed_bodies(before: NaiveDateTime, db: &PgPool) -> Result<u64> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE email_outbox
+        SET html_body = '', text_body = NULL
+        WHERE status = 'failed' AND html_body <> '' AND updated_at < $1
+        "#,
+        before
+    )
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Descarta a mensagem sem entregar, quando o destinatário não aceita mais a categoria
+pub async fn mark_suppressed(id: Uuid, db: &PgPool) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE email_outbox
+        SET status = 'suppressed', html_body = '', text_body = NULL, updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn find_by_id(id: Uuid, db: &PgPool) -> Result<Option<OutboxEmail>> {
+    sqlx::query_as!(
+        OutboxEmail,
+        r#"
+        SELECT id, user_id, to_email, subject, html_body, text_body, category,
+               list_unsubscribe_url, status, attempts, max_attempts,
+               next_attempt_at, last_error, provider_message_id, created_at, updated_at,
+               sent_at
+        FROM email_outbox
+        WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_optional(db)
+    .await
+}
+
+pub async fn list(
+    status: Option<&str>,
+    to_email: Option<&str>,
+    limit: i64,
+    offset: i64,
+    db: &PgPool,
+) -> Result<Vec<OutboxEmail>> {
+    sqlx::query_as!(
+        OutboxEmail,
+        r#"
+        SELECT id, user_id, to_email, subject, html_body, text_body, category,
+               list_unsubscribe_url, status, attempts, max_attempts,
+               next_attempt_at, last_error, provider_message_id, created_at, updated_at,
+               sent_at
+        FROM email_outbox
+        WHERE ($1::TEXT IS NULL OR status = $1)
+          AND ($2::TEXT IS NULL OR to_email = $2)
+        ORDER BY created_at DESC
+        LIMIT $3 OFFSET $4
+        "#,
+        status,
+        to_email,
+        limit,
+        offset
+    )
+    .fetch_all(db)
+    .await
+}
+
+pub async fn count(status: Option<&str>, to_email: Option<&str>, db: &PgPool) -> Result<i64> {
+    let count = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM email_outbox
+        WHERE ($1::TEXT IS NULL OR status = $1)
+          AND ($2::TEXT IS NULL OR to_email = $2)
+        "#,
+        status,
+        to_email
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(count)
+}
+
+/// Devolve uma mensagem em dead letter para a fila, com as tentativas zeradas
+pub async fn requeue_failed(id: Uuid, db: &PgPool) -> Result<Option<OutboxEmail>> {
+    sqlx::query_as!(
+        OutboxEmail,
+        r#"
+        UPDATE email_outbox
+        SET status = 'queued', attempts = 0, next_attempt_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND status = 'failed' AND html_body <> ''
+        RETURNING id, user_id, to_email, subject, html_body, text_body, category,
+                  list_unsubscribe_url, status, attempts, max_attempts,
+                  next_attempt_at, last_error, provider_message_id, created_at, updated_at,
+                  sent_at
+        "#,
+        id
+    )
+    .fetch_optional(db)
+    .await
+}
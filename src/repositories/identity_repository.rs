@@ -0,0 +1,118 @@
+use crate::errors::app_error::AppError;
+use crate::models::identity::UserIdentity;
+use chrono::NaiveDateTime;
+use sqlx::{PgPool, Result};
+use uuid::Uuid;
+
+pub async fn create_auth_state(
+    state_hash: &str,
+    provider: &str,
+    code_verifier: &str,
+    expires_at: NaiveDateTime,
+    db: &PgPool,
+) -> Result<(), AppError> {
+    // Aproveita para descartar fluxos abandonados
+    sqlx::query!("DELETE FROM oidc_auth_states WHERE expires_at < NOW()")
+        .execute(db)
+        .await
+        .map_err(|err| {
+            eprintln!("Erro ao limpar estados OIDC: {:?}", err);
+            AppError::DatabaseError(Some("Erro ao limpar estados OIDC".into()))
+        })?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO oidc_auth_states (state_hash, provider, code_verifier, expires_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        state_hash,
+        provider,
+        code_verifier,
+        expires_at
+    )
+    .execute(db)
+    .await
+    .map_err(|err| {
+        eprintln!("Erro ao gravar estado OIDC: {:?}", err);
+        AppError::DatabaseError(Some("Erro ao gravar estado OIDC".into()))
+    })?;
+
+    Ok(())
+}
+
+/// Consome o estado de forma atômica e devolve o `code_verifier` do PKCE
+pub async fn consume_auth_state(
+    state_hash: &str,
+    provider: &str,
+    db: &PgPool,
+) -> Result<Option<String>> {
+    sqlx::query_scalar!(
+        r#"
+        DELETE FROM oidc_auth_states
+        WHERE state_hash = $1 AND provider = $2 AND expires_at > NOW()
+        RETURNING code_verifier
+        "#,
+        state_hash,
+        provider
+    )
+    .fetch_optional(db)
+    .await
+}
+
+pub async fn find_identity(
+    provider: &str,
+    subject: &str,
+    db: &PgPool,
+) -> Result<Option<UserIdentity>> {
+    sqlx::query_as!(
+        UserIdentity,
+        r#"
+        SELECT id, user_id, provider, subject, email, created_at, last_login_at
+        FROM user_identities
+        WHERE provider = $1 AND subject = $2
+        "#,
+        provider,
+        subject
+    )
+    .fetch_optional(db)
+    .await
+}
+
+pub async fn create_identity(
+    user_id: Uuid,
+    provider: &str,
+    subject: &str,
+    email: Option<&str>,
+    db: &PgPool,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        INSERT INTO user_identities (id, user_id, provider, subject, email, last_login_at)
+        VALUES ($1, $2, $3, $4, $5, NOW())
+        "#,
+        Uuid::new_v4(),
+        user_id,
+        provider,
+        subject,
+        email
+    )
+    .execute(db)
+    .await
+    .map_err(|err| {
+        eprintln!("Erro ao vincular identidade: {:?}", err);
+        AppError::DatabaseError(Some("Erro ao vincular identidade".into()))
+    })?;
+
+    Ok(())
+}
+
+pub async fn touch_identity(id: Uuid, db: &PgPool) -> Result<()> {
+    sqlx::query!(
+        "UPDATE user_identities SET last_login_at = NOW() WHERE id = $1",
+        id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,247 @@
+use crate::errors::app_error::AppError;
+use crate::models::mfa::UserTotp;
+use chrono::NaiveDateTime;
+use sqlx::{PgPool, Postgres, Result, Transaction};
+use uuid::Uuid;
+
+pub async fn find_totp_by_user(user_id: Uuid, db: &PgPool) -> Result<Option<UserTotp>> {
+    sqlx::query_as!(
+        UserTotp,
+        r#"
+        SELECT user_id, secret, enabled, last_used_step, enabled_at, created_at
+        FROM user_totp
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Grava um novo segredo pendente de verificação. Não sobrescreve um TOTP já ativo,
+/// e nesse caso retorna `false`.
+pub async fn upsert_pending_totp(user_id: Uuid, secret: &str, db: &PgPool) -> Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO user_totp (user_id, secret)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE
+        SET secret = EXCLUDED.secret, last_used_step = NULL, created_at = NOW()
+        WHERE user_totp.enabled = FALSE
+        "#,
+        user_id,
+        secret
+    )
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Registra a janela usada, rejeitando janelas iguais ou anteriores (replay do mesmo código)
+pub async fn mark_step_used(user_id: Uuid, step: i64, db: &PgPool) -> Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE user_totp
+        SET last_used_step = $2
+        WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)
+        "#,
+        user_id,
+        step
+    )
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn enable_totp_in_tx(
+    user_id: Uuid,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        UPDATE user_totp
+        SET enabled = TRUE, enabled_at = NOW()
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Erro ao ativar TOTP: {:?}", err);
+        AppError::DatabaseError(Some("Erro ao ativar TOTP".into()))
+    })?;
+
+    Ok(())
+}
+
+pub async fn delete_totp_in_tx(
+    user_id: Uuid,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), AppError> {
+    sqlx::query!("DELETE FROM user_totp WHERE user_id = $1", user_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(|err| {
+            eprintln!("Erro ao remover TOTP: {:?}", err);
+            AppError::DatabaseError(Some("Erro ao remover TOTP".into()))
+        })?;
+
+    sqlx::query!(
+        "DELETE FROM user_recovery_codes WHERE user_id = $1",
+        user_id
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Erro ao remover códigos de recuperação: {:?}", err);
+        AppError::DatabaseError(Some("Erro ao remover códigos de recuperação".into()))
+    })?;
+
+    Ok(())
+}
+
+/// Substitui todos os códigos de recuperação do usuário pelos hashes informados
+pub async fn replace_recovery_codes_in_tx(
+    user_id: Uuid,
+    code_hashes: &[String],
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        "DELETE FROM user_recovery_codes WHERE user_id = $1",
+        user_id
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Erro ao remover códigos de recuperação: {:?}", err);
+        AppError::DatabaseError(Some("Erro ao remover códigos de recuperação".into()))
+    })?;
+
+    let ids: Vec<Uuid> = code_hashes.iter().map(|_| Uuid::new_v4()).collect();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO user_recovery_codes (id, user_id, code_hash)
+        SELECT id, $2, code_hash
+        FROM UNNEST($1::uuid[], $3::text[]) AS t(id, code_hash)
+        "#,
+        &ids,
+        user_id,
+        code_hashes
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Erro ao gravar códigos de recuperação: {:?}", err);
+        AppError::DatabaseError(Some("Erro ao gravar códigos de recuperação".into()))
+    })?;
+
+    Ok(())
+}
+
+/// Consome um código de recuperação de forma atômica; retorna `false` se inválido ou já usado
+pub async fn consume_recovery_code(user_id: Uuid, code_hash: &str, db: &PgPool) -> Result<bool> {
+    let consumed = sqlx::query_scalar!(
+        r#"
+        UPDATE user_recovery_codes
+        SET used_at = NOW()
+        WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+        RETURNING id
+        "#,
+        user_id,
+        code_hash
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(consumed.is_some())
+}
+
+/// Se o desafio de MFA já abriu uma sessão ou esgotou as tentativas
+pub async fn is_challenge_closed(jti: &str, db: &PgPool) -> Result<bool> {
+    let row = sqlx::query!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM mfa_challenges WHERE jti = $1 AND consumed_at IS NOT NULL
+        ) AS "closed!"
+        "#,
+        jti
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(row.closed)
+}
+
+/// Conta uma tentativa errada no desafio e o encerra ao atingir `max_attempts`
+pub async fn register_challenge_failure(
+    jti: &str,
+    user_id: Uuid,
+    expires_at: NaiveDateTime,
+    max_attempts: i32,
+    db: &PgPool,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO mfa_challenges (jti, user_id, failed_attempts, consumed_at, expires_at)
+        VALUES ($1, $2, 1, CASE WHEN $4 <= 1 THEN NOW() END, $3)
+        ON CONFLICT (jti) DO UPDATE
+        SET failed_attempts = mfa_challenges.failed_attempts + 1,
+            consumed_at = COALESCE(
+                mfa_challenges.consumed_at,
+                CASE WHEN mfa_challenges.failed_attempts + 1 >= $4 THEN NOW() END
+            )
+        "#,
+        jti,
+        user_id,
+        expires_at,
+        max_attempts
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Encerra o desafio após o login; retorna `false` se outro pedido já o usou
+pub async fn consume_challenge(
+    jti: &str,
+    user_id: Uuid,
+    expires_at: NaiveDateTime,
+    db: &PgPool,
+) -> Result<bool> {
+    let consumed = sqlx::query_scalar!(
+        r#"
+        INSERT INTO mfa_challenges (jti, user_id, consumed_at, expires_at)
+        VALUES ($1, $2, NOW(), $3)
+        ON CONFLICT (jti) DO UPDATE
+        SET consumed_at = NOW()
+        WHERE mfa_challenges.consumed_at IS NULL
+        RETURNING jti
+        "#,
+        jti,
+        user_id,
+        expires_at
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(consumed.is_some())
+}
+
+pub async fn delete_expired_challenges(before: NaiveDateTime, db: &PgPool) -> Result<u64> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM mfa_challenges
+        WHERE expires_at <= $1
+        "#,
+        before
+    )
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -1,6 +1,17 @@
+pub mod account_data_repository;
+pub mod api_key_repository;
 pub mod category_repository;
 pub mod course_repository;
+pub mod email_outbox_repository;
+pub mod identity_repository;
+pub mod mfa_repository;
 pub mod notification_repository;
+pub mod password_history_repository;
+pub mod phone_verification_repository;
 pub mod profile_repository;
+pub mod refresh_token_repository;
+pub mod revoked_token_repository;
+pub mod role_repository;
+pub mod session_repository;
 pub mod token_repository;
 pub mod user_repository;
@@ -79,3 +79,21 @@ pub async fn list_notifications_for_user_or_platform(
 
     // Ok(notifications)
 }
+
+/// Notificações endereçadas ao usuário (sem as da plataforma), para a exportação de dados
+pub async fn list_user_notifications(
+    user_id: Uuid,
+    db: &PgPool,
+) -> Result<Vec<Notification>, sqlx::Error> {
+    sqlx::query_as::<_, Notification>(
+        r#"
+        SELECT id, title, message, obj_code, obj_id, created_at
+        FROM notifications
+        WHERE obj_code = 'user' AND obj_id = $1
+        ORDER BY created_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await
+}
@@ -0,0 +1,68 @@
+use sqlx::{Postgres, Result, Transaction};
+use uuid::Uuid;
+
+pub async fn insert_in_tx(
+    user_id: Uuid,
+    password_hash: &str,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO password_history (user_id, password_hash)
+        VALUES ($1, $2)
+        "#,
+        user_id,
+        password_hash
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Hashes das últimas `limit` senhas, da mais recente para a mais antiga
+pub async fn recent_hashes_in_tx(
+    user_id: Uuid,
+    limit: i64,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<Vec<String>> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT password_hash
+        FROM password_history
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#,
+        user_id,
+        limit
+    )
+    .fetch_all(&mut **tx)
+    .await
+}
+
+/// Mantém apenas as `keep` entradas mais recentes do usuário
+pub async fn trim_in_tx(
+    user_id: Uuid,
+    keep: i64,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        DELETE FROM password_history
+        WHERE user_id = $1
+          AND id NOT IN (
+              SELECT id FROM password_history
+              WHERE user_id = $1
+              ORDER BY created_at DESC
+              LIMIT $2
+          )
+        "#,
+        user_id,
+        keep
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
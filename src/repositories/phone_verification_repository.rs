@@ -0,0 +1,82 @@
+use crate::models::phone_verification::PhoneVerification;
+use chrono::NaiveDateTime;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Grava um novo código, substituindo o anterior e zerando as tentativas
+pub async fn upsert_verification(
+    user_id: Uuid,
+    phone: &str,
+    code_hash: &str,
+    dt_expires: NaiveDateTime,
+    db: &PgPool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO phone_verifications (user_id, phone, code_hash, attempts, dt_expires)
+        VALUES ($1, $2, $3, 0, $4)
+        ON CONFLICT (user_id) DO UPDATE
+        SET phone = EXCLUDED.phone,
+            code_hash = EXCLUDED.code_hash,
+            attempts = 0,
+            dt_expires = EXCLUDED.dt_expires,
+            dt_created = NOW()
+        "#,
+        user_id,
+        phone,
+        code_hash,
+        dt_expires
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn find_verification(
+    user_id: Uuid,
+    db: &PgPool,
+) -> Result<Option<PhoneVerification>, sqlx::Error> {
+    sqlx::query_as!(
+        PhoneVerification,
+        r#"
+        SELECT user_id, phone, code_hash, attempts, dt_expires, dt_created
+        FROM phone_verifications
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Conta uma tentativa e devolve o código ainda válido. Checagem e escrita são o mesmo
+/// `UPDATE`, então tentativas simultâneas não passam do limite.
+pub async fn register_attempt(
+    user_id: Uuid,
+    db: &PgPool,
+) -> Result<Option<PhoneVerification>, sqlx::Error> {
+    sqlx::query_as!(
+        PhoneVerification,
+        r#"
+        UPDATE phone_verifications
+        SET attempts = attempts + 1
+        WHERE user_id = $1 AND dt_expires > NOW()
+        RETURNING user_id, phone, code_hash, attempts, dt_expires, dt_created
+        "#,
+        user_id
+    )
+    .fetch_optional(db)
+    .await
+}
+
+pub async fn delete_verification(user_id: Uuid, db: &PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM phone_verifications WHERE user_id = $1",
+        user_id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use crate::errors::app_error::AppError;
+use crate::models::email_preferences::{EmailPreferences, EmailRecipient};
 use crate::models::profile::{Profile, UpdateProfileRequest};
 use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
@@ -8,8 +9,9 @@ pub async fn find_profile_by_user_id(user_id: Uuid, db: &PgPool) -> Result<Profi
     sqlx::query_as_unchecked!(
         Profile,
         r#"
-        SELECT id, user_id, bio, birth_date, phone, document, profession, avatar,
-               confirm_email, unsubscribe, access_level, dt_created, dt_updated
+        SELECT id, user_id, bio, birth_date, phone, phone_verified, document, document_type, profession, avatar,
+               confirm_email, unsubscribe, access_level, preferred_language,
+               email_course_announcements, email_marketing, dt_created, dt_updated
         FROM profiles
         WHERE user_id = $1
         "#,
@@ -27,22 +29,30 @@ pub async fn create_profile_in_tx(
     sqlx::query!(
         r#"
         INSERT INTO profiles (
-            id, user_id, bio, birth_date, phone, document, profession, avatar,
-            confirm_email, unsubscribe, access_level, dt_created, dt_updated
+            id, user_id, bio, birth_date, phone, phone_verified, document, document_type,
+            profession, avatar, confirm_email, unsubscribe, access_level, preferred_language,
+            email_course_announcements, email_marketing, dt_created, dt_updated
+        )
+        VALUES (
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
         "#,
         profile.id,
         profile.user_id,
         profile.bio,
         profile.birth_date,
         profile.phone,
+        profile.phone_verified,
         profile.document,
+        profile.document_type,
         profile.profession,
         profile.avatar,
         profile.confirm_email,
         profile.unsubscribe,
         profile.access_level,
+        profile.preferred_language,
+        profile.email_course_announcements,
+        profile.email_marketing,
         profile.dt_created,
         profile.dt_updated
     )
@@ -53,10 +63,13 @@ pub async fn create_profile_in_tx(
     Ok(())
 }
 
-pub async fn update_profile_fields_by_user_id(
+/// `payload.phone` deve chegar em E.164 e `payload.document` normalizado, acompanhado de
+/// `document_type`. Trocar o telefone desfaz a verificação.
+pub async fn update_profile_fields_by_user_id_in_tx(
     user_id: Uuid,
     payload: &UpdateProfileRequest,
-    db: &PgPool,
+    document_type: Option<&str>,
+    tx: &mut Transaction<'_, Postgres>,
 ) -> Result<(), sqlx::Error> {
     sqlx::query!(
         r#"
@@ -64,12 +77,18 @@ pub async fn update_profile_fields_by_user_id(
         SET 
             bio = COALESCE($1, bio),
             phone = COALESCE($2, phone),
+            phone_verified = CASE
+                WHEN $2::text IS NOT NULL AND $2 IS DISTINCT FROM phone THEN FALSE
+                ELSE phone_verified
+            END,
             birth_date = COALESCE($3, birth_date),
             profession = COALESCE($4, profession),
             document = COALESCE($5, document),
             avatar = COALESCE($6, avatar),
+            preferred_language = COALESCE($7, preferred_language),
+            document_type = COALESCE($8, document_type),
             dt_updated = NOW()
-        WHERE user_id = $7
+        WHERE user_id = $9
         "#,
         payload.bio,
         payload.phone,
@@ -77,6 +96,86 @@ pub async fn update_profile_fields_by_user_id(
         payload.profession,
         payload.document,
         payload.avatar,
+        payload.preferred_language,
+        document_type,
+        user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Trava o documento até o fim da transação, serializando cadastros e edições concorrentes
+/// com o mesmo número, e diz se outra conta ativa já o usa
+pub async fn lock_document_in_tx(
+    document: &str,
+    except_user_id: Uuid,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query!("SELECT pg_advisory_xact_lock(hashtext($1))", document)
+        .execute(&mut **tx)
+        .await?;
+
+    let row = sqlx::query!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1
+            FROM profiles p
+            JOIN users u ON u.id = p.user_id
+            WHERE p.document = $1
+              AND u.dt_deleted IS NULL
+              AND p.user_id <> $2
+        ) AS "in_use!"
+        "#,
+        document,
+        except_user_id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(row.in_use)
+}
+
+/// Se outra conta ativa já usa o documento (apenas dígitos)
+pub async fn document_in_use(
+    document: &str,
+    except_user_id: Option<Uuid>,
+    db: &PgPool,
+) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1
+            FROM profiles p
+            JOIN users u ON u.id = p.user_id
+            WHERE p.document = $1
+              AND u.dt_deleted IS NULL
+              AND ($2::uuid IS NULL OR p.user_id <> $2)
+        ) AS "in_use!"
+        "#,
+        document,
+        except_user_id
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(row.in_use)
+}
+
+pub async fn update_avatar(
+    user_id: Uuid,
+    avatar: Option<&str>,
+    db: &PgPool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE profiles
+        SET avatar = $1,
+            dt_updated = NOW()
+        WHERE user_id = $2
+        "#,
+        avatar,
         user_id
     )
     .execute(db)
@@ -85,6 +184,28 @@ pub async fn update_profile_fields_by_user_id(
     Ok(())
 }
 
+/// Marca o telefone como verificado só se ainda for o mesmo que recebeu o código
+pub async fn set_phone_verified(
+    user_id: Uuid,
+    phone: &str,
+    db: &PgPool,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE profiles
+        SET phone_verified = TRUE,
+            dt_updated = NOW()
+        WHERE user_id = $1 AND phone = $2
+        "#,
+        user_id,
+        phone
+    )
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
 pub async fn confirm_email(user_id: Uuid, db: &PgPool) -> Result<(), AppError> {
     sqlx::query!(
         r#"
@@ -104,3 +225,183 @@ pub async fn confirm_email(user_id: Uuid, db: &PgPool) -> Result<(), AppError> {
 
     Ok(())
 }
+
+pub async fn confirm_email_in_tx(
+    user_id: Uuid,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        UPDATE profiles
+        SET confirm_email = true,
+            dt_updated = NOW()
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Erro ao confirmar email: {:?}", err);
+        AppError::InternalError(Some("Erro ao confirmar email".into()))
+    })?;
+
+    Ok(())
+}
+
+pub async fn anonymize_profile_in_tx(
+    user_id: Uuid,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE profiles
+        SET bio = NULL,
+            birth_date = NULL,
+            phone = NULL,
+            phone_verified = FALSE,
+            document = NULL,
+            document_type = NULL,
+            profession = NULL,
+            avatar = NULL,
+            confirm_email = FALSE,
+            unsubscribe = TRUE,
+            email_course_announcements = FALSE,
+            email_marketing = FALSE,
+            dt_updated = NOW()
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn find_email_preferences(
+    user_id: Uuid,
+    db: &PgPool,
+) -> Result<Option<EmailPreferences>, sqlx::Error> {
+    sqlx::query_as!(
+        EmailPreferences,
+        r#"
+        SELECT TRUE AS "transactional!",
+               email_course_announcements AS course_announcements,
+               email_marketing AS marketing,
+               COALESCE(unsubscribe, FALSE) AS "unsubscribe!"
+        FROM profiles
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(db)
+    .await
+}
+
+pub async fn update_email_preferences(
+    user_id: Uuid,
+    course_announcements: Option<bool>,
+    marketing: Option<bool>,
+    unsubscribe: Option<bool>,
+    db: &PgPool,
+) -> Result<Option<EmailPreferences>, sqlx::Error> {
+    sqlx::query_as!(
+        EmailPreferences,
+        r#"
+        UPDATE profiles
+        SET email_course_announcements = COALESCE($2, email_course_announcements),
+            email_marketing = COALESCE($3, email_marketing),
+            unsubscribe = COALESCE($4, unsubscribe),
+            dt_updated = NOW()
+        WHERE user_id = $1
+        RETURNING TRUE AS "transactional!",
+                  email_course_announcements AS course_announcements,
+                  email_marketing AS marketing,
+                  COALESCE(unsubscribe, FALSE) AS "unsubscribe!"
+        "#,
+        user_id,
+        course_announcements,
+        marketing,
+        unsubscribe
+    )
+    .fetch_optional(db)
+    .await
+}
+
+pub async fn is_email_confirmed(user_id: Uuid, db: &PgPool) -> Result<bool, sqlx::Error> {
+    let confirmed = sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(confirm_email, FALSE) AS "confirmed!"
+        FROM profiles
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(confirmed.unwrap_or(false))
+}
+
+/// Indica se o usuário ainda aceita e-mails da categoria. Transacionais sempre são aceitos.
+pub async fn accepts_email_category(
+    user_id: Uuid,
+    category: &str,
+    db: &PgPool,
+) -> Result<bool, sqlx::Error> {
+    let accepts = sqlx::query_scalar!(
+        r#"
+        SELECT CASE
+                   WHEN $2 = 'transactional' THEN TRUE
+                   WHEN p.unsubscribe OR u.dt_deleted IS NOT NULL THEN FALSE
+                   WHEN $2 = 'course_announcements' THEN p.email_course_announcements
+                   WHEN $2 = 'marketing' THEN p.email_marketing
+                   ELSE FALSE
+               END AS "accepts!"
+        FROM profiles p
+        JOIN users u ON u.id = p.user_id
+        WHERE p.user_id = $1
+        "#,
+        user_id,
+        category
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(accepts.unwrap_or(false))
+}
+
+/// Usuários com e-mail confirmado que aceitam a categoria, em páginas ordenadas por id
+/// (`after` é o último id da página anterior)
+pub async fn list_email_recipients_page(
+    category: &str,
+    after: Option<Uuid>,
+    limit: i64,
+    db: &PgPool,
+) -> Result<Vec<EmailRecipient>, sqlx::Error> {
+    sqlx::query_as!(
+        EmailRecipient,
+        r#"
+        SELECT u.id AS user_id, u.email, u.first_name, p.preferred_language
+        FROM users u
+        JOIN profiles p ON p.user_id = u.id
+        WHERE u.dt_deleted IS NULL
+          AND p.confirm_email
+          AND NOT COALESCE(p.unsubscribe, FALSE)
+          AND CASE $1
+                  WHEN 'course_announcements' THEN p.email_course_announcements
+                  WHEN 'marketing' THEN p.email_marketing
+                  ELSE FALSE
+              END
+          AND ($2::UUID IS NULL OR u.id > $2)
+        ORDER BY u.id
+        LIMIT $3
+        "#,
+        category,
+        after,
+        limit
+    )
+    .fetch_all(db)
+    .await
+}
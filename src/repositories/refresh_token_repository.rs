@@ -0,0 +1,154 @@
+use crate::errors::app_error::AppError;
+use crate::models::refresh_token::RefreshToken;
+use chrono::NaiveDateTime;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+pub async fn create_refresh_token(
+    db: &PgPool,
+    user_id: Uuid,
+    family_id: Uuid,
+    token_hash: &str,
+    access_jti: &str,
+    expires_at: NaiveDateTime,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        INSERT INTO refresh_tokens (user_id, family_id, token_hash, access_jti, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        user_id,
+        family_id,
+        token_hash,
+        access_jti,
+        expires_at,
+    )
+    .execute(db)
+    .await
+    .map_err(|err| {
+        eprintln!("Erro ao criar refresh token: {:?}", err);
+        AppError::InternalError(Some("Erro ao criar refresh token".into()))
+    })?;
+
+    Ok(())
+}
+
+/// Marca o refresh token como usado de forma atômica.
+/// Retorna `None` se o token não existe, já foi usado ou foi revogado.
+pub async fn consume_refresh_token(
+    db: &PgPool,
+    token_hash: &str,
+) -> Result<Option<RefreshToken>, AppError> {
+    sqlx::query_as!(
+        RefreshToken,
+        r#"
+        UPDATE refresh_tokens
+        SET used_at = NOW()
+        WHERE token_hash = $1 AND used_at IS NULL AND revoked_at IS NULL
+        RETURNING id, user_id, family_id, token_hash, access_jti, expires_at,
+                  created_at, used_at, revoked_at
+        "#,
+        token_hash
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(|err| {
+        eprintln!("Erro ao consumir refresh token: {:?}", err);
+        AppError::InternalError(Some("Erro ao consumir refresh token".into()))
+    })
+}
+
+pub async fn find_refresh_token_by_hash(
+    db: &PgPool,
+    token_hash: &str,
+) -> Result<Option<RefreshToken>, AppError> {
+    sqlx::query_as!(
+        RefreshToken,
+        r#"
+        SELECT id, user_id, family_id, token_hash, access_jti, expires_at,
+               created_at, used_at, revoked_at
+        FROM refresh_tokens
+        WHERE token_hash = $1
+        "#,
+        token_hash
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(|err| {
+        eprintln!("Erro ao buscar refresh token: {:?}", err);
+        AppError::InternalError(Some("Erro ao buscar refresh token".into()))
+    })
+}
+
+pub async fn revoke_family_in_tx(
+    family_id: Uuid,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<Vec<String>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        UPDATE refresh_tokens
+        SET revoked_at = NOW()
+        WHERE family_id = $1 AND revoked_at IS NULL
+        RETURNING access_jti
+        "#,
+        family_id
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| r.access_jti).collect())
+}
+
+pub async fn revoke_all_by_user_in_tx(
+    user_id: Uuid,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<Vec<String>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        UPDATE refresh_tokens
+        SET revoked_at = NOW()
+        WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+        RETURNING access_jti
+        "#,
+        user_id
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| r.access_jti).collect())
+}
+
+pub async fn revoke_all_by_user_except_family_in_tx(
+    user_id: Uuid,
+    keep_family_id: Uuid,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<Vec<String>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        UPDATE refresh_tokens
+        SET revoked_at = NOW()
+        WHERE user_id = $1 AND family_id <> $2 AND revoked_at IS NULL AND expires_at > NOW()
+        RETURNING access_jti
+        "#,
+        user_id,
+        keep_family_id
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| r.access_jti).collect())
+}
+
+pub async fn delete_expired(before: NaiveDateTime, db: &PgPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM refresh_tokens
+        WHERE expires_at <= $1
+        "#,
+        before
+    )
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
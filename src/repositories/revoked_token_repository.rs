@@ -0,0 +1,57 @@
+use crate::errors::app_error::AppError;
+use chrono::NaiveDateTime;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+pub async fn revoke_jtis_in_tx(
+    user_id: Uuid,
+    jtis: &[String],
+    expires_at: NaiveDateTime,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO revoked_tokens (jti, user_id, expires_at)
+        SELECT jti, $2, $3 FROM UNNEST($1::TEXT[]) AS jti
+        ON CONFLICT (jti) DO NOTHING
+        "#,
+        jtis,
+        user_id,
+        expires_at
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn is_revoked(jti: &str, db: &PgPool) -> Result<bool, AppError> {
+    let revoked = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(SELECT 1 FROM revoked_tokens WHERE jti = $1) as "exists!"
+        "#,
+        jti
+    )
+    .fetch_one(db)
+    .await
+    .map_err(|err| {
+        eprintln!("Erro ao consultar lista de revogação: {:?}", err);
+        AppError::InternalError(Some("Erro ao validar token".into()))
+    })?;
+
+    Ok(revoked)
+}
+
+pub async fn delete_expired(before: NaiveDateTime, db: &PgPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM revoked_tokens
+        WHERE expires_at <= $1
+        "#,
+        before
+    )
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
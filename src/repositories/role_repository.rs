@@ -0,0 +1,124 @@
+use crate::models::role::Role;
+use sqlx::{PgPool, Postgres, Result, Transaction};
+use uuid::Uuid;
+
+pub async fn find_role_names_by_user(user_id: Uuid, db: &PgPool) -> Result<Vec<String>> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT r.name
+        FROM user_roles ur
+        JOIN roles r ON r.id = ur.role_id
+        WHERE ur.user_id = $1
+        ORDER BY r.name
+        "#,
+        user_id
+    )
+    .fetch_all(db)
+    .await
+}
+
+pub async fn find_permission_codes_by_user(user_id: Uuid, db: &PgPool) -> Result<Vec<String>> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT p.code
+        FROM user_roles ur
+        JOIN role_permissions rp ON rp.role_id = ur.role_id
+        JOIN permissions p ON p.id = rp.permission_id
+        WHERE ur.user_id = $1
+        ORDER BY p.code
+        "#,
+        user_id
+    )
+    .fetch_all(db)
+    .await
+}
+
+pub async fn list_roles(db: &PgPool) -> Result<Vec<Role>> {
+    sqlx::query_as!(
+        Role,
+        r#"
+        SELECT id, name, description, dt_created
+        FROM roles
+        ORDER BY name
+        "#
+    )
+    .fetch_all(db)
+    .await
+}
+
+pub async fn find_permission_codes_by_role(role_id: Uuid, db: &PgPool) -> Result<Vec<String>> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT p.code
+        FROM role_permissions rp
+        JOIN permissions p ON p.id = rp.permission_id
+        WHERE rp.role_id = $1
+        ORDER BY p.code
+        "#,
+        role_id
+    )
+    .fetch_all(db)
+    .await
+}
+
+pub async fn assign_role_in_tx(
+    user_id: Uuid,
+    role_name: &str,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO user_roles (user_id, role_id)
+        SELECT $1, id FROM roles WHERE name = $2
+        ON CONFLICT DO NOTHING
+        "#,
+        user_id,
+        role_name
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn role_exists(role_name: &str, db: &PgPool) -> Result<bool> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(SELECT 1 FROM roles WHERE name = $1) as "exists!"
+        "#,
+        role_name
+    )
+    .fetch_one(db)
+    .await
+}
+
+pub async fn assign_role(user_id: Uuid, role_name: &str, db: &PgPool) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO user_roles (user_id, role_id)
+        SELECT $1, id FROM roles WHERE name = $2
+        ON CONFLICT DO NOTHING
+        "#,
+        user_id,
+        role_name
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn remove_role(user_id: Uuid, role_name: &str, db: &PgPool) -> Result<u64> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM user_roles
+        WHERE user_id = $1 AND role_id = (SELECT id FROM roles WHERE name = $2)
+        "#,
+        user_id,
+        role_name
+    )
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,158 @@
+use crate::errors::app_error::AppError;
+use crate::models::session::{SessionInfo, UserSession};
+use chrono::NaiveDateTime;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+pub async fn create_session(
+    db: &PgPool,
+    session_id: Uuid,
+    user_id: Uuid,
+    info: &SessionInfo,
+    expires_at: NaiveDateTime,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        INSERT INTO user_sessions (id, user_id, user_agent, ip_address, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        session_id,
+        user_id,
+        info.user_agent,
+        info.ip_address,
+        expires_at,
+    )
+    .execute(db)
+    .await
+    .map_err(|err| {
+        eprintln!("Erro ao criar sessão: {:?}", err);
+        AppError::InternalError(Some("Erro ao criar sessão".into()))
+    })?;
+
+    Ok(())
+}
+
+pub async fn touch_session(
+    db: &PgPool,
+    session_id: Uuid,
+    expires_at: NaiveDateTime,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        UPDATE user_sessions
+        SET last_seen_at = NOW(), expires_at = $1
+        WHERE id = $2 AND revoked_at IS NULL
+        "#,
+        expires_at,
+        session_id
+    )
+    .execute(db)
+    .await
+    .map_err(|err| {
+        eprintln!("Erro ao atualizar sessão: {:?}", err);
+        AppError::InternalError(Some("Erro ao atualizar sessão".into()))
+    })?;
+
+    Ok(())
+}
+
+pub async fn is_session_active(
+    session_id: Uuid,
+    user_id: Uuid,
+    db: &PgPool,
+) -> Result<bool, AppError> {
+    let active = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM user_sessions
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL AND expires_at > NOW()
+        ) as "exists!"
+        "#,
+        session_id,
+        user_id
+    )
+    .fetch_one(db)
+    .await
+    .map_err(|err| {
+        eprintln!("Erro ao consultar sessão: {:?}", err);
+        AppError::InternalError(Some("Erro ao validar sessão".into()))
+    })?;
+
+    Ok(active)
+}
+
+pub async fn list_active_sessions_by_user(
+    user_id: Uuid,
+    db: &PgPool,
+) -> Result<Vec<UserSession>, sqlx::Error> {
+    sqlx::query_as!(
+        UserSession,
+        r#"
+        SELECT id, user_id, user_agent, ip_address, created_at, last_seen_at, expires_at, revoked_at
+        FROM user_sessions
+        WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+        ORDER BY last_seen_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(db)
+    .await
+}
+
+pub async fn revoke_session_in_tx(
+    session_id: Uuid,
+    user_id: Uuid,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE user_sessions
+        SET revoked_at = NOW()
+        WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+        "#,
+        session_id,
+        user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn revoke_all_sessions_by_user_in_tx(
+    user_id: Uuid,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE user_sessions
+        SET revoked_at = NOW()
+        WHERE user_id = $1 AND revoked_at IS NULL
+        "#,
+        user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn revoke_other_sessions_by_user_in_tx(
+    user_id: Uuid,
+    keep_session_id: Uuid,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE user_sessions
+        SET revoked_at = NOW()
+        WHERE user_id = $1 AND id <> $2 AND revoked_at IS NULL
+        "#,
+        user_id,
+        keep_session_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(result.rows_affected())
+}
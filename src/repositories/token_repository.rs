@@ -1,27 +1,29 @@
 use crate::errors::app_error::AppError;
 use crate::models::token::UserToken;
 use chrono::NaiveDateTime;
-use sqlx::{PgPool, Result};
+use sqlx::{PgPool, Postgres, Result, Transaction};
 use uuid::Uuid;
 
-pub async fn create_token(
-    db: &PgPool,
+pub async fn create_token_in_tx(
     user_id: Uuid,
-    code: &str,
+    code_hash: &str,
     token_type: &str,
     expires_at: NaiveDateTime,
+    new_email: Option<&str>,
+    tx: &mut Transaction<'_, Postgres>,
 ) -> Result<(), AppError> {
     sqlx::query!(
         r#"
-        INSERT INTO user_tokens (user_id, code, token_type, expires_at)
-        VALUES ($1, $2, $3, $4)
+        INSERT INTO user_tokens (user_id, code_hash, token_type, expires_at, new_email)
+        VALUES ($1, $2, $3, $4, $5)
         "#,
         user_id,
-        code,
+        code_hash,
         token_type,
         expires_at,
+        new_email,
     )
-    .execute(db)
+    .execute(&mut **tx)
     .await
     .map_err(|err| {
         eprintln!("Erro ao criar token: {:?}", err);
@@ -31,41 +33,110 @@ pub async fn create_token(
     Ok(())
 }
 
-pub async fn find_token_by_code(db: &PgPool, code: &str) -> Result<Option<UserToken>, AppError> {
-    let result = sqlx::query_as!(
+pub async fn find_token_by_hash_in_tx(
+    code_hash: &str,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<Option<UserToken>, AppError> {
+    sqlx::query_as!(
         UserToken,
         r#"
-        SELECT id, user_id, code, token_type::TEXT, expires_at, created_at, consumed
+        SELECT id, user_id, code_hash, token_type, expires_at, created_at, consumed, new_email
         FROM user_tokens
-        WHERE code = $1
+        WHERE code_hash = $1
         "#,
-        code
+        code_hash
     )
-    .fetch_optional(db)
+    .fetch_optional(&mut **tx)
     .await
     .map_err(|err| {
-        eprintln!("Erro ao buscar token por código: {:?}", err);
+        eprintln!("Erro ao buscar token: {:?}", err);
         AppError::InternalError(Some("Erro ao buscar token".into()))
-    })?;
+    })
+}
 
-    Ok(result)
+/// Marca o token como consumido só se ainda for válido. Como a checagem e a escrita são o
+/// mesmo `UPDATE`, duas requisições simultâneas nunca consomem o mesmo código.
+pub async fn consume_token_in_tx(
+    code_hash: &str,
+    token_type: &str,
+    now: NaiveDateTime,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<Option<UserToken>, AppError> {
+    sqlx::query_as!(
+        UserToken,
+        r#"
+        UPDATE user_tokens
+        SET consumed = true
+        WHERE code_hash = $1 AND token_type = $2 AND NOT consumed AND expires_at > $3
+        RETURNING id, user_id, code_hash, token_type, expires_at, created_at, consumed, new_email
+        "#,
+        code_hash,
+        token_type,
+        now
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Erro ao consumir token: {:?}", err);
+        AppError::InternalError(Some("Erro ao atualizar token".into()))
+    })
 }
 
-pub async fn update_token(db: &PgPool, code: &str) -> Result<(), AppError> {
+/// Invalida os tokens pendentes de um tipo, para que só o mais recente continue valendo
+pub async fn consume_pending_tokens_in_tx(
+    user_id: Uuid,
+    token_type: &str,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), AppError> {
     sqlx::query!(
         r#"
         UPDATE user_tokens
         SET consumed = true
-        WHERE code = $1
+        WHERE user_id = $1 AND token_type = $2 AND NOT consumed
         "#,
-        code
+        user_id,
+        token_type
     )
-    .execute(db)
+    .execute(&mut **tx)
     .await
     .map_err(|err| {
-        eprintln!("Erro ao atualizar token: {:?}", err);
+        eprintln!("Erro ao invalidar tokens: {:?}", err);
         AppError::InternalError(Some("Erro ao atualizar token".into()))
     })?;
 
     Ok(())
 }
+
+/// Quando foi emitido o token mais recente do tipo para o usuário
+pub async fn find_latest_created_at(
+    user_id: Uuid,
+    token_type: &str,
+    db: &PgPool,
+) -> Result<Option<NaiveDateTime>> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT MAX(created_at)
+        FROM user_tokens
+        WHERE user_id = $1 AND token_type = $2
+        "#,
+        user_id,
+        token_type
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// Remove os tokens expirados e retorna quantos foram apagados
+pub async fn delete_expired(now: NaiveDateTime, db: &PgPool) -> Result<u64> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM user_tokens
+        WHERE expires_at <= $1
+        "#,
+        now
+    )
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
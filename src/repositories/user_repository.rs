@@ -1,6 +1,6 @@
 use crate::errors::app_error::AppError;
-use crate::models::user::User;
-use chrono::Utc;
+use crate::models::user::{AdminUserQuery, AdminUserSummary, PublicUser, User};
+use chrono::{NaiveDateTime, Utc};
 use sqlx::{PgPool, Postgres, Result, Transaction};
 use uuid::Uuid;
 
@@ -89,32 +89,144 @@ pub async fn create_user_in_tx(
         println!("[DEBUG][create_user_in_tx] Erro SQLX: {:?}", err);
     }
 
-    result.map_err(|_| AppError::BadRequest(Some("Erro ao criar o usuario".into())))?;
+    result.map_err(|err| match err {
+        sqlx::Error::Database(db_err) if db_err.constraint() == Some("users_username_lower_key") => {
+            AppError::Conflict(Some("Nome de usuário já está em uso".into()))
+        }
+        _ => AppError::BadRequest(Some("Erro ao criar o usuario".into())),
+    })?;
     Ok(())
 }
 
-#[allow(dead_code)]
-pub async fn update_user(user_id: Uuid, user: &User, db: &PgPool) -> Result<(), AppError> {
-    sqlx::query!(
+/// Usernames `base` e `base_N` já ocupados, em minúsculas. `base` só tem `[a-z0-9_]`, então
+/// pode entrar na expressão regular sem escape.
+pub async fn find_usernames_with_base(
+    base: &str,
+    except_user_id: Option<Uuid>,
+    db: &PgPool,
+) -> Result<Vec<String>> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT LOWER(username) AS "username!"
+        FROM users
+        WHERE LOWER(username) ~ ('^' || $1 || '(_[0-9]+)?$')
+          AND ($2::uuid IS NULL OR id <> $2)
+        "#,
+        base,
+        except_user_id
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// Se outra conta, inclusive excluída e ainda não anonimizada, usa o username
+pub async fn username_in_use(username: &str, except_user_id: Uuid, db: &PgPool) -> Result<bool> {
+    let row = sqlx::query!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM users WHERE LOWER(username) = LOWER($1) AND id <> $2
+        ) AS "in_use!"
+        "#,
+        username,
+        except_user_id
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(row.in_use)
+}
+
+pub async fn update_username(user_id: Uuid, username: &str, db: &PgPool) -> Result<(), AppError> {
+    let result = sqlx::query!(
         r#"
         UPDATE users
-        SET username = $1, email = $2, first_name = $3, last_name = $4, dt_updated = $5
-        WHERE dt_deleted IS NULL AND id = $6
+        SET username = $2,
+            dt_updated = NOW()
+        WHERE id = $1 AND dt_deleted IS NULL
         "#,
-        user.username,
-        user.email,
-        user.first_name,
-        user.last_name,
-        user.dt_updated,
-        user_id
+        user_id,
+        username
     )
     .execute(db)
     .await
-    .map_err(|_| AppError::BadRequest(Some("Erro ao atualizar usuário".into())))?;
+    .map_err(|err| match err {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            AppError::Conflict(Some("Nome de usuário já está em uso".into()))
+        }
+        err => AppError::DatabaseError(Some(format!("Erro ao atualizar username: {}", err))),
+    })?;
 
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(Some("Usuário não encontrado".into())));
+    }
     Ok(())
 }
 
+/// Conta ativa (não excluída nem suspensa) para o perfil público
+pub async fn find_public_user_by_username(
+    username: &str,
+    db: &PgPool,
+) -> Result<Option<PublicUser>> {
+    sqlx::query_as!(
+        PublicUser,
+        r#"
+        SELECT u.id, u.username, u.first_name, p.bio, p.profession, p.avatar, u.dt_created
+        FROM users u
+        JOIN profiles p ON p.user_id = u.id
+        WHERE LOWER(u.username) = LOWER($1)
+          AND u.dt_deleted IS NULL
+          AND u.dt_suspended IS NULL
+        "#,
+        username
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Troca o e-mail confirmado. Com `new_username`, o username também é trocado, desde que
+/// ainda esteja livre; caso contrário o atual é mantido.
+pub async fn update_email_in_tx(
+    user_id: Uuid,
+    email: &str,
+    new_username: Option<&str>,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<User, AppError> {
+    sqlx::query_as!(
+        User,
+        r#"
+        UPDATE users
+        SET email = $2,
+            username = CASE
+                WHEN $3::TEXT IS NOT NULL
+                     AND NOT EXISTS (
+                         SELECT 1 FROM users WHERE LOWER(username) = LOWER($3) AND id <> $1
+                     )
+                THEN $3
+                ELSE username
+            END,
+            dt_updated = NOW()
+        WHERE id = $1 AND dt_deleted IS NULL
+        RETURNING id, username, email, first_name, last_name, password,
+                  dt_created, dt_updated, dt_deleted
+        "#,
+        user_id,
+        email,
+        new_username
+    )
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(|err| match err {
+        sqlx::Error::RowNotFound => AppError::NotFound(Some("Usuário não encontrado".into())),
+        sqlx::Error::Database(db_err) if db_err.constraint() == Some("users_username_lower_key") => {
+            AppError::Conflict(Some("Nome de usuário já está em uso".into()))
+        }
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            AppError::Conflict(Some("E-mail já está em uso".into()))
+        }
+        err => AppError::DatabaseError(Some(format!("Erro ao atualizar e-mail: {}", err))),
+    })
+}
+
 pub async fn update_user_fields(
     db: &PgPool,
     user_id: Uuid,
@@ -160,10 +272,92 @@ pub async fn soft_delete_user(db: &PgPool, user_id: Uuid) -> Result<u64> {
     Ok(result.rows_affected())
 }
 
-pub async fn update_user_password(
+/// Conta excluída mais recente com o e-mail que ainda não foi anonimizada, ou seja, que ainda
+/// pode ser restaurada
+pub async fn find_deleted_user_by_email(
+    email: &str,
     db: &PgPool,
+) -> Result<Option<User>, AppError> {
+    sqlx::query_as!(
+        User,
+        r#"
+        SELECT id, username, email, first_name, last_name, password, dt_created, dt_updated, dt_deleted
+        FROM users
+        WHERE dt_deleted IS NOT NULL AND dt_anonymized IS NULL AND email = $1
+        ORDER BY dt_deleted DESC
+        LIMIT 1
+        "#,
+        email
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(|e| AppError::DatabaseError(Some(e.to_string())))
+}
+
+pub async fn restore_user(db: &PgPool, user_id: Uuid) -> Result<u64> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE users
+        SET dt_deleted = NULL,
+            dt_updated = NOW()
+        WHERE id = $1 AND dt_deleted IS NOT NULL AND dt_anonymized IS NULL
+        "#,
+        user_id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Contas excluídas antes de `deleted_before` e ainda não anonimizadas
+pub async fn list_pending_anonymization(
+    deleted_before: NaiveDateTime,
+    db: &PgPool,
+) -> Result<Vec<Uuid>> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT id
+        FROM users
+        WHERE dt_deleted < $1 AND dt_anonymized IS NULL
+        ORDER BY dt_deleted
+        "#,
+        deleted_before
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// Substitui os dados pessoais por valores neutros. O e-mail e o username ficam livres para um
+/// novo cadastro e a senha deixa de ser um hash válido.
+pub async fn anonymize_user_in_tx(
+    user_id: Uuid,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<u64> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE users
+        SET username = 'deleted_' || replace(id::text, '-', ''),
+            email = 'deleted_' || replace(id::text, '-', '') || '@anonimizado.invalid',
+            first_name = 'Usuário',
+            last_name = 'removido',
+            password = '!',
+            dt_anonymized = NOW(),
+            dt_updated = NOW()
+        WHERE id = $1 AND dt_deleted IS NOT NULL AND dt_anonymized IS NULL
+        "#,
+        user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn update_user_password_in_tx(
     user_id: Uuid,
     hashed_password: &str,
+    tx: &mut Transaction<'_, Postgres>,
 ) -> Result<(), AppError> {
     sqlx::query!(
         r#"
@@ -175,7 +369,7 @@ pub async fn update_user_password(
         hashed_password,
         user_id
     )
-    .execute(db)
+    .execute(&mut **tx)
     .await
     .map_err(|err| {
         eprintln!("Erro ao atualizar senha: {:?}", err);
@@ -184,3 +378,150 @@ pub async fn update_user_password(
 
     Ok(())
 }
+
+/// Troca só o formato do hash (rehash no login); a senha continua a mesma
+pub async fn update_password_hash(user_id: Uuid, password_hash: &str, db: &PgPool) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET password = $1
+        WHERE id = $2
+        "#,
+        password_hash,
+        user_id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn search_users_for_admin(
+    query: &AdminUserQuery,
+    db: &PgPool,
+) -> Result<Vec<AdminUserSummary>> {
+    sqlx::query_as!(
+        AdminUserSummary,
+        r#"
+        SELECT u.id, u.username, u.email, u.first_name, u.last_name,
+               ARRAY(
+                   SELECT r.name FROM user_roles ur JOIN roles r ON r.id = ur.role_id
+                   WHERE ur.user_id = u.id ORDER BY r.name
+               ) AS "roles!",
+               COALESCE(p.confirm_email, FALSE) AS "confirm_email!",
+               u.dt_created, u.dt_deleted, u.dt_suspended, u.suspension_reason
+        FROM users u
+        JOIN profiles p ON p.user_id = u.id
+        WHERE ($1::TEXT IS NULL OR u.email ILIKE '%' || $1 || '%')
+          AND ($2::TEXT IS NULL OR (u.first_name || ' ' || u.last_name) ILIKE '%' || $2 || '%')
+          AND ($3::TEXT IS NULL OR EXISTS (
+              SELECT 1 FROM user_roles ur JOIN roles r ON r.id = ur.role_id
+              WHERE ur.user_id = u.id AND r.name = $3
+          ))
+          AND ($4::BOOL IS NULL OR COALESCE(p.confirm_email, FALSE) = $4)
+          AND ($5::BOOL IS NULL OR (u.dt_deleted IS NOT NULL) = $5)
+          AND ($6::BOOL IS NULL OR (u.dt_suspended IS NOT NULL) = $6)
+        ORDER BY u.dt_created DESC
+        LIMIT $7 OFFSET $8
+        "#,
+        query.email,
+        query.name,
+        query.role,
+        query.confirmed,
+        query.deleted,
+        query.suspended,
+        query.limit,
+        query.offset
+    )
+    .fetch_all(db)
+    .await
+}
+
+pub async fn count_users_for_admin(query: &AdminUserQuery, db: &PgPool) -> Result<i64> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM users u
+        JOIN profiles p ON p.user_id = u.id
+        WHERE ($1::TEXT IS NULL OR u.email ILIKE '%' || $1 || '%')
+          AND ($2::TEXT IS NULL OR (u.first_name || ' ' || u.last_name) ILIKE '%' || $2 || '%')
+          AND ($3::TEXT IS NULL OR EXISTS (
+              SELECT 1 FROM user_roles ur JOIN roles r ON r.id = ur.role_id
+              WHERE ur.user_id = u.id AND r.name = $3
+          ))
+          AND ($4::BOOL IS NULL OR COALESCE(p.confirm_email, FALSE) = $4)
+          AND ($5::BOOL IS NULL OR (u.dt_deleted IS NOT NULL) = $5)
+          AND ($6::BOOL IS NULL OR (u.dt_suspended IS NOT NULL) = $6)
+        "#,
+        query.email,
+        query.name,
+        query.role,
+        query.confirmed,
+        query.deleted,
+        query.suspended
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// Busca o usuário para a visão administrativa, inclusive se excluído
+pub async fn find_user_for_admin(user_id: Uuid, db: &PgPool) -> Result<AdminUserSummary, AppError> {
+    sqlx::query_as!(
+        AdminUserSummary,
+        r#"
+        SELECT u.id, u.username, u.email, u.first_name, u.last_name,
+               ARRAY(
+                   SELECT r.name FROM user_roles ur JOIN roles r ON r.id = ur.role_id
+                   WHERE ur.user_id = u.id ORDER BY r.name
+               ) AS "roles!",
+               COALESCE(p.confirm_email, FALSE) AS "confirm_email!",
+               u.dt_created, u.dt_deleted, u.dt_suspended, u.suspension_reason
+        FROM users u
+        JOIN profiles p ON p.user_id = u.id
+        WHERE u.id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?
+    .ok_or_else(|| AppError::NotFound(Some("Usuário não encontrado!".into())))
+}
+
+/// Suspende (`suspended_at` preenchido) ou reativa a conta. Contas excluídas não são alteradas.
+pub async fn set_suspension(
+    user_id: Uuid,
+    suspended_at: Option<NaiveDateTime>,
+    reason: Option<&str>,
+    db: &PgPool,
+) -> Result<u64> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE users
+        SET dt_suspended = $2,
+            suspension_reason = $3,
+            dt_updated = NOW()
+        WHERE id = $1 AND dt_deleted IS NULL
+        "#,
+        user_id,
+        suspended_at,
+        reason
+    )
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn is_suspended(user_id: Uuid, db: &PgPool) -> Result<bool> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM users WHERE id = $1 AND dt_suspended IS NOT NULL
+        ) AS "suspended!"
+        "#,
+        user_id
+    )
+    .fetch_one(db)
+    .await
+}
@@ -0,0 +1,134 @@
+use crate::config::app_state::AppState;
+use crate::errors::app_error::AppError;
+use crate::extensions::request_user_ext::RequestUserExt;
+use crate::middleware::permission_middleware::RequirePermission;
+use crate::models::role::permissions;
+use crate::models::user::{
+    AdminUpdateUserRequest, AdminUserQuery, AdminUserRequest, SuspendUserRequest,
+};
+use crate::services::admin_user_service;
+use actix_web::{HttpRequest, HttpResponse, get, patch, post, web};
+use uuid::Uuid;
+
+#[post(
+    "/admin/users/",
+    wrap = "RequirePermission::new(permissions::USERS_MANAGE)"
+)]
+pub async fn provision_user(
+    req: HttpRequest,
+    payload: web::Json<AdminUserRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let admin_id = req.user_id()?;
+    let mut user =
+        admin_user_service::provision_user(payload.into_inner(), admin_id, &state).await?;
+    user.profile.apply_viewer_visibility(
+        Some(admin_id),
+        req.has_permission(permissions::USERS_MANAGE),
+    );
+    Ok(HttpResponse::Created().json(user))
+}
+
+#[post(
+    "/admin/users/{id}/unlock/",
+    wrap = "RequirePermission::new(permissions::USERS_MANAGE)"
+)]
+pub async fn unlock_user(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let admin_id = req.user_id()?;
+    admin_user_service::unlock_user(path.into_inner(), admin_id, &state).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[get(
+    "/admin/users/",
+    wrap = "RequirePermission::new(permissions::USERS_MANAGE)"
+)]
+pub async fn list_users(
+    query: web::Query<AdminUserQuery>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let users = admin_user_service::list_users(query.into_inner(), &state).await?;
+    Ok(HttpResponse::Ok().json(users))
+}
+
+#[get(
+    "/admin/users/{id}/",
+    wrap = "RequirePermission::new(permissions::USERS_MANAGE)"
+)]
+pub async fn get_user(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let admin_id = req.user_id()?;
+    let mut user = admin_user_service::get_user(path.into_inner(), &state).await?;
+    user.profile.apply_viewer_visibility(
+        Some(admin_id),
+        req.has_permission(permissions::USERS_MANAGE),
+    );
+    Ok(HttpResponse::Ok().json(user))
+}
+
+#[patch(
+    "/admin/users/{id}/",
+    wrap = "RequirePermission::new(permissions::USERS_MANAGE)"
+)]
+pub async fn update_user(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    payload: web::Json<AdminUpdateUserRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let admin_id = req.user_id()?;
+    let mut user =
+        admin_user_service::update_user(path.into_inner(), payload.into_inner(), admin_id, &state)
+            .await?;
+    user.profile.apply_viewer_visibility(
+        Some(admin_id),
+        req.has_permission(permissions::USERS_MANAGE),
+    );
+    Ok(HttpResponse::Ok().json(user))
+}
+
+#[post(
+    "/admin/users/{id}/suspend/",
+    wrap = "RequirePermission::new(permissions::USERS_MANAGE)"
+)]
+pub async fn suspend_user(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    payload: web::Json<SuspendUserRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let admin_id = req.user_id()?;
+    let mut user =
+        admin_user_service::suspend_user(path.into_inner(), payload.into_inner(), admin_id, &state)
+            .await?;
+    user.profile.apply_viewer_visibility(
+        Some(admin_id),
+        req.has_permission(permissions::USERS_MANAGE),
+    );
+    Ok(HttpResponse::Ok().json(user))
+}
+
+#[post(
+    "/admin/users/{id}/unsuspend/",
+    wrap = "RequirePermission::new(permissions::USERS_MANAGE)"
+)]
+pub async fn unsuspend_user(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let admin_id = req.user_id()?;
+    let mut user = admin_user_service::unsuspend_user(path.into_inner(), admin_id, &state).await?;
+    user.profile.apply_viewer_visibility(
+        Some(admin_id),
+        req.has_permission(permissions::USERS_MANAGE),
+    );
+    Ok(HttpResponse::Ok().json(user))
+}
@@ -0,0 +1,116 @@
+use crate::config::app_state::AppState;
+use crate::errors::app_error::AppError;
+use crate::extensions::request_user_ext::RequestUserExt;
+use crate::middleware::permission_middleware::{RejectApiKey, RequirePermission};
+use crate::models::api_key::{ApiKeyExpiryRequest, ApiKeyRequest};
+use crate::models::role::permissions;
+use crate::services::api_key_service;
+use actix_web::{HttpRequest, HttpResponse, delete, get, patch, post, web};
+use uuid::Uuid;
+
+#[post("/me/api-keys/", wrap = "RejectApiKey")]
+pub async fn create_api_key(
+    req: HttpRequest,
+    payload: web::Json<ApiKeyRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+
+    let created =
+        api_key_service::create_api_key(user_id, payload.into_inner(), user_id, &state.db).await?;
+    Ok(HttpResponse::Created().json(created))
+}
+
+#[get("/me/api-keys/", wrap = "RejectApiKey")]
+pub async fn list_api_keys(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+
+    let keys = api_key_service::list_api_keys(user_id, &state.db).await?;
+    Ok(HttpResponse::Ok().json(keys))
+}
+
+#[patch("/me/api-keys/{id}/", wrap = "RejectApiKey")]
+pub async fn update_api_key_expiry(
+    req: HttpRequest,
+    id: web::Path<Uuid>,
+    payload: web::Json<ApiKeyExpiryRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+
+    let key = api_key_service::update_api_key_expiry(
+        id.into_inner(),
+        user_id,
+        payload.into_inner(),
+        &state.db,
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(key))
+}
+
+#[delete("/me/api-keys/{id}/", wrap = "RejectApiKey")]
+pub async fn revoke_api_key(
+    req: HttpRequest,
+    id: web::Path<Uuid>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+
+    api_key_service::revoke_api_key(id.into_inner(), user_id, &state.db).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Chaves de contas de serviço, criadas por um administrador em nome do usuário
+#[post(
+    "/admin/users/{id}/api-keys/",
+    wrap = "RequirePermission::new(permissions::USERS_MANAGE)",
+    wrap = "RejectApiKey"
+)]
+pub async fn admin_create_api_key(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    payload: web::Json<ApiKeyRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let admin_id = req.user_id()?;
+
+    let created = api_key_service::create_api_key(
+        path.into_inner(),
+        payload.into_inner(),
+        admin_id,
+        &state.db,
+    )
+    .await?;
+    Ok(HttpResponse::Created().json(created))
+}
+
+#[get(
+    "/admin/users/{id}/api-keys/",
+    wrap = "RequirePermission::new(permissions::USERS_MANAGE)",
+    wrap = "RejectApiKey"
+)]
+pub async fn admin_list_api_keys(
+    path: web::Path<Uuid>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let keys = api_key_service::list_api_keys(path.into_inner(), &state.db).await?;
+    Ok(HttpResponse::Ok().json(keys))
+}
+
+#[delete(
+    "/admin/users/{id}/api-keys/{key_id}/",
+    wrap = "RequirePermission::new(permissions::USERS_MANAGE)",
+    wrap = "RejectApiKey"
+)]
+pub async fn admin_revoke_api_key(
+    path: web::Path<(Uuid, Uuid)>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let (user_id, key_id) = path.into_inner();
+
+    api_key_service::revoke_api_key(key_id, user_id, &state.db).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
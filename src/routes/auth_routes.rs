@@ -0,0 +1,67 @@
+use crate::config::app_state::AppState;
+use crate::errors::app_error::AppError;
+use crate::extensions::request_user_ext::RequestUserExt;
+use crate::middleware::permission_middleware::RejectApiKey;
+use crate::middleware::rate_limit_middleware::{RateLimitGroup, RateLimiter};
+use crate::models::refresh_token::RefreshTokenRequest;
+use crate::services::auth_service;
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, web};
+use uuid::Uuid;
+
+#[post("/token/refresh/", wrap = "RateLimiter::new(RateLimitGroup::Auth)")]
+pub async fn refresh_token(
+    payload: web::Json<RefreshTokenRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let pair = auth_service::refresh_token_pair(&payload.refresh_token, &state).await?;
+    Ok(HttpResponse::Ok().json(pair))
+}
+
+#[post("/logout/", wrap = "RejectApiKey")]
+pub async fn logout(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+    let session_id = req.session_id()?;
+
+    auth_service::revoke_session(user_id, session_id, &state.db).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[post("/logout-all/", wrap = "RejectApiKey")]
+pub async fn logout_all(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+
+    auth_service::revoke_all_sessions(user_id, &state.db).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[get("/me/sessions/", wrap = "RejectApiKey")]
+pub async fn list_sessions(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+    let session_id = req.session_id()?;
+
+    let sessions = auth_service::list_sessions(user_id, session_id, &state).await?;
+    Ok(HttpResponse::Ok().json(sessions))
+}
+
+#[delete("/me/sessions/{id}/", wrap = "RejectApiKey")]
+pub async fn delete_session(
+    req: HttpRequest,
+    id: web::Path<Uuid>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+
+    if !auth_service::revoke_session(user_id, id.into_inner(), &state.db).await? {
+        return Err(AppError::NotFound(Some("Sessão não encontrada".into())));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -1,24 +1,22 @@
 use crate::config::app_state::AppState;
 use crate::errors::app_error::AppError;
-use crate::extensions::request_user_ext::RequestUserExt;
+use crate::middleware::permission_middleware::RequirePermission;
 use crate::models::category::{
     Category, CategoryQuery, CreateCategoryRequest, UpdateCategoryRequest,
 };
+use crate::models::role::permissions;
 use crate::services::category_service;
-use actix_web::{HttpRequest, HttpResponse, delete, get, post, put, web};
+use actix_web::{HttpResponse, delete, get, post, put, web};
 use uuid::Uuid;
 
-#[post("/categories/")]
+#[post(
+    "/categories/",
+    wrap = "RequirePermission::new(permissions::CATEGORIES_WRITE)"
+)]
 pub async fn create_category(
-    req: HttpRequest,
     payload: web::Json<CreateCategoryRequest>,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, AppError> {
-    let access_level = req.access_level()?;
-    if access_level != "admin" {
-        return Ok(HttpResponse::Forbidden().body("Permissão negada"));
-    }
-
     let category: Category =
         category_service::create_category_service(payload.into_inner(), &state).await?;
 
@@ -38,18 +36,15 @@ pub async fn list_categories(
     Ok(HttpResponse::Ok().json(categories))
 }
 
-#[put("/categories/{id}/")]
+#[put(
+    "/categories/{id}/",
+    wrap = "RequirePermission::new(permissions::CATEGORIES_WRITE)"
+)]
 pub async fn update_category(
-    req: HttpRequest,
     id: web::Path<Uuid>,
     payload: web::Json<UpdateCategoryRequest>,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, AppError> {
-    let access_level = req.access_level()?;
-    if access_level != "admin" {
-        return Ok(HttpResponse::Forbidden().body("Permissão negada"));
-    }
-
     let category =
         category_service::update_category_service(id.into_inner(), payload.into_inner(), &state)
             .await?;
@@ -57,17 +52,14 @@ pub async fn update_category(
     Ok(HttpResponse::Ok().json(category))
 }
 
-#[delete("/categories/{id}/")]
+#[delete(
+    "/categories/{id}/",
+    wrap = "RequirePermission::new(permissions::CATEGORIES_DELETE)"
+)]
 pub async fn delete_category(
-    req: HttpRequest,
     id: web::Path<Uuid>,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, AppError> {
-    let access_level = req.access_level()?;
-    if access_level != "admin" {
-        return Ok(HttpResponse::Forbidden().body("Permissão negada"));
-    }
-
     category_service::soft_delete_category_service(id.into_inner(), &state).await?;
     Ok(HttpResponse::NoContent().finish())
 }
@@ -1,9 +1,11 @@
 use crate::logs::routes::get_logs;
 use crate::middleware::auth_middleware::AuthMiddleware;
+use crate::middleware::rate_limit_middleware::{RateLimitGroup, RateLimiter};
 
 use crate::routes::{
-    category_routes, course_routes, notification_routes, profile_routes, user_private_routes,
-    user_public_routes,
+    admin_user_routes, api_key_routes, auth_routes, category_routes, course_routes,
+    email_outbox_routes, media_routes, mfa_routes, notification_routes, oidc_routes,
+    profile_routes, role_routes, user_private_routes, user_public_routes,
 };
 use crate::websocket::routes::websocket_entry;
 use actix_web::{Scope, web};
@@ -13,18 +15,51 @@ pub fn api_v1_scope() -> Scope {
         .service(user_public_routes::login)
         .service(user_public_routes::create_user)
         .service(user_public_routes::confirm_email)
+        .service(user_public_routes::confirm_email_change)
+        .service(user_public_routes::resend_confirmation)
         .service(user_public_routes::forgot_password)
         .service(user_public_routes::change_password)
+        .service(user_public_routes::unsubscribe)
+        .service(user_public_routes::restore_account)
+        .service(user_public_routes::get_public_profile)
+        .service(auth_routes::refresh_token)
+        .service(mfa_routes::login_mfa)
+        .service(oidc_routes::oidc_start)
+        .service(oidc_routes::oidc_callback)
+        .service(media_routes::get_media)
         .service(websocket_entry)
         .service(
             web::scope("") // escopo vazio herda o "/api/v1"
+                // o rate limit roda depois da autenticação para usar o `Claims.sub` como chave
+                .wrap(RateLimiter::new(RateLimitGroup::User))
                 .wrap(AuthMiddleware)
                 .service(get_logs)
+                .service(auth_routes::logout)
+                .service(auth_routes::logout_all)
+                .service(auth_routes::list_sessions)
+                .service(auth_routes::delete_session)
+                .service(mfa_routes::enroll_totp)
+                .service(mfa_routes::verify_totp)
+                .service(mfa_routes::disable_totp)
+                .service(api_key_routes::create_api_key)
+                .service(api_key_routes::list_api_keys)
+                .service(api_key_routes::update_api_key_expiry)
+                .service(api_key_routes::revoke_api_key)
                 .service(user_private_routes::get_me)
                 .service(user_private_routes::list_users)
                 .service(user_private_routes::update_user)
+                .service(user_private_routes::request_email_change)
+                .service(user_private_routes::change_username)
+                .service(user_private_routes::update_password)
+                .service(user_private_routes::export_personal_data)
                 .service(user_private_routes::delete_user)
                 .service(profile_routes::update_profile)
+                .service(profile_routes::upload_avatar)
+                .service(profile_routes::remove_avatar)
+                .service(profile_routes::send_phone_verification)
+                .service(profile_routes::confirm_phone_verification)
+                .service(profile_routes::get_email_preferences)
+                .service(profile_routes::update_email_preferences)
                 .service(course_routes::create_course)
                 .service(course_routes::list_courses)
                 .service(course_routes::update_course)
@@ -34,6 +69,22 @@ pub fn api_v1_scope() -> Scope {
                 .service(category_routes::list_categories)
                 .service(category_routes::update_category)
                 .service(category_routes::delete_category)
-                .service(course_routes::delete_course),
+                .service(course_routes::delete_course)
+                .service(role_routes::list_roles)
+                .service(role_routes::assign_role)
+                .service(role_routes::remove_role)
+                .service(admin_user_routes::provision_user)
+                .service(admin_user_routes::list_users)
+                .service(admin_user_routes::get_user)
+                .service(admin_user_routes::update_user)
+                .service(admin_user_routes::suspend_user)
+                .service(admin_user_routes::unsuspend_user)
+                .service(admin_user_routes::unlock_user)
+                .service(api_key_routes::admin_create_api_key)
+                .service(api_key_routes::admin_list_api_keys)
+                .service(api_key_routes::admin_revoke_api_key)
+                .service(email_outbox_routes::list_emails)
+                .service(email_outbox_routes::get_email)
+                .service(email_outbox_routes::retry_email),
         )
 }
@@ -1,24 +1,31 @@
 use crate::config::app_state::AppState;
 use crate::errors::app_error::AppError;
 use crate::extensions::request_user_ext::RequestUserExt;
+use crate::middleware::permission_middleware::RequirePermission;
+use crate::models::auth::protected_actions;
 use crate::models::course::{CourseQuery, CreateCourseRequest, UpdateCourseRequest};
-use crate::services::course_service;
+use crate::models::role::permissions;
+use crate::services::{account_policy_service, course_service};
 use actix_web::{HttpRequest, HttpResponse, Responder, delete, get, post, put, web};
 use serde_json::json;
 use uuid::Uuid;
 
-#[post("/courses/")]
+#[post(
+    "/courses/",
+    wrap = "RequirePermission::new(permissions::COURSES_WRITE)"
+)]
 pub async fn create_course(
     req: HttpRequest,
     payload: web::Json<CreateCourseRequest>,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let access_level = req.access_level()?;
-    if access_level != "admin" {
-        return Ok(HttpResponse::Forbidden().body("Permissão negada"));
-    }
-
     let user_id = req.user_id()?;
+    account_policy_service::ensure_email_confirmed(
+        user_id,
+        protected_actions::CREATE_COURSE,
+        &state.db,
+    )
+    .await?;
 
     let course = course_service::create_course_service(payload.into_inner(), user_id, &state)
         .await
@@ -29,18 +36,16 @@ pub async fn create_course(
     Ok(HttpResponse::Created().json(course))
 }
 
-#[put("/courses/{id}/")]
+#[put(
+    "/courses/{id}/",
+    wrap = "RequirePermission::new(permissions::COURSES_WRITE)"
+)]
 pub async fn update_course(
     req: HttpRequest,
     path: web::Path<Uuid>,
     payload: web::Json<UpdateCourseRequest>,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, AppError> {
-    let access_level = req.access_level()?;
-    if access_level != "admin" {
-        return Ok(HttpResponse::Forbidden().body("Permissão negada"));
-    }
-
     let user_id = req.user_id()?;
     let id = path.into_inner();
     let course =
@@ -60,7 +65,10 @@ pub async fn list_courses(
     Ok(HttpResponse::Ok().json(result))
 }
 
-#[delete("/courses/{id}/")]
+#[delete(
+    "/courses/{id}/",
+    wrap = "RequirePermission::new(permissions::COURSES_DELETE)"
+)]
 pub async fn delete_course(
     path: web::Path<Uuid>,
     state: web::Data<AppState>,
@@ -72,16 +80,13 @@ pub async fn delete_course(
     Ok(HttpResponse::NoContent().finish())
 }
 
-#[post("/courses/sync/")]
+#[post(
+    "/courses/sync/",
+    wrap = "RequirePermission::new(permissions::COURSES_SYNC)"
+)]
 async fn sync_courses_to_elasticsearch(
-    req: HttpRequest,
     state: web::Data<AppState>,
 ) -> Result<impl Responder, AppError> {
-    let access_level = req.access_level()?;
-    if access_level != "admin" {
-        return Ok(HttpResponse::Forbidden().body("Permissão negada"));
-    }
-
     let indexed = course_service::reindex_courses(&state)
         .await
         .map_err(|e| AppError::InternalError(Some(format!("Erro ao reindexar cursos: {}", e))))?;
@@ -0,0 +1,54 @@
+use crate::config::app_state::AppState;
+use crate::errors::app_error::AppError;
+use crate::extensions::request_user_ext::RequestUserExt;
+use crate::middleware::permission_middleware::RequirePermission;
+use crate::models::email_outbox::OutboxEmailFilter;
+use crate::models::role::permissions;
+use crate::services::email_outbox_service;
+use crate::utils::pagination::PaginationParams;
+use actix_web::{HttpRequest, HttpResponse, get, post, web};
+use uuid::Uuid;
+
+#[get(
+    "/admin/emails/",
+    wrap = "RequirePermission::new(permissions::EMAILS_MANAGE)"
+)]
+pub async fn list_emails(
+    filter: web::Query<OutboxEmailFilter>,
+    query: web::Query<PaginationParams>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let PaginationParams { limit, offset } = query.into_inner();
+
+    let paginated =
+        email_outbox_service::list_emails(filter.into_inner(), limit, offset, &state.db).await?;
+    Ok(HttpResponse::Ok().json(paginated))
+}
+
+#[get(
+    "/admin/emails/{id}/",
+    wrap = "RequirePermission::new(permissions::EMAILS_MANAGE)"
+)]
+pub async fn get_email(
+    path: web::Path<Uuid>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let email = email_outbox_service::get_email(path.into_inner(), &state.db).await?;
+    Ok(HttpResponse::Ok().json(email))
+}
+
+/// Devolve à fila um e-mail que esgotou as tentativas
+#[post(
+    "/admin/emails/{id}/retry/",
+    wrap = "RequirePermission::new(permissions::EMAILS_MANAGE)"
+)]
+pub async fn retry_email(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let admin_id = req.user_id()?;
+
+    let email = email_outbox_service::retry_email(path.into_inner(), admin_id, &state.db).await?;
+    Ok(HttpResponse::Ok().json(email))
+}
@@ -0,0 +1,36 @@
+use crate::config::app_state::AppState;
+use crate::errors::app_error::AppError;
+use crate::storage::is_valid_key;
+use actix_web::http::header;
+use actix_web::{HttpResponse, get, web};
+use tracing::error;
+
+/// Serve os objetos do armazenamento (avatares). As chaves levam um UUID por envio, então
+/// o conteúdo de uma URL nunca muda e pode ficar em cache indefinidamente.
+#[get("/media/{key:.*}")]
+pub async fn get_media(
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let key = path.into_inner();
+    let not_found = || AppError::NotFound(Some("Arquivo não encontrado".into()));
+    if !is_valid_key(&key) {
+        return Err(not_found());
+    }
+
+    let blob = state
+        .blobs
+        .get(&key)
+        .await
+        .map_err(|e| {
+            error!(key = %key, error = %e, "Erro ao ler arquivo do armazenamento");
+            AppError::InternalError(Some("Erro ao ler arquivo".into()))
+        })?
+        .ok_or_else(not_found)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(blob.content_type)
+        .insert_header((header::CACHE_CONTROL, "public, max-age=31536000, immutable"))
+        .insert_header((header::X_CONTENT_TYPE_OPTIONS, "nosniff"))
+        .body(blob.data))
+}
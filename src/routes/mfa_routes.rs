@@ -0,0 +1,59 @@
+use crate::config::app_state::AppState;
+use crate::errors::app_error::AppError;
+use crate::extensions::request_user_ext::RequestUserExt;
+use crate::middleware::permission_middleware::RejectApiKey;
+use crate::middleware::rate_limit_middleware::{RateLimitGroup, RateLimiter};
+use crate::models::mfa::{MfaLoginRequest, TotpCodeRequest};
+use crate::models::session::SessionInfo;
+use crate::services::mfa_service;
+use actix_web::{HttpRequest, HttpResponse, post, web};
+
+#[post("/login/mfa/", wrap = "RateLimiter::new(RateLimitGroup::Auth)")]
+pub async fn login_mfa(
+    req: HttpRequest,
+    payload: web::Json<MfaLoginRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let response = mfa_service::complete_mfa_login(
+        payload.into_inner(),
+        SessionInfo::from_request(&req),
+        &state,
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[post("/me/mfa/totp/", wrap = "RejectApiKey")]
+pub async fn enroll_totp(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+
+    let enrollment = mfa_service::enroll_totp(user_id, &state).await?;
+    Ok(HttpResponse::Ok().json(enrollment))
+}
+
+#[post("/me/mfa/totp/verify/", wrap = "RejectApiKey")]
+pub async fn verify_totp(
+    req: HttpRequest,
+    payload: web::Json<TotpCodeRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+
+    let codes = mfa_service::confirm_totp_enrollment(user_id, payload.into_inner(), &state).await?;
+    Ok(HttpResponse::Ok().json(codes))
+}
+
+#[post("/me/mfa/totp/disable/", wrap = "RejectApiKey")]
+pub async fn disable_totp(
+    req: HttpRequest,
+    payload: web::Json<TotpCodeRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+
+    mfa_service::disable_totp(user_id, payload.into_inner(), &state).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -1,7 +1,16 @@
+pub mod admin_user_routes;
+pub mod api_key_routes;
+pub mod auth_routes;
 pub mod category_routes;
 pub mod configure;
 pub mod course_routes;
+pub mod email_outbox_routes;
+pub mod media_routes;
+pub mod mfa_routes;
 pub mod notification_routes;
+pub mod oidc_routes;
 pub mod profile_routes;
+pub mod role_routes;
 pub mod user_private_routes;
 pub mod user_public_routes;
+pub mod well_known_routes;
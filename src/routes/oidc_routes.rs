@@ -0,0 +1,54 @@
+use crate::config::app_state::AppState;
+use crate::config::{OidcProviderSettings, get_settings};
+use crate::errors::app_error::AppError;
+use crate::middleware::rate_limit_middleware::{RateLimitGroup, RateLimiter};
+use crate::models::identity::OidcCallbackQuery;
+use crate::models::session::SessionInfo;
+use crate::services::oidc_service;
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse, get, web};
+
+fn find_provider(name: &str) -> Result<&'static OidcProviderSettings, AppError> {
+    get_settings()
+        .oidc
+        .provider(name)
+        .ok_or_else(|| AppError::NotFound(Some("Provedor de login não configurado".into())))
+}
+
+#[get(
+    "/auth/{provider}/start/",
+    wrap = "RateLimiter::new(RateLimitGroup::Auth)"
+)]
+pub async fn oidc_start(
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let provider = find_provider(&path.into_inner())?;
+
+    let authorization_url = oidc_service::start_authorization(provider, &state.db).await?;
+    Ok(HttpResponse::Found()
+        .insert_header((header::LOCATION, authorization_url))
+        .finish())
+}
+
+#[get(
+    "/auth/{provider}/callback/",
+    wrap = "RateLimiter::new(RateLimitGroup::Auth)"
+)]
+pub async fn oidc_callback(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<OidcCallbackQuery>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let provider = find_provider(&path.into_inner())?;
+
+    let response = oidc_service::complete_authorization(
+        provider,
+        query.into_inner(),
+        SessionInfo::from_request(&req),
+        &state,
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(response))
+}
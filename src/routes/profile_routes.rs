@@ -1,12 +1,21 @@
 use crate::config::app_state::AppState;
+use crate::config::get_settings;
 use crate::errors::app_error::AppError;
 use crate::extensions::has_any_field::HasAnyField;
 use crate::extensions::request_user_ext::RequestUserExt;
+use crate::middleware::permission_middleware::RejectApiKey;
+use crate::models::email_preferences::UpdateEmailPreferencesRequest;
+use crate::models::phone_verification::ConfirmPhoneRequest;
 use crate::models::profile::UpdateProfileRequest;
-use crate::services::profile_service;
-use actix_web::{HttpRequest, HttpResponse, Responder, post, web};
+use crate::models::role::permissions;
+use crate::services::{
+    avatar_service, email_preferences_service, phone_verification_service, profile_service,
+};
+use actix_multipart::Multipart;
+use actix_web::{HttpRequest, HttpResponse, Responder, delete, get, patch, post, web};
+use futures_util::TryStreamExt;
 
-#[post("/profiles/")]
+#[post("/profiles/", wrap = "RejectApiKey")]
 pub async fn update_profile(
     req: HttpRequest,
     payload: web::Json<UpdateProfileRequest>,
@@ -18,7 +27,114 @@ pub async fn update_profile(
         )));
     }
     let user_id = req.user_id()?; // 🔐 Garantido pelo middleware
-    let profile =
+    let mut profile =
         profile_service::update_profile_by_user_id(user_id, payload.into_inner(), &state).await?;
+    profile
+        .user
+        .profile
+        .apply_viewer_visibility(Some(user_id), req.has_permission(permissions::USERS_MANAGE));
     Ok(HttpResponse::Ok().json(profile))
 }
+
+#[get("/profiles/email-preferences/")]
+pub async fn get_email_preferences(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+    let preferences = email_preferences_service::get_email_preferences(user_id, &state.db).await?;
+    Ok(HttpResponse::Ok().json(preferences))
+}
+
+#[patch("/profiles/email-preferences/", wrap = "RejectApiKey")]
+pub async fn update_email_preferences(
+    req: HttpRequest,
+    payload: web::Json<UpdateEmailPreferencesRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+    let preferences = email_preferences_service::update_email_preferences(
+        user_id,
+        payload.into_inner(),
+        &state.db,
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(preferences))
+}
+
+/// Lê o campo `avatar` do formulário, interrompendo a leitura assim que o limite é passado
+async fn read_avatar_field(mut payload: Multipart) -> Result<(String, Vec<u8>), AppError> {
+    let max_bytes = get_settings().avatar.max_bytes;
+    let invalid_form = |_| AppError::BadRequest(Some("Formulário multipart inválido".into()));
+
+    while let Some(mut field) = payload.try_next().await.map_err(invalid_form)? {
+        if field.name() != Some("avatar") {
+            continue;
+        }
+        let content_type = field
+            .content_type()
+            .map(|mime| mime.essence_str().to_string())
+            .unwrap_or_default();
+
+        let mut data = Vec::new();
+        while let Some(chunk) = field.try_next().await.map_err(invalid_form)? {
+            if data.len() + chunk.len() > max_bytes {
+                return Err(AppError::PayloadTooLarge(Some(format!(
+                    "A imagem deve ter no máximo {} bytes",
+                    max_bytes
+                ))));
+            }
+            data.extend_from_slice(&chunk);
+        }
+        return Ok((content_type, data));
+    }
+
+    Err(AppError::BadRequest(Some(
+        "Campo 'avatar' não enviado".into(),
+    )))
+}
+
+/// Envia um novo avatar (`multipart/form-data`, campo `avatar`) em JPEG, PNG ou WebP
+#[post("/me/avatar/", wrap = "RejectApiKey")]
+pub async fn upload_avatar(
+    req: HttpRequest,
+    payload: Multipart,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+    let (content_type, data) = read_avatar_field(payload).await?;
+    let avatar = avatar_service::upload_avatar(user_id, &content_type, data, &state).await?;
+    Ok(HttpResponse::Ok().json(avatar))
+}
+
+#[delete("/me/avatar/", wrap = "RejectApiKey")]
+pub async fn remove_avatar(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+    avatar_service::remove_avatar(user_id, &state).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Envia por SMS um código para verificar o telefone do perfil
+#[post("/me/phone/verification/", wrap = "RejectApiKey")]
+pub async fn send_phone_verification(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+    let sent = phone_verification_service::send_verification_code(user_id, &state).await?;
+    Ok(HttpResponse::Ok().json(sent))
+}
+
+#[post("/me/phone/verification/confirm/", wrap = "RejectApiKey")]
+pub async fn confirm_phone_verification(
+    req: HttpRequest,
+    payload: web::Json<ConfirmPhoneRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+    phone_verification_service::confirm_phone(user_id, payload.into_inner(), &state).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
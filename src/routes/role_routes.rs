@@ -0,0 +1,43 @@
+use crate::config::app_state::AppState;
+use crate::errors::app_error::AppError;
+use crate::middleware::permission_middleware::RequirePermission;
+use crate::models::role::{AssignRoleRequest, permissions};
+use crate::services::role_service;
+use actix_web::{HttpResponse, delete, get, post, web};
+use uuid::Uuid;
+
+#[get(
+    "/admin/roles/",
+    wrap = "RequirePermission::new(permissions::ROLES_MANAGE)"
+)]
+pub async fn list_roles(state: web::Data<AppState>) -> Result<HttpResponse, AppError> {
+    let roles = role_service::list_roles_service(&state).await?;
+    Ok(HttpResponse::Ok().json(roles))
+}
+
+#[post(
+    "/admin/users/{id}/roles/",
+    wrap = "RequirePermission::new(permissions::ROLES_MANAGE)"
+)]
+pub async fn assign_role(
+    id: web::Path<Uuid>,
+    payload: web::Json<AssignRoleRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let response =
+        role_service::assign_role_service(id.into_inner(), &payload.role, &state).await?;
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[delete(
+    "/admin/users/{id}/roles/{role}/",
+    wrap = "RequirePermission::new(permissions::ROLES_MANAGE)"
+)]
+pub async fn remove_role(
+    path: web::Path<(Uuid, String)>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let (user_id, role) = path.into_inner();
+    let response = role_service::remove_role_service(user_id, &role, &state).await?;
+    Ok(HttpResponse::Ok().json(response))
+}
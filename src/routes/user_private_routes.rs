@@ -1,10 +1,14 @@
 use crate::config::app_state::AppState;
 use crate::errors::app_error::AppError;
 use crate::extensions::request_user_ext::RequestUserExt;
-use crate::models::user::UpdateUserRequest;
-use crate::services::user_private_service;
+use crate::middleware::permission_middleware::{RejectApiKey, RequirePermission};
+use crate::models::role::permissions;
+use crate::models::user::{
+    ChangeEmailRequest, ChangeUsernameRequest, UpdatePasswordRequest, UpdateUserRequest,
+};
+use crate::services::{personal_data_service, user_private_service, username_service};
 use crate::utils::pagination::PaginationParams;
-use actix_web::{HttpRequest, HttpResponse, delete, get, patch, web};
+use actix_web::{HttpRequest, HttpResponse, delete, get, patch, post, web};
 
 #[get("/me/")]
 pub async fn get_me(
@@ -20,11 +24,16 @@ pub async fn get_me(
         .ok_or(AppError::Unauthorized(None))?;
     let user_id = req.user_id()?;
 
-    let response = user_private_service::get_me_by_user_id(user_id, token, &state).await?;
+    let mut response = user_private_service::get_me_by_user_id(user_id, token, &state).await?;
+    response
+        .user
+        .profile
+        .apply_viewer_visibility(Some(user_id), req.has_permission(permissions::USERS_MANAGE));
     Ok(HttpResponse::Ok().json(response))
 }
 
-#[get("/users/")]
+/// Lista completa, com e-mails; restrita a quem gerencia usuários
+#[get("/users/", wrap = "RequirePermission::new(permissions::USERS_MANAGE)")]
 pub async fn list_users(
     req: HttpRequest,
     query: web::Query<PaginationParams>,
@@ -42,7 +51,7 @@ pub async fn list_users(
     Ok(HttpResponse::Ok().json(paginated))
 }
 
-#[patch("/users/")]
+#[patch("/users/", wrap = "RejectApiKey")]
 pub async fn update_user(
     req: HttpRequest,
     payload: web::Json<UpdateUserRequest>,
@@ -56,13 +65,19 @@ pub async fn update_user(
         .map(|s| s.to_string())
         .ok_or(AppError::Unauthorized(None))?;
     let user_id = req.user_id()?;
-    let response =
+    let mut response =
         user_private_service::update_logged_user(user_id, payload.into_inner(), token, &state)
             .await?;
+    response
+        .user
+        .profile
+        .apply_viewer_visibility(Some(user_id), req.has_permission(permissions::USERS_MANAGE));
     Ok(HttpResponse::Ok().json(response))
 }
 
-#[delete("/users/")]
+/// Exclui a conta; ela pode ser restaurada em `POST /restore-account/` até o fim do prazo de
+/// carência, quando os dados pessoais são anonimizados
+#[delete("/users/", wrap = "RejectApiKey")]
 pub async fn delete_user(
     req: HttpRequest,
     state: web::Data<AppState>,
@@ -71,3 +86,52 @@ pub async fn delete_user(
     user_private_service::delete_logged_user(user_id, &state).await?;
     Ok(HttpResponse::NoContent().finish())
 }
+
+/// Pede a troca do e-mail; o novo endereço recebe o link de confirmação
+#[post("/me/email/", wrap = "RejectApiKey")]
+pub async fn request_email_change(
+    req: HttpRequest,
+    payload: web::Json<ChangeEmailRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+    user_private_service::request_email_change(user_id, payload.into_inner(), &state).await?;
+    Ok(HttpResponse::Accepted().finish())
+}
+
+/// Troca o username; o antigo fica livre para outras contas
+#[patch("/me/username/", wrap = "RejectApiKey")]
+pub async fn change_username(
+    req: HttpRequest,
+    payload: web::Json<ChangeUsernameRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+    let response = username_service::change_username(user_id, payload.into_inner(), &state).await?;
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Troca a senha informando a atual; as outras sessões são encerradas
+#[post("/me/password/", wrap = "RejectApiKey")]
+pub async fn update_password(
+    req: HttpRequest,
+    payload: web::Json<UpdatePasswordRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+    let session_id = req.session_id()?;
+    user_private_service::update_password(user_id, session_id, payload.into_inner(), &state)
+        .await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Exporta os dados pessoais do usuário logado em JSON
+#[get("/me/export/", wrap = "RejectApiKey")]
+pub async fn export_personal_data(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = req.user_id()?;
+    let export = personal_data_service::export_personal_data(user_id, &state).await?;
+    Ok(HttpResponse::Ok().json(export))
+}
@@ -1,29 +1,53 @@
 use crate::config::app_state::AppState;
 use crate::errors::app_error::AppError;
-use crate::models::auth::{ChangePasswordRequest, ForgotPasswordRequest, LoginRequest};
+use crate::middleware::rate_limit_middleware::{RateLimitGroup, RateLimiter};
+use crate::models::auth::{
+    ChangePasswordRequest, ForgotPasswordRequest, LoginRequest, ResendConfirmationRequest,
+    RestoreAccountRequest,
+};
+use crate::models::email_preferences::UnsubscribeQuery;
+use crate::models::session::SessionInfo;
 use crate::models::user::UserRequest;
-use crate::services::user_public_service;
-use actix_web::{HttpResponse, Responder, get, post, web};
+use crate::services::{
+    account_deletion_service, email_preferences_service, user_public_service, username_service,
+};
+use actix_web::{HttpRequest, HttpResponse, Responder, get, post, web};
+use validator::Validate;
 
-#[post("/users/")]
+#[post("/users/", wrap = "RateLimiter::new(RateLimitGroup::Auth)")]
 pub async fn create_user(
+    req: HttpRequest,
     user_data: web::Json<UserRequest>,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, AppError> {
-    let res = user_public_service::create_user_with_request(user_data.into_inner(), &state).await?;
+    let res = user_public_service::create_user_with_request(
+        user_data.into_inner(),
+        SessionInfo::from_request(&req),
+        &state,
+    )
+    .await?;
     Ok(HttpResponse::Ok().json(res))
 }
 
-#[post("/login/")]
+#[post("/login/", wrap = "RateLimiter::new(RateLimitGroup::Auth)")]
 pub async fn login(
+    req: HttpRequest,
     payload: web::Json<LoginRequest>,
     state: web::Data<AppState>,
 ) -> Result<impl Responder, AppError> {
-    let response = user_public_service::login_user(payload.into_inner(), &state).await?;
+    let response = user_public_service::login_user(
+        payload.into_inner(),
+        SessionInfo::from_request(&req),
+        &state,
+    )
+    .await?;
     Ok(web::Json(response))
 }
 
-#[get("/confirm-email/{code}/")]
+#[get(
+    "/confirm-email/{code}/",
+    wrap = "RateLimiter::new(RateLimitGroup::Public)"
+)]
 pub async fn confirm_email(
     code: web::Path<String>,
     state: web::Data<AppState>,
@@ -32,7 +56,36 @@ pub async fn confirm_email(
     Ok(HttpResponse::Ok().finish())
 }
 
-#[post("/forgot-password/")]
+#[post(
+    "/resend-confirmation/",
+    wrap = "RateLimiter::new(RateLimitGroup::Auth)"
+)]
+pub async fn resend_confirmation(
+    payload: web::Json<ResendConfirmationRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let payload = payload.into_inner();
+    payload
+        .validate()
+        .map_err(|e| AppError::BadRequest(Some(format!("Dados inválidos: {}", e))))?;
+
+    user_public_service::resend_confirmation(&payload.email, &state).await?;
+    Ok(HttpResponse::Accepted().finish())
+}
+
+#[get(
+    "/confirm-email-change/{code}/",
+    wrap = "RateLimiter::new(RateLimitGroup::Public)"
+)]
+pub async fn confirm_email_change(
+    code: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    user_public_service::confirm_email_change(&code, &state).await?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[post("/forgot-password/", wrap = "RateLimiter::new(RateLimitGroup::Auth)")]
 pub async fn forgot_password(
     payload: web::Json<ForgotPasswordRequest>,
     state: web::Data<AppState>,
@@ -41,7 +94,7 @@ pub async fn forgot_password(
     Ok(HttpResponse::Ok().finish())
 }
 
-#[post("/change-password/")]
+#[post("/change-password/", wrap = "RateLimiter::new(RateLimitGroup::Auth)")]
 pub async fn change_password(
     payload: web::Json<ChangePasswordRequest>,
     state: web::Data<AppState>,
@@ -51,3 +104,41 @@ pub async fn change_password(
     user_public_service::change_password(&code, &password, &state).await?;
     Ok(HttpResponse::Ok().finish())
 }
+
+/// Descadastro em um clique (RFC 8058): o token vai na URL do `List-Unsubscribe`
+#[post("/unsubscribe/", wrap = "RateLimiter::new(RateLimitGroup::Public)")]
+pub async fn unsubscribe(
+    query: web::Query<UnsubscribeQuery>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let response = email_preferences_service::unsubscribe(&query.token, &state.db).await?;
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Restaura uma conta excluída dentro do prazo de carência (`ACCOUNT_DELETION_GRACE_DAYS`)
+#[post("/restore-account/", wrap = "RateLimiter::new(RateLimitGroup::Auth)")]
+pub async fn restore_account(
+    payload: web::Json<RestoreAccountRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let payload = payload.into_inner();
+    payload
+        .validate()
+        .map_err(|e| AppError::BadRequest(Some(format!("Dados inválidos: {}", e))))?;
+
+    account_deletion_service::restore_account(payload, &state).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Perfil público: bio, profissão, avatar e cursos ativos do autor, sem dados de contato
+#[get(
+    "/users/{username}/",
+    wrap = "RateLimiter::new(RateLimitGroup::Public)"
+)]
+pub async fn get_public_profile(
+    username: web::Path<String>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let profile = username_service::get_public_profile(&username, &state).await?;
+    Ok(HttpResponse::Ok().json(profile))
+}
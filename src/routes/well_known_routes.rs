@@ -0,0 +1,10 @@
+use crate::utils::jwt::jwt_keys;
+use actix_web::{HttpResponse, Responder, get};
+
+/// Chaves públicas para que outros serviços validem os tokens emitidos pela API
+#[get("/.well-known/jwks.json")]
+pub async fn jwks() -> impl Responder {
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "public, max-age=300"))
+        .json(jwt_keys().jwks())
+}
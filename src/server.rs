@@ -1,12 +1,25 @@
 use actix::Actor;
 use actix_web::{App, HttpServer, web};
 use tokio::task::LocalSet;
-use tracing::info;
+use tracing::{info, warn};
 use tracing_actix_web::TracingLogger;
 
 use crate::config::{app_state::AppState, get_settings};
-use crate::db::{elasticsearch::get_elastic_client, mongo::init_mongodb, postgres::get_db_pool};
+use crate::db::{
+    elasticsearch::get_elastic_client, mongo::init_mongodb, postgres::get_db_pool,
+    redis::init_redis,
+};
+use crate::mailer::build_email_sender;
 use crate::routes::configure::api_v1_scope;
+use crate::routes::well_known_routes;
+use crate::services::{
+    account_deletion_service, auth_service, email_outbox_service, email_service, token_service,
+};
+use crate::sms::build_sms_sender;
+use crate::storage::build_blob_store;
+use crate::stores::login_attempt_store::build_login_attempt_store;
+use crate::stores::rate_limit_store::build_rate_limit_store;
+use crate::utils::jwt::init_jwt_keys;
 use crate::utils::setup_development_logging;
 use crate::websocket::server::WsServer;
 
@@ -16,10 +29,50 @@ pub async fn start_server() -> Result<(), Box<dyn std::error::Error + Send + Syn
     setup_development_logging().expect("Falha ao configurar logs");
 
     let settings = get_settings();
+    init_jwt_keys()?;
     let pool = get_db_pool().await;
     let mongo_db = init_mongodb().await.unwrap();
     let elastic_client = get_elastic_client()?;
 
+    let redis = match &settings.redis.url {
+        Some(url) => Some(init_redis(url).await?),
+        None => {
+            if settings.is_production() {
+                warn!(
+                    "REDIS_URL não definida: contadores de login e rate limit ficarão em memória"
+                );
+            }
+            None
+        }
+    };
+    let login_attempts = build_login_attempt_store(redis.clone());
+    let rate_limits = build_rate_limit_store(redis);
+    email_service::init_templates()?;
+    let mailer = build_email_sender(&settings.email)?;
+    let blobs = build_blob_store(&settings.storage, &settings.email.app_url)?;
+    let sms = build_sms_sender(&settings.sms)?;
+    if settings.email.outbox.worker_enabled {
+        email_outbox_service::spawn_worker(
+            pool.clone(),
+            mailer.clone(),
+            settings.email.outbox.clone(),
+        );
+    }
+    if settings.jwt.purge_enabled {
+        auth_service::spawn_purge_job(pool.clone(), settings.jwt.clone());
+    }
+    if settings.user_tokens.purge_enabled {
+        token_service::spawn_purge_job(pool.clone(), settings.user_tokens.clone());
+    }
+    if settings.account.deletion_purge_enabled {
+        account_deletion_service::spawn_purge_job(
+            pool.clone(),
+            mongo_db.clone(),
+            blobs.clone(),
+            settings.account.clone(),
+        );
+    }
+
     let local = LocalSet::new();
 
     info!(
@@ -38,15 +91,21 @@ pub async fn start_server() -> Result<(), Box<dyn std::error::Error + Send + Syn
                 mongo: mongo_db,
                 es: elastic_client,
                 ws_server,
+                login_attempts,
+                rate_limits,
+                mailer,
+                blobs,
+                sms,
             });
 
             HttpServer::new(move || {
                 App::new()
                     .wrap(TracingLogger::default())
                     .app_data(app_state.clone())
+                    .service(well_known_routes::jwks)
                     .service(api_v1_scope())
             })
-            .bind((settings.server.host.clone(), settings.server.port))?
+            .bind((settings.server.host, settings.server.port))?
             .run()
             .await
         })
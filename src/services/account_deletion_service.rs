@@ -0,0 +1,178 @@
+use crate::config::app_state::AppState;
+use crate::config::{AccountSettings, get_settings};
+use crate::errors::app_error::AppError;
+use crate::logs::repository as log_repository;
+use crate::models::auth::RestoreAccountRequest;
+use crate::repositories::{account_data_repository, profile_repository, user_repository};
+use crate::services::avatar_service;
+use crate::storage::BlobStore;
+use actix_web::web;
+use chrono::{Duration, NaiveDateTime, Utc};
+use mongodb::Database;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Até quando uma conta excluída em `deleted_at` pode ser restaurada
+pub fn restore_deadline(deleted_at: NaiveDateTime, grace_days: i64) -> NaiveDateTime {
+    deleted_at + Duration::days(grace_days)
+}
+
+/// Desfaz a exclusão de uma conta ainda dentro do prazo de carência. As sessões encerradas na
+/// exclusão não voltam: o titular precisa fazer login de novo.
+pub async fn restore_account(
+    payload: RestoreAccountRequest,
+    state: &web::Data<AppState>,
+) -> Result<(), AppError> {
+    let db = &state.db;
+    let invalid_credentials = || AppError::Unauthorized(Some("Credenciais inválidas".into()));
+
+    let user = user_repository::find_deleted_user_by_email(&payload.email, db)
+        .await?
+        .ok_or_else(invalid_credentials)?;
+    if !user.verify_password(&payload.password).await.is_valid() {
+        return Err(invalid_credentials());
+    }
+
+    let deleted_at = user.dt_deleted.ok_or_else(invalid_credentials)?;
+    let grace_days = get_settings().account.deletion_grace_days;
+    if restore_deadline(deleted_at, grace_days) <= Utc::now().naive_utc() {
+        return Err(AppError::Forbidden(Some(
+            "O prazo para restaurar a conta terminou".into(),
+        )));
+    }
+
+    // o e-mail pode ter sido usado em um novo cadastro durante o prazo
+    if user_repository::find_user_by_email(&user.email, db)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::Conflict(Some("E-mail já está em uso".into())));
+    }
+
+    let restored = user_repository::restore_user(db, user.id)
+        .await
+        .map_err(|err| match err {
+            // cadastro concorrente com o mesmo e-mail entre a checagem acima e a restauração
+            sqlx::Error::Database(db_err)
+                if db_err.constraint() == Some("users_email_active_key") =>
+            {
+                AppError::Conflict(Some("E-mail já está em uso".into()))
+            }
+            err => AppError::DatabaseError(Some(err.to_string())),
+        })?;
+    if restored == 0 {
+        return Err(invalid_credentials());
+    }
+
+    info!(user_id = %user.id, "Conta restaurada");
+    Ok(())
+}
+
+/// Anonimiza a conta: dados pessoais do usuário e do perfil são apagados ou substituídos e os
+/// registros auxiliares removidos. A linha em `users` continua existindo para os cursos do autor.
+pub async fn anonymize_user(
+    user_id: Uuid,
+    db: &PgPool,
+    mongo: &Database,
+    blobs: &dyn BlobStore,
+) -> Result<(), AppError> {
+    // lido antes da anonimização, que limpa o campo
+    let avatar = profile_repository::find_profile_by_user_id(user_id, db)
+        .await
+        .ok()
+        .and_then(|profile| profile.avatar);
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao iniciar transação: {e}"))))?;
+
+    let affected = user_repository::anonymize_user_in_tx(user_id, &mut tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao anonimizar usuário: {e}"))))?;
+    if affected == 0 {
+        return Err(AppError::NotFound(Some(
+            "Conta excluída não encontrada".into(),
+        )));
+    }
+
+    profile_repository::anonymize_profile_in_tx(user_id, &mut tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao anonimizar perfil: {e}"))))?;
+    account_data_repository::delete_account_data_in_tx(user_id, &mut tx)
+        .await
+        .map_err(|e| {
+            AppError::DatabaseError(Some(format!("Erro ao apagar dados da conta: {e}")))
+        })?;
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao commitar transação: {e}"))))?;
+
+    // os logs podem citar o e-mail; uma falha aqui não desfaz a anonimização
+    if let Err(err) = log_repository::delete_logs_by_user(mongo, user_id).await {
+        warn!(user_id = %user_id, error = %err, "Erro ao apagar logs da conta anonimizada");
+    }
+    if let Some(avatar) = avatar {
+        avatar_service::delete_avatar_objects(user_id, &avatar, blobs).await;
+    }
+
+    info!(user_id = %user_id, "Conta anonimizada");
+    Ok(())
+}
+
+/// Anonimiza as contas cujo prazo de restauração terminou e retorna quantas foram processadas
+pub async fn purge_expired_deletions(
+    grace_days: i64,
+    db: &PgPool,
+    mongo: &Database,
+    blobs: &dyn BlobStore,
+) -> Result<u64, AppError> {
+    let deleted_before = Utc::now().naive_utc() - Duration::days(grace_days);
+    let user_ids = user_repository::list_pending_anonymization(deleted_before, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    let mut purged = 0;
+    for user_id in user_ids {
+        match anonymize_user(user_id, db, mongo, blobs).await {
+            Ok(()) => purged += 1,
+            Err(err) => error!(user_id = %user_id, error = %err, "Erro ao anonimizar conta"),
+        }
+    }
+
+    Ok(purged)
+}
+
+/// Inicia a anonimização periódica das contas excluídas
+pub fn spawn_purge_job(
+    db: PgPool,
+    mongo: Database,
+    blobs: Arc<dyn BlobStore>,
+    settings: AccountSettings,
+) {
+    let interval = std::time::Duration::from_secs(settings.deletion_purge_interval_seconds);
+
+    tokio::spawn(async move {
+        info!(
+            grace_days = settings.deletion_grace_days,
+            purge_interval = settings.deletion_purge_interval_seconds,
+            "Anonimização de contas excluídas iniciada"
+        );
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+
+            match purge_expired_deletions(settings.deletion_grace_days, &db, &mongo, blobs.as_ref())
+                .await
+            {
+                Ok(0) => {}
+                Ok(purged) => info!(purged, "Contas excluídas anonimizadas"),
+                Err(err) => error!(error = %err, "Erro ao anonimizar contas excluídas"),
+            }
+        }
+    });
+}
@@ -0,0 +1,73 @@
+use crate::config::{AccountSettings, get_settings};
+use crate::errors::app_error::AppError;
+use crate::repositories::{profile_repository, user_repository};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+/// Ações bloqueadas enquanto o e-mail da conta não for confirmado
+#[derive(Debug, Clone)]
+pub struct ConfirmedEmailPolicy {
+    pub actions: Vec<String>,
+}
+
+impl ConfirmedEmailPolicy {
+    pub fn from_settings(settings: &AccountSettings) -> Self {
+        Self {
+            actions: settings.require_confirmed_email_for.clone(),
+        }
+    }
+
+    pub fn requires_confirmation(&self, action: &str) -> bool {
+        self.actions.iter().any(|a| a == action)
+    }
+
+    pub async fn ensure_allowed(
+        &self,
+        user_id: Uuid,
+        action: &str,
+        db: &PgPool,
+    ) -> Result<(), AppError> {
+        if !self.requires_confirmation(action) {
+            return Ok(());
+        }
+
+        let confirmed = profile_repository::is_email_confirmed(user_id, db)
+            .await
+            .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+        if !confirmed {
+            info!(user_id = %user_id, action, "Ação bloqueada: e-mail não confirmado");
+            return Err(AppError::EmailNotConfirmed(Some(
+                "Confirme seu e-mail para realizar esta ação. Peça um novo link em \
+                 /resend-confirmation/ se necessário"
+                    .into(),
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Aplica a política configurada em `REQUIRE_CONFIRMED_EMAIL_FOR`
+pub async fn ensure_email_confirmed(
+    user_id: Uuid,
+    action: &str,
+    db: &PgPool,
+) -> Result<(), AppError> {
+    ConfirmedEmailPolicy::from_settings(&get_settings().account)
+        .ensure_allowed(user_id, action, db)
+        .await
+}
+
+/// Recusa contas suspensas por um administrador
+pub async fn ensure_not_suspended(user_id: Uuid, db: &PgPool) -> Result<(), AppError> {
+    let suspended = user_repository::is_suspended(user_id, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    if suspended {
+        info!(user_id = %user_id, "Acesso recusado: conta suspensa");
+        return Err(AppError::Forbidden(Some("Conta suspensa".into())));
+    }
+
+    Ok(())
+}
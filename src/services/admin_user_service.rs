@@ -0,0 +1,287 @@
+use crate::config::app_state::AppState;
+use crate::config::get_settings;
+use crate::errors::app_error::AppError;
+use crate::log_fail;
+use crate::logs::model::LogLevel;
+use crate::models::profile::Profile;
+use crate::models::role::DEFAULT_ROLE;
+use crate::models::token::token_types;
+use crate::models::user::{
+    AdminUpdateUserRequest, AdminUserDetail, AdminUserQuery, AdminUserRequest, AdminUserSummary,
+    PasswordResetAction, SuspendUserRequest, User, UserWithProfile,
+};
+use crate::repositories::{profile_repository, role_repository, token_repository, user_repository};
+use crate::services::{
+    auth_service, email_service, login_throttle_service, profile_service, token_service,
+    user_public_service, username_service,
+};
+use crate::utils::pagination::PaginatedResponse;
+use crate::utils::password;
+use actix_web::web;
+use chrono::Utc;
+use tracing::{info, warn};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Provisiona um usuário definindo campos privilegiados (papéis, e-mail confirmado),
+/// que não são aceitos no cadastro público
+pub async fn provision_user(
+    req: AdminUserRequest,
+    admin_id: Uuid,
+    state: &web::Data<AppState>,
+) -> Result<UserWithProfile, AppError> {
+    let db = &state.db;
+    let mongo_db = &state.mongo;
+
+    if let Err(e) = req.validate() {
+        warn!(error = %e, "Dados inválidos no provisionamento de usuário");
+        return Err(AppError::BadRequest(Some(format!(
+            "Dados inválidos: {}",
+            e
+        ))));
+    }
+
+    let roles = req
+        .roles
+        .clone()
+        .unwrap_or_else(|| vec![DEFAULT_ROLE.to_string()]);
+    for role in &roles {
+        let exists = role_repository::role_exists(role, db)
+            .await
+            .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+        if !exists {
+            return Err(AppError::BadRequest(Some(format!(
+                "Papel inexistente: {}",
+                role
+            ))));
+        }
+    }
+
+    let mut user = User::new(
+        &username_service::generate_unique_username(&req.email, None, db).await?,
+        &req.email,
+        &req.first_name,
+        &req.last_name,
+        password::hash_password(&req.password).await?,
+    );
+
+    if let Some(document) = req.profile.as_ref().and_then(|p| p.document.as_deref()) {
+        let unique = get_settings().account.unique_document;
+        profile_service::prepare_document(document, user.id, unique, db).await?;
+    }
+    let mut profile = Profile::from_request(user.id, req.profile);
+    profile.confirm_email = req.confirm_email.unwrap_or(false);
+    profile.unsubscribe = req.unsubscribe.unwrap_or(false);
+
+    match user_public_service::create_user_and_profile(&mut user, &profile, &roles, state).await {
+        Ok(()) => {}
+        Err(AppError::Conflict(message)) => return Err(AppError::Conflict(message)),
+        Err(err) => {
+            log_fail!(
+                err,
+                LogLevel::Error,
+                format!("Erro ao provisionar o usuário: {}", &req.email),
+                "admin_user_service",
+                Some(admin_id),
+                mongo_db
+            );
+            return Err(AppError::BadRequest(Some("Erro ao criar o usuario".into())));
+        }
+    }
+
+    info!(
+        user_id = %user.id,
+        admin_id = %admin_id,
+        "Usuário provisionado por administrador"
+    );
+
+    Ok(UserWithProfile::from_user_and_profile(user, profile))
+}
+
+/// Remove o bloqueio de login por excesso de tentativas da conta
+pub async fn unlock_user(
+    user_id: Uuid,
+    admin_id: Uuid,
+    state: &web::Data<AppState>,
+) -> Result<(), AppError> {
+    let user = user_repository::find_user_by_id(user_id, &state.db).await?;
+
+    login_throttle_service::unlock_account(&user.email, state).await?;
+
+    info!(
+        user_id = %user.id,
+        admin_id = %admin_id,
+        "Conta desbloqueada por administrador"
+    );
+
+    Ok(())
+}
+
+/// Busca usuários por e-mail, nome, papel e estado da conta
+pub async fn list_users(
+    query: AdminUserQuery,
+    state: &web::Data<AppState>,
+) -> Result<PaginatedResponse<AdminUserSummary>, AppError> {
+    let db = &state.db;
+
+    let count = user_repository::count_users_for_admin(&query, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao contar usuários: {e}"))))?;
+    let results = user_repository::search_users_for_admin(&query, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao listar usuários: {e}"))))?;
+
+    Ok(PaginatedResponse {
+        count,
+        results,
+        limit: query.limit,
+        offset: query.offset,
+    })
+}
+
+pub async fn get_user(
+    user_id: Uuid,
+    state: &web::Data<AppState>,
+) -> Result<AdminUserDetail, AppError> {
+    let db = &state.db;
+
+    let user = user_repository::find_user_for_admin(user_id, db).await?;
+    let profile = profile_repository::find_profile_by_user_id(user_id, db).await?;
+
+    Ok(AdminUserDetail { user, profile })
+}
+
+/// Atualiza o perfil e a redefinição de senha de outro usuário
+pub async fn update_user(
+    user_id: Uuid,
+    mut req: AdminUpdateUserRequest,
+    admin_id: Uuid,
+    state: &web::Data<AppState>,
+) -> Result<AdminUserDetail, AppError> {
+    let db = &state.db;
+
+    req.validate()
+        .map_err(|e| AppError::BadRequest(Some(format!("Dados inválidos: {}", e))))?;
+
+    let user = user_repository::find_user_by_id(user_id, db).await?;
+
+    if let Some(profile) = req.profile.as_mut() {
+        let document_type = profile_service::normalize_profile_update(user_id, profile, db).await?;
+        profile_service::save_profile_update(user_id, profile, document_type, db).await?;
+    }
+    if let Some(action) = req.password_reset {
+        apply_password_reset(&user, action, state).await?;
+    }
+
+    info!(
+        user_id = %user_id,
+        admin_id = %admin_id,
+        "Usuário atualizado por administrador"
+    );
+
+    get_user(user_id, state).await
+}
+
+async fn apply_password_reset(
+    user: &User,
+    action: PasswordResetAction,
+    state: &web::Data<AppState>,
+) -> Result<(), AppError> {
+    let db = &state.db;
+
+    let mut tx = db.begin().await.map_err(|err| {
+        AppError::DatabaseError(Some(format!("Erro ao iniciar transação: {}", err)))
+    })?;
+
+    match action {
+        PasswordResetAction::Send => {
+            let profile = profile_repository::find_profile_by_user_id(user.id, db).await?;
+            let code = token_service::create_user_token_in_tx(
+                user.id,
+                token_types::CHANGE_PASSWORD,
+                &mut tx,
+            )
+            .await?;
+            email_service::queue_reset_password_in_tx(
+                user,
+                &profile.preferred_language,
+                &code,
+                &mut tx,
+            )
+            .await?;
+        }
+        PasswordResetAction::Cancel => {
+            token_repository::consume_pending_tokens_in_tx(
+                user.id,
+                token_types::CHANGE_PASSWORD,
+                &mut tx,
+            )
+            .await?;
+        }
+    }
+
+    tx.commit().await.map_err(|err| {
+        AppError::DatabaseError(Some(format!("Erro ao commitar transação: {}", err)))
+    })
+}
+
+/// Suspende a conta e encerra suas sessões; login e tokens passam a ser recusados
+pub async fn suspend_user(
+    user_id: Uuid,
+    req: SuspendUserRequest,
+    admin_id: Uuid,
+    state: &web::Data<AppState>,
+) -> Result<AdminUserDetail, AppError> {
+    let db = &state.db;
+
+    req.validate()
+        .map_err(|e| AppError::BadRequest(Some(format!("Dados inválidos: {}", e))))?;
+    if user_id == admin_id {
+        return Err(AppError::BadRequest(Some(
+            "Não é possível suspender a própria conta".into(),
+        )));
+    }
+
+    let affected = user_repository::set_suspension(
+        user_id,
+        Some(Utc::now().naive_utc()),
+        req.reason.as_deref(),
+        db,
+    )
+    .await
+    .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao suspender usuário: {e}"))))?;
+    if affected == 0 {
+        return Err(AppError::NotFound(Some("Usuário não encontrado".into())));
+    }
+
+    auth_service::revoke_all_sessions(user_id, db).await?;
+
+    info!(
+        user_id = %user_id,
+        admin_id = %admin_id,
+        "Conta suspensa por administrador"
+    );
+
+    get_user(user_id, state).await
+}
+
+pub async fn unsuspend_user(
+    user_id: Uuid,
+    admin_id: Uuid,
+    state: &web::Data<AppState>,
+) -> Result<AdminUserDetail, AppError> {
+    let affected = user_repository::set_suspension(user_id, None, None, &state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao reativar usuário: {e}"))))?;
+    if affected == 0 {
+        return Err(AppError::NotFound(Some("Usuário não encontrado".into())));
+    }
+
+    info!(
+        user_id = %user_id,
+        admin_id = %admin_id,
+        "Suspensão removida por administrador"
+    );
+
+    get_user(user_id, state).await
+}
@@ -0,0 +1,160 @@
+use crate::errors::app_error::AppError;
+use crate::models::api_key::{
+    API_KEY_PREFIX, ApiKey, ApiKeyCreatedResponse, ApiKeyExpiryRequest, ApiKeyRequest,
+};
+use crate::models::auth::Claims;
+use crate::repositories::{api_key_repository, role_repository, user_repository};
+use crate::utils::crypto::{generate_random_code, sha256_hex};
+use chrono::{NaiveDateTime, Utc};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+use validator::Validate;
+
+const API_KEY_ID_LENGTH: usize = 8;
+const API_KEY_SECRET_LENGTH: usize = 32;
+
+/// Cria uma chave para `user_id` (pessoal, ou de uma conta de serviço quando criada por um
+/// administrador). Os escopos precisam ser permissões que o dono já possui.
+pub async fn create_api_key(
+    user_id: Uuid,
+    payload: ApiKeyRequest,
+    created_by: Uuid,
+    db: &PgPool,
+) -> Result<ApiKeyCreatedResponse, AppError> {
+    if let Err(e) = payload.validate() {
+        return Err(AppError::BadRequest(Some(format!(
+            "Dados inválidos: {}",
+            e
+        ))));
+    }
+    ensure_future_expiry(payload.expires_at)?;
+
+    // garante que o dono existe e não foi excluído
+    user_repository::find_user_by_id(user_id, db).await?;
+
+    let owner_permissions = role_repository::find_permission_codes_by_user(user_id, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    if let Some(scope) = payload
+        .scopes
+        .iter()
+        .find(|scope| !owner_permissions.contains(scope))
+    {
+        return Err(AppError::BadRequest(Some(format!(
+            "Escopo não permitido: {}",
+            scope
+        ))));
+    }
+
+    let prefix = format!(
+        "{}_{}",
+        API_KEY_PREFIX,
+        generate_random_code(API_KEY_ID_LENGTH)
+    );
+    let key = format!("{}_{}", prefix, generate_random_code(API_KEY_SECRET_LENGTH));
+
+    let mut scopes = payload.scopes;
+    scopes.sort();
+    scopes.dedup();
+
+    let api_key = api_key_repository::create_api_key(
+        user_id,
+        &payload.name,
+        &prefix,
+        &sha256_hex(&key),
+        &scopes,
+        payload.expires_at,
+        created_by,
+        db,
+    )
+    .await
+    .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao criar chave de API: {e}"))))?;
+
+    info!(user_id = %user_id, prefix = %api_key.prefix, "Chave de API criada");
+
+    Ok(ApiKeyCreatedResponse { api_key, key })
+}
+
+pub async fn list_api_keys(user_id: Uuid, db: &PgPool) -> Result<Vec<ApiKey>, AppError> {
+    api_key_repository::list_api_keys_by_user(user_id, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))
+}
+
+pub async fn revoke_api_key(id: Uuid, user_id: Uuid, db: &PgPool) -> Result<(), AppError> {
+    let revoked = api_key_repository::revoke_api_key(id, user_id, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    if !revoked {
+        return Err(AppError::NotFound(Some(
+            "Chave de API não encontrada".into(),
+        )));
+    }
+
+    info!(user_id = %user_id, api_key_id = %id, "Chave de API revogada");
+    Ok(())
+}
+
+pub async fn update_api_key_expiry(
+    id: Uuid,
+    user_id: Uuid,
+    payload: ApiKeyExpiryRequest,
+    db: &PgPool,
+) -> Result<ApiKey, AppError> {
+    ensure_future_expiry(payload.expires_at)?;
+
+    api_key_repository::update_api_key_expiry(id, user_id, payload.expires_at, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?
+        .ok_or_else(|| AppError::NotFound(Some("Chave de API não encontrada".into())))
+}
+
+/// Valida o header `Authorization: ApiKey <key>` e monta as mesmas `Claims` de um JWT.
+/// As permissões efetivas são os escopos da chave que o dono ainda possui.
+pub async fn authenticate(key: &str, db: &PgPool) -> Result<Claims, AppError> {
+    let invalid = || AppError::Unauthorized(Some("Chave de API inválida".into()));
+
+    if !key.starts_with(API_KEY_PREFIX) {
+        return Err(invalid());
+    }
+
+    let api_key = api_key_repository::touch_active_api_key(&sha256_hex(key), db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?
+        .ok_or_else(invalid)?;
+
+    let owner_permissions = role_repository::find_permission_codes_by_user(api_key.user_id, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    let permissions = api_key
+        .scopes
+        .into_iter()
+        .filter(|scope| owner_permissions.contains(scope))
+        .collect();
+
+    Ok(Claims {
+        sub: api_key.user_id.to_string(),
+        exp: api_key
+            .expires_at
+            .map(|expires_at| expires_at.and_utc().timestamp() as usize)
+            .unwrap_or(0),
+        // a chave não herda papéis, apenas os escopos concedidos
+        roles: Vec::new(),
+        permissions,
+        jti: format!("api_key:{}", api_key.id),
+        // sem sessão: rotas de sessão (logout, /me/sessions/) respondem 401
+        sid: String::new(),
+        api_key: true,
+    })
+}
+
+fn ensure_future_expiry(expires_at: Option<NaiveDateTime>) -> Result<(), AppError> {
+    match expires_at {
+        Some(expires_at) if expires_at <= Utc::now().naive_utc() => Err(AppError::BadRequest(
+            Some("A expiração deve estar no futuro".into()),
+        )),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,325 @@
+use crate::config::app_state::AppState;
+use crate::config::{JwtSettings, get_settings};
+use crate::errors::app_error::AppError;
+use crate::log_fail;
+use crate::logs::model::LogLevel;
+use crate::models::auth::Claims;
+use crate::models::refresh_token::TokenPairResponse;
+use crate::models::session::{SessionInfo, SessionResponse};
+use crate::repositories::{
+    mfa_repository, refresh_token_repository, revoked_token_repository, role_repository,
+    session_repository, user_repository,
+};
+use crate::utils::crypto::{generate_random_code, sha256_hex};
+use crate::utils::jwt::{generate_jwt, verify_jwt};
+use actix_web::web;
+use chrono::{Duration, NaiveDateTime, Utc};
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Abre uma nova sessão (dispositivo) para o usuário e emite o primeiro par de tokens
+pub async fn start_session(
+    user_id: Uuid,
+    info: &SessionInfo,
+    db: &PgPool,
+) -> Result<TokenPairResponse, AppError> {
+    let session_id = Uuid::new_v4();
+
+    session_repository::create_session(db, session_id, user_id, info, refresh_token_expiry())
+        .await?;
+
+    issue_token_pair(user_id, session_id, db).await
+}
+
+/// Gera um access token (JWT) e um refresh token opaco vinculados à sessão.
+/// Os refresh tokens de uma sessão formam uma família (`family_id = session_id`).
+/// Papéis e permissões são lidos do banco a cada emissão.
+pub async fn issue_token_pair(
+    user_id: Uuid,
+    session_id: Uuid,
+    db: &PgPool,
+) -> Result<TokenPairResponse, AppError> {
+    let settings = get_settings();
+    let jti = Uuid::new_v4().to_string();
+
+    let roles = role_repository::find_role_names_by_user(user_id, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao buscar papéis: {e}"))))?;
+    let permissions = role_repository::find_permission_codes_by_user(user_id, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao buscar permissões: {e}"))))?;
+
+    let token = generate_jwt(
+        &user_id.to_string(),
+        roles,
+        permissions,
+        &jti,
+        &session_id.to_string(),
+    )
+    .map_err(|err| {
+        error!(error = %err, "Erro ao gerar token");
+        AppError::InternalError(Some("Erro ao gerar token".into()))
+    })?;
+
+    let refresh_token = generate_random_code(64);
+
+    refresh_token_repository::create_refresh_token(
+        db,
+        user_id,
+        session_id,
+        &sha256_hex(&refresh_token),
+        &jti,
+        refresh_token_expiry(),
+    )
+    .await?;
+
+    Ok(TokenPairResponse {
+        token,
+        expires_in: settings.jwt.expires_in.to_string(),
+        refresh_token,
+        refresh_expires_in: settings.jwt.refresh_expires_in.to_string(),
+    })
+}
+
+/// Troca um refresh token válido por um novo par (rotação).
+/// Reuso de um refresh token já trocado encerra a sessão inteira.
+pub async fn refresh_token_pair(
+    refresh_token: &str,
+    state: &web::Data<AppState>,
+) -> Result<TokenPairResponse, AppError> {
+    let db = &state.db;
+    let mongo_db = &state.mongo;
+    let token_hash = sha256_hex(refresh_token);
+
+    let current = match refresh_token_repository::consume_refresh_token(db, &token_hash).await? {
+        Some(token) => token,
+        None => {
+            if let Some(reused) =
+                refresh_token_repository::find_refresh_token_by_hash(db, &token_hash).await?
+            {
+                warn!(user_id = %reused.user_id, "Reuso de refresh token detectado");
+                log_fail!(
+                    format!("sessão {}", reused.family_id),
+                    LogLevel::Warn,
+                    "Reuso de refresh token detectado",
+                    "auth_service",
+                    Some(reused.user_id),
+                    mongo_db
+                );
+                revoke_session(reused.user_id, reused.family_id, db).await?;
+            }
+            return Err(AppError::Unauthorized(Some(
+                "Refresh token inválido".into(),
+            )));
+        }
+    };
+
+    if current.expires_at < Utc::now().naive_utc() {
+        return Err(AppError::Unauthorized(Some(
+            "Refresh token expirado".into(),
+        )));
+    }
+
+    if !session_repository::is_session_active(current.family_id, current.user_id, db).await? {
+        return Err(AppError::Unauthorized(Some("Sessão encerrada".into())));
+    }
+
+    let user = user_repository::find_user_by_id(current.user_id, db)
+        .await
+        .map_err(|_| AppError::Unauthorized(Some("Refresh token inválido".into())))?;
+
+    session_repository::touch_session(db, current.family_id, refresh_token_expiry()).await?;
+
+    issue_token_pair(user.id, current.family_id, db).await
+}
+
+pub async fn list_sessions(
+    user_id: Uuid,
+    current_session_id: Uuid,
+    state: &web::Data<AppState>,
+) -> Result<Vec<SessionResponse>, AppError> {
+    let db = &state.db;
+
+    let sessions = session_repository::list_active_sessions_by_user(user_id, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao listar sessões: {e}"))))?;
+
+    Ok(sessions
+        .into_iter()
+        .map(|session| SessionResponse {
+            current: session.id == current_session_id,
+            session,
+        })
+        .collect())
+}
+
+/// Encerra uma sessão: revoga seus refresh tokens e os access tokens emitidos com eles.
+/// Retorna `false` se a sessão não existe ou já estava encerrada.
+pub async fn revoke_session(
+    user_id: Uuid,
+    session_id: Uuid,
+    db: &PgPool,
+) -> Result<bool, AppError> {
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao iniciar transação: {e}"))))?;
+
+    let affected = session_repository::revoke_session_in_tx(session_id, user_id, &mut tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao encerrar sessão: {e}"))))?;
+
+    let jtis = refresh_token_repository::revoke_family_in_tx(session_id, &mut tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao revogar tokens: {e}"))))?;
+
+    revoked_token_repository::revoke_jtis_in_tx(user_id, &jtis, access_token_expiry(), &mut tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao revogar tokens: {e}"))))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao commitar transação: {e}"))))?;
+
+    Ok(affected > 0)
+}
+
+/// Encerra todas as sessões do usuário ("sair de todos os dispositivos")
+pub async fn revoke_all_sessions(user_id: Uuid, db: &PgPool) -> Result<(), AppError> {
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao iniciar transação: {e}"))))?;
+
+    session_repository::revoke_all_sessions_by_user_in_tx(user_id, &mut tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao encerrar sessões: {e}"))))?;
+
+    let jtis = refresh_token_repository::revoke_all_by_user_in_tx(user_id, &mut tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao revogar tokens: {e}"))))?;
+
+    revoked_token_repository::revoke_jtis_in_tx(user_id, &jtis, access_token_expiry(), &mut tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao revogar tokens: {e}"))))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao commitar transação: {e}"))))?;
+
+    Ok(())
+}
+
+/// Encerra as sessões do usuário exceto `keep_session_id` (a do próprio pedido)
+pub async fn revoke_other_sessions(
+    user_id: Uuid,
+    keep_session_id: Uuid,
+    db: &PgPool,
+) -> Result<(), AppError> {
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao iniciar transação: {e}"))))?;
+
+    session_repository::revoke_other_sessions_by_user_in_tx(user_id, keep_session_id, &mut tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao encerrar sessões: {e}"))))?;
+
+    let jtis = refresh_token_repository::revoke_all_by_user_except_family_in_tx(
+        user_id,
+        keep_session_id,
+        &mut tx,
+    )
+    .await
+    .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao revogar tokens: {e}"))))?;
+
+    revoked_token_repository::revoke_jtis_in_tx(user_id, &jtis, access_token_expiry(), &mut tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao revogar tokens: {e}"))))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao commitar transação: {e}"))))?;
+
+    Ok(())
+}
+
+/// Decodifica o JWT, confere a lista de revogação (`jti`) e se a sessão (`sid`) segue ativa
+pub async fn validate_access_token(token: &str, db: &PgPool) -> Result<Claims, AppError> {
+    let claims =
+        verify_jwt(token).map_err(|_| AppError::Unauthorized(Some("Token inválido".into())))?;
+
+    if revoked_token_repository::is_revoked(&claims.jti, db).await? {
+        return Err(AppError::Unauthorized(Some("Token revogado".into())));
+    }
+
+    let (user_id, session_id) = match (Uuid::parse_str(&claims.sub), Uuid::parse_str(&claims.sid)) {
+        (Ok(user_id), Ok(session_id)) => (user_id, session_id),
+        _ => return Err(AppError::Unauthorized(Some("Token inválido".into()))),
+    };
+
+    if !session_repository::is_session_active(session_id, user_id, db).await? {
+        return Err(AppError::Unauthorized(Some("Sessão encerrada".into())));
+    }
+
+    Ok(claims)
+}
+
+// Mesma folga que a validação do `exp` aplica (padrão do jsonwebtoken)
+const REVOKED_TOKEN_PURGE_LEEWAY_SECONDS: i64 = 60;
+
+/// Apaga refresh tokens e desafios de MFA expirados e entradas da lista de revogação
+/// que já não podem mais ser aceitas; retorna quantas linhas foram removidas
+pub async fn purge_expired_tokens(db: &PgPool) -> Result<u64, AppError> {
+    let now = Utc::now().naive_utc();
+
+    let refresh = refresh_token_repository::delete_expired(now, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    let revoked = revoked_token_repository::delete_expired(
+        now - Duration::seconds(REVOKED_TOKEN_PURGE_LEEWAY_SECONDS),
+        db,
+    )
+    .await
+    .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    let challenges = mfa_repository::delete_expired_challenges(now, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    Ok(refresh + revoked + challenges)
+}
+
+/// Inicia a limpeza periódica dos refresh tokens e revogações expirados
+pub fn spawn_purge_job(db: PgPool, settings: JwtSettings) {
+    let interval = std::time::Duration::from_secs(settings.purge_interval_seconds);
+
+    tokio::spawn(async move {
+        info!(
+            purge_interval = settings.purge_interval_seconds,
+            "Limpeza de refresh tokens expirados iniciada"
+        );
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+
+            match purge_expired_tokens(&db).await {
+                Ok(0) => {}
+                Ok(purged) => info!(purged, "Refresh tokens e revogações expirados removidos"),
+                Err(err) => error!(error = %err, "Erro ao remover refresh tokens expirados"),
+            }
+        }
+    });
+}
+
+fn refresh_token_expiry() -> NaiveDateTime {
+    let settings = get_settings();
+    (Utc::now() + Duration::seconds(settings.jwt.refresh_expires_in as i64)).naive_utc()
+}
+
+// Um access token revogado só precisa ficar na lista até expirar
+fn access_token_expiry() -> NaiveDateTime {
+    let settings = get_settings();
+    (Utc::now() + Duration::seconds(settings.jwt.expires_in as i64)).naive_utc()
+}
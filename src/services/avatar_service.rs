@@ -0,0 +1,124 @@
+use crate::config::app_state::AppState;
+use crate::config::get_settings;
+use crate::errors::app_error::AppError;
+use crate::models::profile::{AvatarResponse, AvatarThumbnail};
+use crate::repositories::profile_repository;
+use crate::storage::BlobStore;
+use crate::utils::avatar_image;
+use actix_web::web;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+/// Prefixo dos objetos de avatar de um usuário
+fn avatar_prefix(user_id: Uuid) -> String {
+    format!("avatars/{}/", user_id)
+}
+
+/// Valida a imagem, grava as miniaturas e aponta `Profile.avatar` para a maior delas.
+/// Os arquivos do avatar anterior são removidos depois da troca.
+pub async fn upload_avatar(
+    user_id: Uuid,
+    content_type: &str,
+    data: Vec<u8>,
+    state: &web::Data<AppState>,
+) -> Result<AvatarResponse, AppError> {
+    let settings = &get_settings().avatar;
+    if data.is_empty() {
+        return Err(AppError::BadRequest(Some("Arquivo de imagem vazio".into())));
+    }
+    if data.len() > settings.max_bytes {
+        return Err(AppError::PayloadTooLarge(Some(format!(
+            "A imagem deve ter no máximo {} bytes",
+            settings.max_bytes
+        ))));
+    }
+
+    let mut sizes = settings.sizes.clone();
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    sizes.dedup();
+    let thumbnails = avatar_image::process_avatar(data, content_type.to_string(), sizes).await?;
+
+    let previous = profile_repository::find_profile_by_user_id(user_id, &state.db)
+        .await?
+        .avatar;
+
+    let upload_prefix = format!("{}{}/", avatar_prefix(user_id), Uuid::new_v4());
+    let mut stored: Vec<(u32, String)> = Vec::with_capacity(thumbnails.len());
+    for thumbnail in thumbnails {
+        let key = format!("{}{}.jpg", upload_prefix, thumbnail.size);
+        if let Err(e) = state.blobs.put(&key, "image/jpeg", thumbnail.data).await {
+            error!(user_id = %user_id, key = %key, error = %e, "Erro ao gravar avatar");
+            for (_, key) in &stored {
+                let _ = state.blobs.delete(key).await;
+            }
+            return Err(AppError::InternalError(Some(
+                "Erro ao salvar avatar".into(),
+            )));
+        }
+        stored.push((thumbnail.size, key));
+    }
+
+    let thumbnails: Vec<AvatarThumbnail> = stored
+        .iter()
+        .map(|(size, key)| AvatarThumbnail {
+            size: *size,
+            url: state.blobs.url(key),
+        })
+        .collect();
+    let avatar = thumbnails[0].url.clone();
+
+    profile_repository::update_avatar(user_id, Some(&avatar), &state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    if let Some(previous) = previous {
+        delete_avatar_objects(user_id, &previous, state.blobs.as_ref()).await;
+    }
+
+    Ok(AvatarResponse { avatar, thumbnails })
+}
+
+/// Remove o avatar do perfil e os arquivos armazenados
+pub async fn remove_avatar(user_id: Uuid, state: &web::Data<AppState>) -> Result<(), AppError> {
+    let profile = profile_repository::find_profile_by_user_id(user_id, &state.db).await?;
+
+    profile_repository::update_avatar(user_id, None, &state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    if let Some(avatar) = profile.avatar {
+        delete_avatar_objects(user_id, &avatar, state.blobs.as_ref()).await;
+    }
+    Ok(())
+}
+
+/// Apaga as miniaturas do envio a que `avatar_url` pertence. Só mexe em objetos do próprio
+/// usuário: URLs externas (informadas pelo PATCH do perfil) ou de outro usuário são ignoradas.
+/// Falhas só são registradas, já que o perfil não aponta mais para esses arquivos.
+pub async fn delete_avatar_objects(user_id: Uuid, avatar_url: &str, blobs: &dyn BlobStore) {
+    let Some(key) = blobs.key_from_url(avatar_url) else {
+        return;
+    };
+    let Some((upload_prefix, _)) = key.rsplit_once('/') else {
+        return;
+    };
+    if !key.starts_with(&avatar_prefix(user_id)) {
+        return;
+    }
+
+    let mut keys: Vec<String> = get_settings()
+        .avatar
+        .sizes
+        .iter()
+        .map(|size| format!("{}/{}.jpg", upload_prefix, size))
+        .collect();
+    if !keys.contains(&key) {
+        keys.push(key.clone());
+    }
+
+    for key in keys {
+        if let Err(e) = blobs.delete(&key).await {
+            warn!(user_id = %user_id, key = %key, error = %e, "Erro ao apagar avatar antigo");
+        }
+    }
+}
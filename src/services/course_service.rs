@@ -107,7 +107,7 @@ pub async fn create_course_service(
         &format!("Curso '{}' foi criado com sucesso", course.name),
         ObjCodeType::Platform,
         None,
-        &state,
+        state,
     )
     .await?;
 
@@ -0,0 +1,262 @@
+use crate::config::EmailOutboxSettings;
+use crate::errors::app_error::AppError;
+use crate::mailer::{EmailMessage, EmailSender};
+use crate::models::email_outbox::{OutboxEmail, OutboxEmailFilter, email_status};
+use crate::models::email_preferences::email_categories;
+use crate::repositories::{email_outbox_repository, profile_repository};
+use crate::utils::pagination::PaginatedResponse;
+use chrono::{Duration, NaiveDateTime, Utc};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Tempo que uma mensagem fica reservada para o worker que a pegou
+const LEASE_SECONDS: i64 = 300;
+
+/// Por quanto tempo uma mensagem em dead letter guarda o corpo para ser devolvida à fila
+const FAILED_BODY_RETENTION_DAYS: i64 = 7;
+
+/// Regras de reenvio: atraso de `retry_base_seconds * 2^(tentativa - 1)`, limitado a
+/// `retry_max_seconds`. O número máximo de tentativas é gravado em cada mensagem.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub retry_base_seconds: u64,
+    pub retry_max_seconds: u64,
+}
+
+impl RetryPolicy {
+    pub fn from_settings(settings: &EmailOutboxSettings) -> Self {
+        Self {
+            retry_base_seconds: settings.retry_base_seconds,
+            retry_max_seconds: settings.retry_max_seconds,
+        }
+    }
+
+    fn next_attempt_at(&self, attempts: i32) -> NaiveDateTime {
+        let exponent = attempts.saturating_sub(1).clamp(0, 30) as u32;
+        let delay = self
+            .retry_base_seconds
+            .saturating_mul(2u64.saturating_pow(exponent))
+            .min(self.retry_max_seconds);
+
+        (Utc::now() + Duration::seconds(delay as i64)).naive_utc()
+    }
+}
+
+fn lease_until() -> NaiveDateTime {
+    (Utc::now() + Duration::seconds(LEASE_SECONDS)).naive_utc()
+}
+
+async fn deliver_claimed(
+    email: &OutboxEmail,
+    mailer: &dyn EmailSender,
+    policy: &RetryPolicy,
+    db: &PgPool,
+) -> sqlx::Result<()> {
+    // a preferência é conferida na entrega, pois pode ter mudado desde o enfileiramento
+    if email.category != email_categories::TRANSACTIONAL {
+        let accepts = match email.user_id {
+            Some(user_id) => {
+                profile_repository::accepts_email_category(user_id, &email.category, db).await?
+            }
+            None => false,
+        };
+        if !accepts {
+            email_outbox_repository::mark_suppressed(email.id, db).await?;
+            info!(
+                email_id = %email.id,
+                category = %email.category,
+                "E-mail descartado: destinatário não aceita a categoria"
+            );
+            return Ok(());
+        }
+    }
+
+    let message = EmailMessage {
+        to: email.to_email.clone(),
+        subject: email.subject.clone(),
+        html: email.html_body.clone(),
+        text: email.text_body.clone(),
+        list_unsubscribe: email.list_unsubscribe_url.clone(),
+    };
+
+    match mailer.send(&message).await {
+        Ok(provider_message_id) => {
+            email_outbox_repository::mark_sent(email.id, &provider_message_id, db).await?;
+            info!(email_id = %email.id, to = %email.to_email, "E-mail entregue");
+        }
+        Err(err) if email.attempts >= email.max_attempts => {
+            email_outbox_repository::mark_failed(email.id, &err.to_string(), db).await?;
+            error!(
+                email_id = %email.id,
+                attempts = email.attempts,
+                error = ?err,
+                "E-mail movido para dead letter após esgotar as tentativas"
+            );
+        }
+        Err(err) => {
+            let next_attempt_at = policy.next_attempt_at(email.attempts);
+            email_outbox_repository::schedule_retry(
+                email.id,
+                &err.to_string(),
+                next_attempt_at,
+                db,
+            )
+            .await?;
+            warn!(
+                email_id = %email.id,
+                attempts = email.attempts,
+                next_attempt_at = %next_attempt_at,
+                error = ?err,
+                "Falha ao entregar e-mail, nova tentativa agendada"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Entrega um lote de mensagens vencidas e retorna quantas foram processadas
+pub async fn process_due_emails(
+    mailer: &dyn EmailSender,
+    policy: &RetryPolicy,
+    batch_size: i64,
+    db: &PgPool,
+) -> Result<usize, AppError> {
+    let emails = email_outbox_repository::claim_due(batch_size, lease_until(), db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    for email in &emails {
+        deliver_claimed(email, mailer, policy, db)
+            .await
+            .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    }
+
+    Ok(emails.len())
+}
+
+/// Tenta entregar uma mensagem específica, se ela estiver na fila e vencida.
+/// Retorna a mensagem atualizada.
+pub async fn deliver_email(
+    id: Uuid,
+    mailer: &dyn EmailSender,
+    policy: &RetryPolicy,
+    db: &PgPool,
+) -> Result<OutboxEmail, AppError> {
+    if let Some(email) = email_outbox_repository::claim_by_id(id, lease_until(), db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?
+    {
+        deliver_claimed(&email, mailer, policy, db)
+            .await
+            .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    }
+
+    get_email(id, db).await
+}
+
+/// Apaga o corpo das mensagens em dead letter mais antigas que a retenção
+pub async fn purge_failed_bodies(db: &PgPool) -> Result<u64, AppError> {
+    let before = (Utc::now() - Duration::days(FAILED_BODY_RETENTION_DAYS)).naive_utc();
+    email_outbox_repository::clear_failed_bodies(before, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))
+}
+
+/// Inicia o worker que entrega a fila periodicamente
+pub fn spawn_worker(db: PgPool, mailer: Arc<dyn EmailSender>, settings: EmailOutboxSettings) {
+    let policy = RetryPolicy::from_settings(&settings);
+    let interval = std::time::Duration::from_secs(settings.poll_interval_seconds);
+
+    tokio::spawn(async move {
+        info!(
+            poll_interval = settings.poll_interval_seconds,
+            "Worker do outbox de e-mails iniciado"
+        );
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+
+            // esvazia a fila em lotes antes de esperar o próximo ciclo
+            loop {
+                match process_due_emails(mailer.as_ref(), &policy, settings.batch_size, &db).await {
+                    Ok(processed) if processed as i64 >= settings.batch_size => continue,
+                    Ok(_) => break,
+                    Err(err) => {
+                        error!(error = %err, "Erro ao processar o outbox de e-mails");
+                        break;
+                    }
+                }
+            }
+
+            match purge_failed_bodies(&db).await {
+                Ok(0) => {}
+                Ok(purged) => info!(purged, "Corpo de e-mails em dead letter removido"),
+                Err(err) => error!(error = %err, "Erro ao limpar e-mails em dead letter"),
+            }
+        }
+    });
+}
+
+pub async fn list_emails(
+    filter: OutboxEmailFilter,
+    limit: i64,
+    offset: i64,
+    db: &PgPool,
+) -> Result<PaginatedResponse<OutboxEmail>, AppError> {
+    if let Some(status) = filter.status.as_deref()
+        && ![
+            email_status::QUEUED,
+            email_status::SENT,
+            email_status::FAILED,
+        ]
+        .contains(&status)
+    {
+        return Err(AppError::BadRequest(Some(format!(
+            "Status inválido: {}",
+            status
+        ))));
+    }
+
+    let status = filter.status.as_deref();
+    let to_email = filter.to_email.as_deref();
+
+    let results = email_outbox_repository::list(status, to_email, limit, offset, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    let count = email_outbox_repository::count(status, to_email, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    Ok(PaginatedResponse {
+        count,
+        results,
+        limit,
+        offset,
+    })
+}
+
+pub async fn get_email(id: Uuid, db: &PgPool) -> Result<OutboxEmail, AppError> {
+    email_outbox_repository::find_by_id(id, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?
+        .ok_or_else(|| AppError::NotFound(Some("E-mail não encontrado".into())))
+}
+
+/// Devolve à fila uma mensagem em dead letter
+pub async fn retry_email(id: Uuid, admin_id: Uuid, db: &PgPool) -> Result<OutboxEmail, AppError> {
+    let email = email_outbox_repository::requeue_failed(id, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?
+        .ok_or_else(|| {
+            AppError::NotFound(Some(
+                "Nenhum e-mail com falha encontrado com este id".into(),
+            ))
+        })?;
+
+    info!(email_id = %id, admin_id = %admin_id, "E-mail devolvido à fila por um administrador");
+    Ok(email)
+}
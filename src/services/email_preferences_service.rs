@@ -0,0 +1,77 @@
+use crate::errors::app_error::AppError;
+use crate::models::email_preferences::{
+    EmailPreferences, UnsubscribeResponse, UpdateEmailPreferencesRequest, email_categories,
+};
+use crate::repositories::profile_repository;
+use crate::utils::jwt::decode_unsubscribe_token;
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+pub async fn get_email_preferences(
+    user_id: Uuid,
+    db: &PgPool,
+) -> Result<EmailPreferences, AppError> {
+    profile_repository::find_email_preferences(user_id, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?
+        .ok_or_else(|| AppError::NotFound(Some("Perfil não encontrado".into())))
+}
+
+pub async fn update_email_preferences(
+    user_id: Uuid,
+    payload: UpdateEmailPreferencesRequest,
+    db: &PgPool,
+) -> Result<EmailPreferences, AppError> {
+    if payload.transactional == Some(false) {
+        return Err(AppError::BadRequest(Some(
+            "E-mails transacionais não podem ser desativados".into(),
+        )));
+    }
+
+    let preferences = profile_repository::update_email_preferences(
+        user_id,
+        payload.course_announcements,
+        payload.marketing,
+        payload.unsubscribe,
+        db,
+    )
+    .await
+    .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?
+    .ok_or_else(|| AppError::NotFound(Some("Perfil não encontrado".into())))?;
+
+    info!(user_id = %user_id, "Preferências de e-mail atualizadas");
+    Ok(preferences)
+}
+
+/// Descadastro pelo link do e-mail, sem login. Idempotente: repetir o link não é erro.
+pub async fn unsubscribe(token: &str, db: &PgPool) -> Result<UnsubscribeResponse, AppError> {
+    let invalid = || AppError::BadRequest(Some("Link de descadastro inválido ou expirado".into()));
+
+    let claims = decode_unsubscribe_token(token).map_err(|_| invalid())?;
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| invalid())?;
+
+    let (course_announcements, marketing) = match claims.category.as_str() {
+        email_categories::COURSE_ANNOUNCEMENTS => (Some(false), None),
+        email_categories::MARKETING => (None, Some(false)),
+        _ => return Err(invalid()),
+    };
+
+    profile_repository::update_email_preferences(
+        user_id,
+        course_announcements,
+        marketing,
+        None,
+        db,
+    )
+    .await
+    .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?
+    .ok_or_else(invalid)?;
+
+    info!(user_id = %user_id, category = %claims.category, "Descadastro pelo link do e-mail");
+
+    Ok(UnsubscribeResponse {
+        category: claims.category,
+        unsubscribed: true,
+    })
+}
@@ -1,131 +1,310 @@
+use crate::config::get_settings;
 use crate::errors::app_error::AppError;
-use aws_sdk_sesv2::Client;
-use aws_sdk_sesv2::config::Credentials;
-use aws_sdk_sesv2::config::{BehaviorVersion, Config};
-use std::env;
-use tera::{Context, Tera};
-
-#[allow(dead_code)]
-pub struct EmailService {
-    ses_client: Client,
-    tera: Tera,
-    sender_email: String,
+use crate::mailer::{EmailMessage, EmailTemplate, EmailTemplates};
+use crate::models::course::Course;
+use crate::models::email_preferences::email_categories;
+use crate::models::user::User;
+use crate::repositories::{email_outbox_repository, profile_repository};
+use crate::utils::jwt::generate_unsubscribe_token;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::sync::OnceLock;
+use tera::Context;
+use tracing::{error, info};
+use uuid::Uuid;
+
+static TEMPLATES: OnceLock<EmailTemplates> = OnceLock::new();
+
+/// Carrega os templates na inicialização para falhar cedo com `EMAIL_TEMPLATES_DIR` inválido
+pub fn init_templates() -> anyhow::Result<()> {
+    templates_or_load()?;
+    Ok(())
+}
+
+fn templates_or_load() -> anyhow::Result<&'static EmailTemplates> {
+    if let Some(templates) = TEMPLATES.get() {
+        return Ok(templates);
+    }
+
+    let templates = EmailTemplates::from_settings(&get_settings().email)?;
+    Ok(TEMPLATES.get_or_init(|| templates))
 }
 
-#[allow(dead_code)]
-impl EmailService {
-    pub async fn new() -> Result<Self, AppError> {
-        // Carregar credenciais AWS das variáveis de ambiente
-        let access_key = env::var("AWS_ACCESS_KEY_ID")
-            .map_err(|_| AppError::InternalError(Some("AWS_ACCESS_KEY_ID não definida".into())))?;
-        let secret_key = env::var("AWS_SECRET_ACCESS_KEY").map_err(|_| {
-            AppError::InternalError(Some("AWS_SECRET_ACCESS_KEY não definida".into()))
+fn render(
+    template: EmailTemplate,
+    user_id: Uuid,
+    to: &str,
+    language: &str,
+    mut ctx: Context,
+) -> Result<EmailMessage, AppError> {
+    // e-mails não transacionais levam o link de descadastro no corpo e no cabeçalho
+    let list_unsubscribe = if template.category() == email_categories::TRANSACTIONAL {
+        None
+    } else {
+        let settings = get_settings();
+        let token =
+            generate_unsubscribe_token(&user_id.to_string(), template.category()).map_err(|e| {
+                error!(error = %e, "Erro ao gerar token de descadastro");
+                AppError::InternalError(Some("Erro ao montar e-mail".into()))
+            })?;
+
+        ctx.insert(
+            "unsubscribe_link",
+            &format!(
+                "{}/unsubscribe?token={}",
+                settings.email.frontend_url.trim_end_matches('/'),
+                token
+            ),
+        );
+        Some(format!(
+            "{}/api/v1/unsubscribe/?token={}",
+            settings.email.app_url.trim_end_matches('/'),
+            token
+        ))
+    };
+
+    let rendered = templates_or_load()
+        .and_then(|templates| templates.render(template, language, &ctx))
+        .map_err(|err| {
+            error!(error = ?err, template = template.name(), language, "Erro ao renderizar e-mail");
+            AppError::InternalError(Some("Erro ao montar e-mail".into()))
         })?;
-        let region = env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string());
 
-        // Carregar email remetente das variáveis de ambiente
-        let sender_email = env::var("DEFAULT_FROM_EMAIL")
-            .map_err(|_| AppError::InternalError(Some("DEFAULT_FROM_EMAIL não definida".into())))?;
+    Ok(EmailMessage {
+        to: to.to_string(),
+        subject: rendered.subject,
+        html: rendered.html,
+        text: Some(rendered.text),
+        list_unsubscribe,
+    })
+}
 
-        let credentials = Credentials::new(&access_key, &secret_key, None, None, "static");
+/// Renderiza o template e grava a mensagem no outbox, dentro da transação informada
+async fn queue_in_tx(
+    template: EmailTemplate,
+    user_id: Uuid,
+    to: &str,
+    language: &str,
+    ctx: Context,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<Uuid, AppError> {
+    let message = render(template, user_id, to, language, ctx)?;
+    let max_attempts = get_settings().email.outbox.max_attempts;
 
-        let config = Config::builder()
-            .behavior_version(BehaviorVersion::latest())
-            .credentials_provider(credentials)
-            .region(aws_sdk_sesv2::config::Region::new(region))
-            .build();
+    let id = email_outbox_repository::enqueue_in_tx(
+        &message,
+        template.category(),
+        Some(user_id),
+        max_attempts,
+        tx,
+    )
+    .await
+    .map_err(|e| {
+        error!(error = %e, to = %message.to, "Erro ao enfileirar e-mail");
+        AppError::DatabaseError(Some("Erro ao enfileirar e-mail".into()))
+    })?;
 
-        let client = Client::from_conf(config);
+    info!(email_id = %id, to = %message.to, subject = %message.subject, "E-mail enfileirado");
+    Ok(id)
+}
 
-        let tera = Tera::new("src/templates/**/*.html").map_err(|err| {
-            eprintln!("Erro ao carregar templates: {:?}", err);
-            AppError::InternalError(Some("Erro no template engine".into()))
-        })?;
+/// Enfileira o e-mail de confirmação; a entrega fica a cargo do worker do outbox
+pub async fn queue_confirm_email_in_tx(
+    user: &User,
+    language: &str,
+    code: &str,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<Uuid, AppError> {
+    let settings = get_settings();
+    let link = format!(
+        "{}/api/v1/confirm-email/{}/",
+        settings.email.app_url.trim_end_matches('/'),
+        code
+    );
 
-        Ok(Self {
-            ses_client: client,
-            tera,
-            sender_email,
-        })
-    }
+    let mut ctx = Context::new();
+    ctx.insert("name", &user.first_name);
+    ctx.insert("link", &link);
 
-    pub async fn send_reset_password(
-        &self,
-        to_email: &str,
-        user_name: &str,
-        reset_link: &str,
-    ) -> Result<(), AppError> {
-        let mut ctx = Context::new();
-        ctx.insert("name", user_name);
-        ctx.insert("link", reset_link);
-
-        let body = self
-            .tera
-            .render("emails/reset_password.html", &ctx)
-            .map_err(|e| {
-                eprintln!("Erro ao renderizar e-mail: {}", e);
-                AppError::InternalError(Some("Erro ao montar e-mail".into()))
-            })?;
+    queue_in_tx(
+        EmailTemplate::ConfirmEmail,
+        user.id,
+        &user.email,
+        language,
+        ctx,
+        tx,
+    )
+    .await
+}
+
+pub async fn queue_reset_password_in_tx(
+    user: &User,
+    language: &str,
+    code: &str,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<Uuid, AppError> {
+    let settings = get_settings();
+    let link = format!(
+        "{}/reset-password?token={}",
+        settings.email.frontend_url.trim_end_matches('/'),
+        code
+    );
 
-        self.send_html_email(to_email, "🔐 Redefina sua senha", &body)
+    let mut ctx = Context::new();
+    ctx.insert("name", &user.first_name);
+    ctx.insert("link", &link);
+
+    queue_in_tx(
+        EmailTemplate::ResetPassword,
+        user.id,
+        &user.email,
+        language,
+        ctx,
+        tx,
+    )
+    .await
+}
+
+/// Envia ao novo endereço o link que conclui a troca de e-mail
+pub async fn queue_change_email_in_tx(
+    user: &User,
+    language: &str,
+    new_email: &str,
+    code: &str,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<Uuid, AppError> {
+    let settings = get_settings();
+    let link = format!(
+        "{}/api/v1/confirm-email-change/{}/",
+        settings.email.app_url.trim_end_matches('/'),
+        code
+    );
+
+    let mut ctx = Context::new();
+    ctx.insert("name", &user.first_name);
+    ctx.insert("link", &link);
+
+    queue_in_tx(
+        EmailTemplate::ChangeEmail,
+        user.id,
+        new_email,
+        language,
+        ctx,
+        tx,
+    )
+    .await
+}
+
+/// Avisa o endereço atual de que uma troca de e-mail foi pedida
+pub async fn queue_email_change_notice_in_tx(
+    user: &User,
+    language: &str,
+    new_email: &str,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<Uuid, AppError> {
+    let mut ctx = Context::new();
+    ctx.insert("name", &user.first_name);
+    ctx.insert("new_email", new_email);
+
+    queue_in_tx(
+        EmailTemplate::EmailChangeNotice,
+        user.id,
+        &user.email,
+        language,
+        ctx,
+        tx,
+    )
+    .await
+}
+
+/// Avisa o titular de que a senha da conta foi alterada
+pub async fn queue_password_changed_in_tx(
+    user: &User,
+    language: &str,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<Uuid, AppError> {
+    let settings = get_settings();
+    let link = format!(
+        "{}/forgot-password",
+        settings.email.frontend_url.trim_end_matches('/')
+    );
+
+    let mut ctx = Context::new();
+    ctx.insert("name", &user.first_name);
+    ctx.insert("link", &link);
+
+    queue_in_tx(
+        EmailTemplate::PasswordChanged,
+        user.id,
+        &user.email,
+        language,
+        ctx,
+        tx,
+    )
+    .await
+}
+
+/// Anuncia um curso a quem aceita novidades sobre cursos, enfileirando `batch_size`
+/// destinatários por transação. Retorna quantos e-mails foram enfileirados.
+pub async fn queue_course_announcement(
+    course: &Course,
+    batch_size: i64,
+    db: &PgPool,
+) -> Result<usize, AppError> {
+    let settings = get_settings();
+    let link = format!(
+        "{}/courses/{}",
+        settings.email.frontend_url.trim_end_matches('/'),
+        course.id
+    );
+
+    let mut queued = 0;
+    let mut after = None;
+    loop {
+        let recipients = profile_repository::list_email_recipients_page(
+            email_categories::COURSE_ANNOUNCEMENTS,
+            after,
+            batch_size,
+            db,
+        )
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+        let Some(last) = recipients.last() else {
+            break;
+        };
+        after = Some(last.user_id);
+
+        let mut tx = db
+            .begin()
             .await
-    }
+            .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+        for recipient in &recipients {
+            let mut ctx = Context::new();
+            ctx.insert("name", &recipient.first_name);
+            ctx.insert("course_name", &course.name);
+            ctx.insert("link", &link);
 
-    async fn send_html_email(
-        &self,
-        to_email: &str,
-        subject: &str,
-        body_html: &str,
-    ) -> Result<(), AppError> {
-        let destination = aws_sdk_sesv2::types::Destination::builder()
-            .to_addresses(to_email)
-            .build();
-
-        let content = aws_sdk_sesv2::types::EmailContent::builder()
-            .simple(
-                aws_sdk_sesv2::types::Message::builder()
-                    .subject(
-                        aws_sdk_sesv2::types::Content::builder()
-                            .data(subject)
-                            .charset("UTF-8")
-                            .build()
-                            .map_err(|e| {
-                                eprintln!("Erro ao construir subject Content: {:?}", e);
-                                AppError::InternalError(Some("Falha ao preparar o email".into()))
-                            })?,
-                    )
-                    .body(
-                        aws_sdk_sesv2::types::Body::builder()
-                            .html(
-                                aws_sdk_sesv2::types::Content::builder()
-                                    .data(body_html)
-                                    .charset("UTF-8")
-                                    .build()
-                                    .map_err(|e| {
-                                        eprintln!("Erro ao construir subject Content: {:?}", e);
-                                        AppError::InternalError(Some(
-                                            "Falha ao preparar o email".into(),
-                                        ))
-                                    })?,
-                            )
-                            .build(),
-                    )
-                    .build(),
+            queue_in_tx(
+                EmailTemplate::CourseAnnouncement,
+                recipient.user_id,
+                &recipient.email,
+                &recipient.preferred_language,
+                ctx,
+                &mut tx,
             )
-            .build();
-
-        self.ses_client
-            .send_email()
-            .from_email_address(&self.sender_email)
-            .destination(destination)
-            .content(content)
-            .send()
+            .await?;
+        }
+        tx.commit()
             .await
-            .map_err(|err| {
-                eprintln!("Erro ao enviar e-mail: {:?}", err);
-                AppError::InternalError(Some("Erro ao enviar e-mail".into()))
-            })?;
+            .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
 
-        Ok(())
+        queued += recipients.len();
+        info!(course_id = %course.id, queued, "Lote do anúncio de curso enfileirado");
+
+        if (recipients.len() as i64) < batch_size {
+            break;
+        }
     }
+
+    Ok(queued)
 }
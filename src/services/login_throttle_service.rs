@@ -0,0 +1,136 @@
+use crate::config::app_state::AppState;
+use crate::config::get_settings;
+use crate::errors::app_error::AppError;
+use actix_web::web;
+use tracing::{error, warn};
+
+fn email_key(email: &str) -> String {
+    format!("login:email:{}", email.trim().to_lowercase())
+}
+
+fn ip_key(ip: &str) -> String {
+    format!("login:ip:{}", ip)
+}
+
+/// Bloqueio com backoff exponencial: dobra a cada falha acima do limite, até o teto configurado
+fn lockout_duration(failures: u32, max_attempts: u32) -> u64 {
+    let settings = &get_settings().login_throttle;
+    let exponent = failures.saturating_sub(max_attempts).min(20);
+
+    settings
+        .lockout_seconds
+        .saturating_mul(1u64 << exponent)
+        .min(settings.max_lockout_seconds)
+}
+
+/// Rejeita a tentativa se o IP (429) ou a conta (423) estiverem bloqueados.
+/// Falhas do backend de contadores não impedem o login.
+pub async fn ensure_login_allowed(
+    email: &str,
+    ip: Option<&str>,
+    state: &web::Data<AppState>,
+) -> Result<(), AppError> {
+    let store = &state.login_attempts;
+
+    if let Some(ip) = ip {
+        match store.lock_remaining(&ip_key(ip)).await {
+            Ok(Some(retry_after)) => {
+                warn!(ip = %ip, "Login bloqueado para o IP");
+                return Err(AppError::too_many_requests(
+                    "Muitas tentativas de login. Tente novamente mais tarde",
+                    retry_after,
+                ));
+            }
+            Ok(None) => {}
+            Err(err) => error!(error = %err, "Erro ao consultar bloqueio de login por IP"),
+        }
+    }
+
+    match store.lock_remaining(&email_key(email)).await {
+        Ok(Some(retry_after)) => {
+            warn!(email = %email, "Login bloqueado para a conta");
+            Err(AppError::locked(
+                "Conta temporariamente bloqueada por excesso de tentativas",
+                retry_after,
+            ))
+        }
+        Ok(None) => Ok(()),
+        Err(err) => {
+            error!(error = %err, "Erro ao consultar bloqueio de login por conta");
+            Ok(())
+        }
+    }
+}
+
+/// Contabiliza uma falha para a conta e para o IP. Retorna o erro de bloqueio
+/// quando esta falha atingir o limite; caso contrário, `Ok(())`.
+pub async fn register_failed_login(
+    email: &str,
+    ip: Option<&str>,
+    state: &web::Data<AppState>,
+) -> Result<(), AppError> {
+    let settings = &get_settings().login_throttle;
+    let store = &state.login_attempts;
+
+    let mut lock_error = None;
+
+    if let Some(ip) = ip {
+        let key = ip_key(ip);
+        match store.register_failure(&key, settings.window_seconds).await {
+            Ok(failures) if failures >= settings.max_attempts_per_ip => {
+                let duration = lockout_duration(failures, settings.max_attempts_per_ip);
+                if let Err(err) = store.lock(&key, duration).await {
+                    error!(error = %err, "Erro ao bloquear IP");
+                }
+                warn!(ip = %ip, failures, duration, "IP bloqueado por excesso de falhas de login");
+                lock_error = Some(AppError::too_many_requests(
+                    "Muitas tentativas de login. Tente novamente mais tarde",
+                    duration,
+                ));
+            }
+            Ok(_) => {}
+            Err(err) => error!(error = %err, "Erro ao registrar falha de login por IP"),
+        }
+    }
+
+    let key = email_key(email);
+    match store.register_failure(&key, settings.window_seconds).await {
+        Ok(failures) if failures >= settings.max_attempts_per_email => {
+            let duration = lockout_duration(failures, settings.max_attempts_per_email);
+            if let Err(err) = store.lock(&key, duration).await {
+                error!(error = %err, "Erro ao bloquear conta");
+            }
+            warn!(email = %email, failures, duration, "Conta bloqueada por excesso de falhas de login");
+            lock_error.get_or_insert(AppError::locked(
+                "Conta temporariamente bloqueada por excesso de tentativas",
+                duration,
+            ));
+        }
+        Ok(_) => {}
+        Err(err) => error!(error = %err, "Erro ao registrar falha de login por conta"),
+    }
+
+    match lock_error {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Login bem-sucedido zera o contador da conta (o do IP expira com a janela)
+pub async fn register_successful_login(email: &str, state: &web::Data<AppState>) {
+    if let Err(err) = state.login_attempts.reset(&email_key(email)).await {
+        error!(error = %err, "Erro ao limpar falhas de login");
+    }
+}
+
+/// Remove o bloqueio e o contador de falhas da conta
+pub async fn unlock_account(email: &str, state: &web::Data<AppState>) -> Result<(), AppError> {
+    state
+        .login_attempts
+        .reset(&email_key(email))
+        .await
+        .map_err(|err| {
+            error!(error = %err, "Erro ao desbloquear conta");
+            AppError::InternalError(Some("Erro ao desbloquear conta".into()))
+        })
+}
@@ -0,0 +1,315 @@
+use crate::config::app_state::AppState;
+use crate::config::get_settings;
+use crate::errors::app_error::AppError;
+use crate::log_fail;
+use crate::logs::model::LogLevel;
+use crate::models::mfa::{
+    MfaChallengeResponse, MfaLoginRequest, RecoveryCodesResponse, TotpCodeRequest,
+    TotpEnrollmentResponse, UserTotp,
+};
+use crate::models::session::SessionInfo;
+use crate::models::user::{UserResponse, UserWithProfile};
+use crate::repositories::{mfa_repository, profile_repository, user_repository};
+use crate::services::{auth_service, login_throttle_service};
+use crate::utils::crypto::{generate_random_code, sha256_hex};
+use crate::utils::jwt::{decode_mfa_challenge, generate_mfa_challenge};
+use crate::utils::totp;
+use actix_web::web;
+use chrono::DateTime;
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+use validator::Validate;
+
+const RECOVERY_CODES_COUNT: usize = 10;
+const RECOVERY_CODE_LENGTH: usize = 10;
+/// Códigos errados aceitos por desafio; depois disso é preciso repetir a senha
+const MFA_CHALLENGE_MAX_ATTEMPTS: i32 = 3;
+
+/// Inicia o cadastro do TOTP: gera um segredo pendente e devolve a URI `otpauth://`.
+/// O segundo fator só passa a valer após `confirm_totp_enrollment`.
+pub async fn enroll_totp(
+    user_id: Uuid,
+    state: &web::Data<AppState>,
+) -> Result<TotpEnrollmentResponse, AppError> {
+    let db = &state.db;
+    let user = user_repository::find_user_by_id(user_id, db).await?;
+
+    let secret = totp::generate_secret();
+    let saved = mfa_repository::upsert_pending_totp(user_id, &secret, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    if !saved {
+        return Err(AppError::BadRequest(Some(
+            "A autenticação em dois fatores já está ativa".into(),
+        )));
+    }
+
+    let otpauth_uri = totp::otpauth_uri(&secret, &user.email).map_err(|e| {
+        warn!(error = %e, "Erro ao gerar URI otpauth");
+        AppError::InternalError(Some("Erro ao gerar URI otpauth".into()))
+    })?;
+
+    Ok(TotpEnrollmentResponse {
+        secret,
+        otpauth_uri,
+    })
+}
+
+/// Confirma o cadastro com um código válido, ativa o TOTP e gera os códigos de recuperação
+pub async fn confirm_totp_enrollment(
+    user_id: Uuid,
+    payload: TotpCodeRequest,
+    state: &web::Data<AppState>,
+) -> Result<RecoveryCodesResponse, AppError> {
+    let db = &state.db;
+
+    if let Err(e) = payload.validate() {
+        return Err(AppError::BadRequest(Some(format!(
+            "Dados inválidos: {}",
+            e
+        ))));
+    }
+
+    let user_totp = find_totp(user_id, db)
+        .await?
+        .ok_or_else(|| AppError::BadRequest(Some("Nenhum cadastro de TOTP pendente".into())))?;
+
+    if user_totp.enabled {
+        return Err(AppError::BadRequest(Some(
+            "A autenticação em dois fatores já está ativa".into(),
+        )));
+    }
+
+    if !verify_totp_code(&user_totp, &payload.code, totp::now(), db).await? {
+        return Err(AppError::Unauthorized(Some("Código inválido".into())));
+    }
+
+    let recovery_codes: Vec<String> = (0..RECOVERY_CODES_COUNT)
+        .map(|_| format_recovery_code(&generate_random_code(RECOVERY_CODE_LENGTH)))
+        .collect();
+    let code_hashes: Vec<String> = recovery_codes
+        .iter()
+        .map(|code| sha256_hex(&normalize_recovery_code(code)))
+        .collect();
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    mfa_repository::enable_totp_in_tx(user_id, &mut tx).await?;
+    mfa_repository::replace_recovery_codes_in_tx(user_id, &code_hashes, &mut tx).await?;
+    tx.commit()
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    info!(user_id = %user_id, "Autenticação em dois fatores ativada");
+
+    Ok(RecoveryCodesResponse { recovery_codes })
+}
+
+/// Desativa o TOTP mediante um código válido (TOTP ou de recuperação)
+pub async fn disable_totp(
+    user_id: Uuid,
+    payload: TotpCodeRequest,
+    state: &web::Data<AppState>,
+) -> Result<(), AppError> {
+    let db = &state.db;
+
+    if let Err(e) = payload.validate() {
+        return Err(AppError::BadRequest(Some(format!(
+            "Dados inválidos: {}",
+            e
+        ))));
+    }
+
+    let user_totp = match find_totp(user_id, db).await? {
+        Some(user_totp) if user_totp.enabled => user_totp,
+        _ => {
+            return Err(AppError::BadRequest(Some(
+                "A autenticação em dois fatores não está ativa".into(),
+            )));
+        }
+    };
+
+    if !verify_second_factor(&user_totp, &payload.code, db).await? {
+        return Err(AppError::Unauthorized(Some("Código inválido".into())));
+    }
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    mfa_repository::delete_totp_in_tx(user_id, &mut tx).await?;
+    tx.commit()
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    info!(user_id = %user_id, "Autenticação em dois fatores desativada");
+
+    Ok(())
+}
+
+pub async fn is_totp_enabled(user_id: Uuid, db: &PgPool) -> Result<bool, AppError> {
+    Ok(find_totp(user_id, db)
+        .await?
+        .is_some_and(|user_totp| user_totp.enabled))
+}
+
+/// Emite o token de desafio que deve ser trocado, junto do código, em `complete_mfa_login`
+pub fn create_challenge(user_id: Uuid) -> Result<MfaChallengeResponse, AppError> {
+    let expires_in = get_settings().mfa.challenge_expires_in;
+    let mfa_token = generate_mfa_challenge(&user_id.to_string(), expires_in).map_err(|e| {
+        warn!(error = %e, "Erro ao gerar desafio de MFA");
+        AppError::InternalError(Some("Erro ao gerar desafio de MFA".into()))
+    })?;
+
+    Ok(MfaChallengeResponse {
+        mfa_required: true,
+        mfa_token,
+        expires_in: expires_in.to_string(),
+    })
+}
+
+/// Segunda etapa do login: valida o desafio e o código e abre a sessão
+pub async fn complete_mfa_login(
+    payload: MfaLoginRequest,
+    session_info: SessionInfo,
+    state: &web::Data<AppState>,
+) -> Result<UserResponse, AppError> {
+    let db = &state.db;
+    let mongo_db = &state.mongo;
+
+    if let Err(e) = payload.validate() {
+        return Err(AppError::BadRequest(Some(format!(
+            "Dados inválidos: {}",
+            e
+        ))));
+    }
+
+    let invalid_challenge =
+        || AppError::Unauthorized(Some("Desafio de MFA inválido ou expirado".into()));
+
+    let claims = decode_mfa_challenge(&payload.mfa_token).map_err(|_| invalid_challenge())?;
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| invalid_challenge())?;
+    let expires_at = DateTime::from_timestamp(claims.exp as i64, 0)
+        .ok_or_else(invalid_challenge)?
+        .naive_utc();
+
+    let user_totp = match find_totp(user_id, db).await? {
+        Some(user_totp) if user_totp.enabled => user_totp,
+        _ => return Err(invalid_challenge()),
+    };
+
+    let user = user_repository::find_user_by_id(user_id, db).await?;
+    let client_ip = session_info.ip_address.as_deref();
+    login_throttle_service::ensure_login_allowed(&user.email, client_ip, state).await?;
+
+    // cada desafio abre uma única sessão e aceita poucas tentativas
+    let closed = mfa_repository::is_challenge_closed(&claims.jti, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    if closed {
+        return Err(invalid_challenge());
+    }
+
+    if !verify_second_factor(&user_totp, &payload.code, db).await? {
+        warn!(user_id = %user_id, "Código de segundo fator inválido");
+        log_fail!(
+            "Código de segundo fator inválido",
+            LogLevel::Warn,
+            "Falha na segunda etapa do login",
+            "mfa_service",
+            Some(user_id),
+            mongo_db
+        );
+        mfa_repository::register_challenge_failure(
+            &claims.jti,
+            user_id,
+            expires_at,
+            MFA_CHALLENGE_MAX_ATTEMPTS,
+            db,
+        )
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+        login_throttle_service::register_failed_login(&user.email, client_ip, state).await?;
+        return Err(AppError::Unauthorized(Some("Código inválido".into())));
+    }
+
+    let consumed = mfa_repository::consume_challenge(&claims.jti, user_id, expires_at, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    if !consumed {
+        return Err(invalid_challenge());
+    }
+
+    login_throttle_service::register_successful_login(&user.email, state).await;
+
+    let profile = profile_repository::find_profile_by_user_id(user_id, db).await?;
+
+    let token_pair = auth_service::start_session(user_id, &session_info, db).await?;
+
+    info!(user_id = %user_id, "Login com segundo fator realizado com sucesso");
+
+    Ok(UserResponse::from_token_pair(
+        UserWithProfile::from_user_and_profile(user, profile),
+        token_pair,
+    ))
+}
+
+async fn find_totp(user_id: Uuid, db: &PgPool) -> Result<Option<UserTotp>, AppError> {
+    mfa_repository::find_totp_by_user(user_id, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))
+}
+
+/// Aceita um código TOTP de 6 dígitos ou, na falta dele, um código de recuperação
+async fn verify_second_factor(
+    user_totp: &UserTotp,
+    code: &str,
+    db: &PgPool,
+) -> Result<bool, AppError> {
+    let code = code.trim();
+
+    if code.len() == 6 && code.chars().all(|c| c.is_ascii_digit()) {
+        return verify_totp_code(user_totp, code, totp::now(), db).await;
+    }
+
+    mfa_repository::consume_recovery_code(
+        user_totp.user_id,
+        &sha256_hex(&normalize_recovery_code(code)),
+        db,
+    )
+    .await
+    .map_err(|e| AppError::DatabaseError(Some(e.to_string())))
+}
+
+/// Valida o código no instante `now` e registra a janela usada para impedir replay
+async fn verify_totp_code(
+    user_totp: &UserTotp,
+    code: &str,
+    now: u64,
+    db: &PgPool,
+) -> Result<bool, AppError> {
+    let Some(step) = totp::verify_code(&user_totp.secret, code.trim(), now) else {
+        return Ok(false);
+    };
+
+    mfa_repository::mark_step_used(user_totp.user_id, step as i64, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))
+}
+
+fn format_recovery_code(raw: &str) -> String {
+    let raw = raw.to_uppercase();
+    let (first, second) = raw.split_at(raw.len() / 2);
+    format!("{}-{}", first, second)
+}
+
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
@@ -1,8 +1,24 @@
+pub mod account_deletion_service;
+pub mod account_policy_service;
+pub mod admin_user_service;
+pub mod api_key_service;
+pub mod auth_service;
+pub mod avatar_service;
 pub mod category_service;
 pub mod course_service;
+pub mod email_outbox_service;
+pub mod email_preferences_service;
 pub mod email_service;
+pub mod login_throttle_service;
+pub mod mfa_service;
 pub mod notification_service;
+pub mod oidc_service;
+pub mod password_service;
+pub mod personal_data_service;
+pub mod phone_verification_service;
 pub mod profile_service;
+pub mod role_service;
 pub mod token_service;
 pub mod user_private_service;
 pub mod user_public_service;
+pub mod username_service;
@@ -0,0 +1,292 @@
+use crate::config::OidcProviderSettings;
+use crate::config::app_state::AppState;
+use crate::config::get_settings;
+use crate::errors::app_error::AppError;
+use crate::log_fail;
+use crate::logs::model::LogLevel;
+use crate::models::identity::{OidcCallbackQuery, OidcTokenResponse, OidcUserInfo};
+use crate::models::mfa::LoginResponse;
+use crate::models::profile::Profile;
+use crate::models::role::DEFAULT_ROLE;
+use crate::models::session::SessionInfo;
+use crate::models::user::{User, UserResponse, UserWithProfile};
+use crate::repositories::{identity_repository, profile_repository, user_repository};
+use crate::services::{
+    account_policy_service, auth_service, mfa_service, user_public_service, username_service,
+};
+use crate::utils::crypto::{generate_random_code, sha256_hex};
+use crate::utils::password;
+use actix_web::web;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{Duration, Utc};
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+
+const USER_AGENT: &str = "rust-usecases";
+
+/// Inicia o fluxo authorization code + PKCE e devolve a URL de autorização do provedor
+pub async fn start_authorization(
+    provider: &OidcProviderSettings,
+    db: &PgPool,
+) -> Result<String, AppError> {
+    let state = generate_random_code(32);
+    let code_verifier = generate_random_code(64);
+    let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+
+    let expires_at =
+        (Utc::now() + Duration::seconds(get_settings().oidc.state_expires_in as i64)).naive_utc();
+
+    identity_repository::create_auth_state(
+        &sha256_hex(&state),
+        &provider.name,
+        &code_verifier,
+        expires_at,
+        db,
+    )
+    .await?;
+
+    let url = Url::parse_with_params(
+        &provider.authorization_url,
+        &[
+            ("response_type", "code"),
+            ("client_id", provider.client_id.as_str()),
+            ("redirect_uri", provider.redirect_uri.as_str()),
+            ("scope", provider.scopes.as_str()),
+            ("state", state.as_str()),
+            ("code_challenge", code_challenge.as_str()),
+            ("code_challenge_method", "S256"),
+        ],
+    )
+    .map_err(|e| {
+        error!(error = %e, provider = %provider.name, "URL de autorização inválida");
+        AppError::InternalError(Some("URL de autorização do provedor inválida".into()))
+    })?;
+
+    Ok(url.to_string())
+}
+
+/// Conclui o fluxo: valida o `state`, troca o código pelo access token, consulta o
+/// userinfo e autentica a conta vinculada (criando ou vinculando pelo e-mail verificado)
+pub async fn complete_authorization(
+    provider: &OidcProviderSettings,
+    query: OidcCallbackQuery,
+    session_info: SessionInfo,
+    state: &web::Data<AppState>,
+) -> Result<LoginResponse, AppError> {
+    let db = &state.db;
+
+    if let Some(err) = query.error {
+        warn!(provider = %provider.name, error = %err, "Provedor recusou a autorização");
+        return Err(AppError::Unauthorized(Some(
+            "Autorização negada pelo provedor".into(),
+        )));
+    }
+
+    let (Some(code), Some(oauth_state)) = (query.code, query.state) else {
+        return Err(AppError::BadRequest(Some(
+            "Parâmetros code e state são obrigatórios".into(),
+        )));
+    };
+
+    let code_verifier =
+        identity_repository::consume_auth_state(&sha256_hex(&oauth_state), &provider.name, db)
+            .await
+            .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?
+            .ok_or_else(|| AppError::Unauthorized(Some("State inválido ou expirado".into())))?;
+
+    let access_token = exchange_code(provider, &code, &code_verifier).await?;
+    let user_info = fetch_user_info(provider, &access_token).await?;
+
+    let user = resolve_user(provider, &user_info, state).await?;
+    account_policy_service::ensure_not_suspended(user.id, db).await?;
+
+    if mfa_service::is_totp_enabled(user.id, db).await? {
+        info!(user_id = %user.id, "Login social aguardando segundo fator");
+        return Ok(LoginResponse::MfaRequired(mfa_service::create_challenge(
+            user.id,
+        )?));
+    }
+
+    let profile = profile_repository::find_profile_by_user_id(user.id, db).await?;
+    let token_pair = auth_service::start_session(user.id, &session_info, db).await?;
+
+    info!(
+        user_id = %user.id,
+        provider = %provider.name,
+        "Login social realizado com sucesso"
+    );
+
+    Ok(LoginResponse::Authenticated(Box::new(
+        UserResponse::from_token_pair(
+            UserWithProfile::from_user_and_profile(user, profile),
+            token_pair,
+        ),
+    )))
+}
+
+async fn exchange_code(
+    provider: &OidcProviderSettings,
+    code: &str,
+    code_verifier: &str,
+) -> Result<String, AppError> {
+    let response = reqwest::Client::new()
+        .post(&provider.token_url)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", provider.redirect_uri.as_str()),
+            ("client_id", provider.client_id.as_str()),
+            ("client_secret", provider.client_secret.as_str()),
+            ("code_verifier", code_verifier),
+        ])
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| {
+            error!(error = %e, provider = %provider.name, "Erro ao trocar o código de autorização");
+            AppError::Unauthorized(Some("Falha na autenticação com o provedor".into()))
+        })?;
+
+    let token = response.json::<OidcTokenResponse>().await.map_err(|e| {
+        error!(error = %e, provider = %provider.name, "Resposta de token inválida");
+        AppError::Unauthorized(Some("Falha na autenticação com o provedor".into()))
+    })?;
+
+    Ok(token.access_token)
+}
+
+async fn fetch_user_info(
+    provider: &OidcProviderSettings,
+    access_token: &str,
+) -> Result<OidcUserInfo, AppError> {
+    let value = reqwest::Client::new()
+        .get(&provider.userinfo_url)
+        .bearer_auth(access_token)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| {
+            error!(error = %e, provider = %provider.name, "Erro ao consultar userinfo");
+            AppError::Unauthorized(Some("Falha na autenticação com o provedor".into()))
+        })?
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| {
+            error!(error = %e, provider = %provider.name, "Resposta de userinfo inválida");
+            AppError::Unauthorized(Some("Falha na autenticação com o provedor".into()))
+        })?;
+
+    OidcUserInfo::from_json(&value)
+        .ok_or_else(|| AppError::Unauthorized(Some("Provedor não informou o usuário".into())))
+}
+
+/// Identidade já vinculada → conta vinculada; e-mail verificado de conta existente →
+/// vincula; e-mail livre → cria a conta. E-mail não verificado nunca vincula contas.
+async fn resolve_user(
+    provider: &OidcProviderSettings,
+    user_info: &OidcUserInfo,
+    state: &web::Data<AppState>,
+) -> Result<User, AppError> {
+    let db = &state.db;
+    let mongo_db = &state.mongo;
+
+    let identity = identity_repository::find_identity(&provider.name, &user_info.subject, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    if let Some(identity) = identity {
+        if let Err(err) = identity_repository::touch_identity(identity.id, db).await {
+            warn!(error = %err, "Erro ao atualizar último login da identidade");
+        }
+        return user_repository::find_user_by_id(identity.user_id, db)
+            .await
+            .map_err(|_| AppError::Unauthorized(Some("Conta vinculada indisponível".into())));
+    }
+
+    let Some(email) = user_info.email.as_deref() else {
+        return Err(AppError::BadRequest(Some(
+            "O provedor não informou o e-mail".into(),
+        )));
+    };
+
+    let user = match user_repository::find_user_by_email(email, db).await? {
+        Some(user) if user_info.email_verified => {
+            info!(
+                user_id = %user.id,
+                provider = %provider.name,
+                "Identidade vinculada pelo e-mail verificado"
+            );
+            user
+        }
+        Some(user) => {
+            log_fail!(
+                format!("E-mail não verificado pelo provedor {}", provider.name),
+                LogLevel::Warn,
+                "Vínculo de identidade recusado",
+                "oidc_service",
+                Some(user.id),
+                mongo_db
+            );
+            return Err(AppError::Conflict(Some(
+                "Já existe uma conta com este e-mail. Entre com e-mail e senha".into(),
+            )));
+        }
+        None => create_user_from_identity(email, user_info, state).await?,
+    };
+
+    identity_repository::create_identity(
+        user.id,
+        &provider.name,
+        &user_info.subject,
+        Some(email),
+        db,
+    )
+    .await?;
+
+    Ok(user)
+}
+
+async fn create_user_from_identity(
+    email: &str,
+    user_info: &OidcUserInfo,
+    state: &web::Data<AppState>,
+) -> Result<User, AppError> {
+    let username = username_service::generate_unique_username(email, None, &state.db).await?;
+    let first_name = user_info.first_name.as_deref().unwrap_or(&username);
+    let last_name = user_info.last_name.as_deref().unwrap_or("");
+
+    // A conta só é acessada pelo provedor até que o usuário redefina a senha
+    let mut user = User::new(
+        &username,
+        email,
+        first_name,
+        last_name,
+        password::hash_password(&generate_random_code(32)).await?,
+    );
+
+    let mut profile = Profile::from_request(user.id, None);
+    profile.confirm_email = user_info.email_verified;
+
+    user_public_service::create_user_and_profile(
+        &mut user,
+        &profile,
+        &[DEFAULT_ROLE.to_string()],
+        state,
+    )
+    .await
+    .map_err(|e| {
+        error!(error = ?e, "Erro ao criar usuário a partir da identidade");
+        AppError::BadRequest(Some("Erro ao criar o usuario".into()))
+    })?;
+
+    info!(user_id = %user.id, "Usuário criado via login social");
+
+    Ok(user)
+}
@@ -0,0 +1,107 @@
+use crate::config::get_settings;
+use crate::errors::app_error::AppError;
+use crate::models::user::User;
+use crate::repositories::{password_history_repository, user_repository};
+use crate::utils::password::{hash_password, verify_password};
+use crate::utils::password_policy::password_violations;
+use sqlx::{PgPool, Postgres, Transaction};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Rejeita senhas fora da política configurada
+pub fn ensure_policy(password: &str) -> Result<(), AppError> {
+    let violations = password_violations(password, &get_settings().password);
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    Err(AppError::BadRequest(Some(format!(
+        "Senha inválida: {}",
+        violations.join("; ")
+    ))))
+}
+
+/// Registra o hash de uma senha definida, mantendo só as últimas `PASSWORD_HISTORY_SIZE`
+pub async fn record_password_in_tx(
+    user_id: Uuid,
+    password_hash: &str,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), AppError> {
+    let history_size = get_settings().password.history_size;
+    if history_size == 0 {
+        return Ok(());
+    }
+
+    password_history_repository::insert_in_tx(user_id, password_hash, tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    password_history_repository::trim_in_tx(user_id, history_size, tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    Ok(())
+}
+
+async fn ensure_not_reused(
+    user: &User,
+    new_password: &str,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), AppError> {
+    let history_size = get_settings().password.history_size;
+    if history_size == 0 {
+        return Ok(());
+    }
+
+    let mut hashes = password_history_repository::recent_hashes_in_tx(user.id, history_size, tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    // contas criadas antes do histórico só têm a senha atual
+    if !hashes.contains(&user.password) {
+        hashes.push(user.password.clone());
+    }
+
+    for hash in &hashes {
+        if verify_password(new_password, hash).await.is_valid() {
+            return Err(AppError::BadRequest(Some(format!(
+                "A nova senha não pode ser igual às últimas {} senhas",
+                history_size
+            ))));
+        }
+    }
+
+    Ok(())
+}
+
+/// Troca a senha aplicando a política e o histórico, na transação informada
+pub async fn change_password_in_tx(
+    user: &User,
+    new_password: &str,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), AppError> {
+    ensure_policy(new_password)?;
+    ensure_not_reused(user, new_password, tx).await?;
+
+    let password_hash = hash_password(new_password).await?;
+    user_repository::update_user_password_in_tx(user.id, &password_hash, tx).await?;
+    record_password_in_tx(user.id, &password_hash, tx).await?;
+
+    info!(user_id = %user.id, "Senha alterada");
+    Ok(())
+}
+
+/// Regrava com o Argon2id atual o hash de uma senha que acabou de ser verificada.
+/// Falhas só são registradas: o login não depende disso.
+pub async fn rehash_password(user_id: Uuid, password: &str, db: &PgPool) {
+    let password_hash = match hash_password(password).await {
+        Ok(hash) => hash,
+        Err(err) => {
+            warn!(user_id = %user_id, error = %err, "Erro ao regravar hash da senha");
+            return;
+        }
+    };
+
+    match user_repository::update_password_hash(user_id, &password_hash, db).await {
+        Ok(()) => info!(user_id = %user_id, "Hash da senha atualizado para o Argon2id"),
+        Err(err) => warn!(user_id = %user_id, error = %err, "Erro ao regravar hash da senha"),
+    }
+}
@@ -0,0 +1,39 @@
+use crate::config::app_state::AppState;
+use crate::errors::app_error::AppError;
+use crate::logs::repository as log_repository;
+use crate::models::user::PersonalDataExport;
+use crate::repositories::{notification_repository, profile_repository, user_repository};
+use actix_web::web;
+use chrono::Utc;
+use tracing::warn;
+use uuid::Uuid;
+
+/// Reúne os dados pessoais do titular: conta, perfil, notificações e logs
+pub async fn export_personal_data(
+    user_id: Uuid,
+    state: &web::Data<AppState>,
+) -> Result<PersonalDataExport, AppError> {
+    let db = &state.db;
+
+    let user = user_repository::find_user_by_id(user_id, db).await?;
+    let profile = profile_repository::find_profile_by_user_id(user_id, db).await?;
+    let notifications = notification_repository::list_user_notifications(user_id, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao listar notificações: {e}"))))?;
+
+    let logs = match log_repository::find_logs_by_user(&state.mongo, user_id).await {
+        Ok(logs) => Some(logs),
+        Err(err) => {
+            warn!(user_id = %user_id, error = %err, "Logs fora da exportação de dados");
+            None
+        }
+    };
+
+    Ok(PersonalDataExport {
+        exported_at: Utc::now().naive_utc(),
+        user,
+        profile,
+        notifications,
+        logs,
+    })
+}
@@ -0,0 +1,141 @@
+use crate::config::app_state::AppState;
+use crate::config::get_settings;
+use crate::errors::app_error::AppError;
+use crate::models::phone_verification::{CODE_LENGTH, ConfirmPhoneRequest, PhoneVerificationSent};
+use crate::models::profile::languages;
+use crate::repositories::{phone_verification_repository, profile_repository};
+use crate::sms::SmsMessage;
+use crate::utils::crypto::{generate_numeric_code, sha256_hex};
+use crate::utils::phone::normalize_phone;
+use actix_web::web;
+use chrono::{Duration, Utc};
+use tracing::{info, warn};
+use uuid::Uuid;
+use validator::Validate;
+
+fn sms_body(language: &str, code: &str, minutes: i64) -> String {
+    match language {
+        languages::EN => {
+            format!("Your verification code is {code}. It expires in {minutes} minutes.")
+        }
+        _ => format!("Seu código de verificação é {code}. Ele expira em {minutes} minutos."),
+    }
+}
+
+/// Envia por SMS um código para confirmar o telefone do perfil. Reenvios para o mesmo número
+/// respeitam `PHONE_VERIFICATION_RESEND_SECONDS`.
+pub async fn send_verification_code(
+    user_id: Uuid,
+    state: &web::Data<AppState>,
+) -> Result<PhoneVerificationSent, AppError> {
+    let db = &state.db;
+    let settings = &get_settings().phone;
+    let profile = profile_repository::find_profile_by_user_id(user_id, db).await?;
+
+    // telefones gravados antes da normalização podem não estar em E.164
+    let phone = profile
+        .phone
+        .clone()
+        .filter(|p| normalize_phone(p, &settings.default_region).as_deref() == Some(p.as_str()))
+        .ok_or_else(|| {
+            AppError::BadRequest(Some(
+                "Cadastre um telefone válido no perfil antes de verificá-lo".into(),
+            ))
+        })?;
+
+    if profile.phone_verified {
+        return Err(AppError::Conflict(Some("Telefone já verificado".into())));
+    }
+
+    let previous = phone_verification_repository::find_verification(user_id, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    if let Some(previous) = previous.filter(|p| p.phone == phone) {
+        let resend_at =
+            previous.dt_created + Duration::seconds(settings.verification_resend_seconds);
+        let wait = (resend_at - Utc::now().naive_utc()).num_seconds();
+        if wait > 0 {
+            return Err(AppError::too_many_requests(
+                format!("Aguarde {} segundos para pedir um novo código", wait),
+                wait as u64,
+            ));
+        }
+    }
+
+    let minutes = settings.verification_expires_minutes;
+    let code = generate_numeric_code(CODE_LENGTH);
+    let dt_expires = Utc::now().naive_utc() + Duration::minutes(minutes);
+    phone_verification_repository::upsert_verification(
+        user_id,
+        &phone,
+        &sha256_hex(&code),
+        dt_expires,
+        db,
+    )
+    .await
+    .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    let message = SmsMessage {
+        to: phone.clone(),
+        body: sms_body(&profile.preferred_language, &code, minutes),
+    };
+    if let Err(err) = state.sms.send(&message).await {
+        warn!(error = %err, %user_id, "Erro ao enviar SMS de verificação");
+        // sem o SMS o código é inútil; libera um novo pedido imediato
+        let _ = phone_verification_repository::delete_verification(user_id, db).await;
+        return Err(AppError::InternalError(Some(
+            "Não foi possível enviar o SMS de verificação".into(),
+        )));
+    }
+    info!(%user_id, "Código de verificação de telefone enviado");
+
+    Ok(PhoneVerificationSent {
+        phone,
+        expires_in: minutes * 60,
+    })
+}
+
+/// Confere o código; após `PHONE_VERIFICATION_MAX_ATTEMPTS` erros ele deixa de valer
+pub async fn confirm_phone(
+    user_id: Uuid,
+    payload: ConfirmPhoneRequest,
+    state: &web::Data<AppState>,
+) -> Result<(), AppError> {
+    let db = &state.db;
+    let invalid = || AppError::BadRequest(Some("Código inválido ou expirado".into()));
+
+    if let Err(e) = payload.validate() {
+        return Err(AppError::BadRequest(Some(format!(
+            "Dados inválidos: {}",
+            e
+        ))));
+    }
+
+    let verification = phone_verification_repository::register_attempt(user_id, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?
+        .ok_or_else(invalid)?;
+
+    let max_attempts = get_settings().phone.verification_max_attempts;
+    if verification.attempts > max_attempts {
+        return Err(invalid());
+    }
+    if verification.code_hash != sha256_hex(payload.code.trim()) {
+        return Err(invalid());
+    }
+
+    // o telefone pode ter mudado depois do envio do código
+    let verified = profile_repository::set_phone_verified(user_id, &verification.phone, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    phone_verification_repository::delete_verification(user_id, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    if !verified {
+        return Err(invalid());
+    }
+    info!(%user_id, "Telefone verificado");
+
+    Ok(())
+}
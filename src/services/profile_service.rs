@@ -1,27 +1,138 @@
 // src/services/profile_service.rs
 use crate::config::app_state::AppState;
+use crate::config::get_settings;
 use crate::errors::app_error::AppError;
 use crate::models::profile::UpdateProfileRequest;
 use crate::models::user::{UserResponse, UserWithProfile};
 use crate::repositories::{profile_repository, user_repository};
+use crate::utils::document::{Document, DocumentType, parse_document};
+use crate::utils::phone::normalize_phone;
 use actix_web::web;
+use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
+use validator::Validate;
+
+/// Valida o CPF/CNPJ e o reduz aos dígitos. Com `unique` (`ACCOUNT_UNIQUE_DOCUMENT`), recusa
+/// documentos já usados por outra conta ativa; a garantia contra cadastros simultâneos fica com
+/// `ensure_document_available_in_tx`, na transação que grava o perfil.
+pub async fn prepare_document(
+    document: &str,
+    user_id: Uuid,
+    unique: bool,
+    db: &PgPool,
+) -> Result<Document, AppError> {
+    let document = parse_document(document).ok_or_else(|| {
+        AppError::BadRequest(Some(
+            "Documento inválido: informe um CPF ou CNPJ válido".into(),
+        ))
+    })?;
+
+    if unique {
+        let in_use = profile_repository::document_in_use(&document.number, Some(user_id), db)
+            .await
+            .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+        if in_use {
+            return Err(AppError::Conflict(Some(
+                "Documento já cadastrado em outra conta".into(),
+            )));
+        }
+    }
+
+    Ok(document)
+}
+
+/// Com `ACCOUNT_UNIQUE_DOCUMENT`, trava o documento na transação e recusa se outra conta ativa
+/// já o usa. Deve rodar na mesma transação que grava o perfil.
+pub async fn ensure_document_available_in_tx(
+    document: &str,
+    user_id: Uuid,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), AppError> {
+    if !get_settings().account.unique_document {
+        return Ok(());
+    }
+
+    let in_use = profile_repository::lock_document_in_tx(document, user_id, tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    if in_use {
+        return Err(AppError::Conflict(Some(
+            "Documento já cadastrado em outra conta".into(),
+        )));
+    }
+
+    Ok(())
+}
+
+/// Grava a atualização já normalizada por `normalize_profile_update`
+pub async fn save_profile_update(
+    user_id: Uuid,
+    payload: &UpdateProfileRequest,
+    document_type: Option<DocumentType>,
+    db: &PgPool,
+) -> Result<(), AppError> {
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    if let Some(document) = &payload.document {
+        ensure_document_available_in_tx(document, user_id, &mut tx).await?;
+    }
+    profile_repository::update_profile_fields_by_user_id_in_tx(
+        user_id,
+        payload,
+        document_type.map(|kind| kind.as_str()),
+        &mut tx,
+    )
+    .await
+    .map_err(|e| AppError::DatabaseError(Some(format!("Erro ao atualizar perfil: {e}"))))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))
+}
+
+/// Troca telefone e documento da atualização pelas formas normalizadas (E.164 e apenas
+/// dígitos) e devolve o tipo de documento a gravar
+pub async fn normalize_profile_update(
+    user_id: Uuid,
+    payload: &mut UpdateProfileRequest,
+    db: &PgPool,
+) -> Result<Option<DocumentType>, AppError> {
+    if let Some(phone) = &payload.phone {
+        let phone = normalize_phone(phone, &get_settings().phone.default_region)
+            .ok_or_else(|| AppError::BadRequest(Some("Número de telefone inválido".into())))?;
+        payload.phone = Some(phone);
+    }
+
+    let Some(document) = &payload.document else {
+        return Ok(None);
+    };
+    let unique = get_settings().account.unique_document;
+    let document = prepare_document(document, user_id, unique, db).await?;
+    payload.document = Some(document.number);
+    Ok(Some(document.kind))
+}
 
 pub async fn update_profile_by_user_id(
     user_id: Uuid,
-    data: UpdateProfileRequest,
+    mut data: UpdateProfileRequest,
     state: &web::Data<AppState>,
 ) -> Result<UserResponse, AppError> {
     let db = &state.db;
 
+    if let Err(e) = data.validate() {
+        return Err(AppError::BadRequest(Some(format!(
+            "Dados inválidos: {}",
+            e
+        ))));
+    }
+
     let user = user_repository::find_user_by_id(user_id, db).await?;
+    let document_type = normalize_profile_update(user.id, &mut data, db).await?;
 
-    profile_repository::update_profile_fields_by_user_id(user.id, &data, db)
-        .await
-        .map_err(|e| {
-            eprintln!("Erro ao atualizar profile: {:?}", e);
-            AppError::BadRequest(Some("Erro ao atualizar perfil".into()))
-        })?;
+    save_profile_update(user.id, &data, document_type, db).await?;
 
     let profile = profile_repository::find_profile_by_user_id(user_id, db).await?;
 
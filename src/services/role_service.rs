@@ -0,0 +1,86 @@
+use crate::config::app_state::AppState;
+use crate::errors::app_error::AppError;
+use crate::models::role::{RoleWithPermissions, UserRolesResponse};
+use crate::repositories::{role_repository, user_repository};
+use crate::services::auth_service;
+use actix_web::web;
+use uuid::Uuid;
+
+pub async fn list_roles_service(
+    state: &web::Data<AppState>,
+) -> Result<Vec<RoleWithPermissions>, AppError> {
+    let db = &state.db;
+
+    let roles = role_repository::list_roles(db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    let mut result = Vec::with_capacity(roles.len());
+    for role in roles {
+        let permissions = role_repository::find_permission_codes_by_role(role.id, db)
+            .await
+            .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+        result.push(RoleWithPermissions { role, permissions });
+    }
+
+    Ok(result)
+}
+
+/// As permissões viajam no access token, então toda mudança de papel encerra as
+/// sessões do usuário para que o novo conjunto valha imediatamente
+pub async fn assign_role_service(
+    user_id: Uuid,
+    role_name: &str,
+    state: &web::Data<AppState>,
+) -> Result<UserRolesResponse, AppError> {
+    let db = &state.db;
+
+    user_repository::find_user_by_id(user_id, db).await?;
+
+    let exists = role_repository::role_exists(role_name, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    if !exists {
+        return Err(AppError::NotFound(Some("Papel não encontrado".into())));
+    }
+
+    role_repository::assign_role(user_id, role_name, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    auth_service::revoke_all_sessions(user_id, db).await?;
+
+    user_roles_response(user_id, state).await
+}
+
+pub async fn remove_role_service(
+    user_id: Uuid,
+    role_name: &str,
+    state: &web::Data<AppState>,
+) -> Result<UserRolesResponse, AppError> {
+    let db = &state.db;
+
+    let affected = role_repository::remove_role(user_id, role_name, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    if affected == 0 {
+        return Err(AppError::NotFound(Some(
+            "Usuário não possui este papel".into(),
+        )));
+    }
+
+    auth_service::revoke_all_sessions(user_id, db).await?;
+
+    user_roles_response(user_id, state).await
+}
+
+async fn user_roles_response(
+    user_id: Uuid,
+    state: &web::Data<AppState>,
+) -> Result<UserRolesResponse, AppError> {
+    let roles = role_repository::find_role_names_by_user(user_id, &state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    Ok(UserRolesResponse { user_id, roles })
+}
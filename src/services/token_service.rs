@@ -1,38 +1,90 @@
+use crate::config::{UserTokenSettings, get_settings};
 use crate::errors::app_error::AppError;
-use crate::models::token::UserToken;
+use crate::models::token::{UserToken, token_types};
 use crate::repositories::token_repository;
-use chrono::{Duration, Utc};
-use rand::{Rng, distributions::Alphanumeric};
-use sqlx::PgPool;
+use crate::utils::crypto::{generate_random_code, sha256_hex};
+use chrono::{Duration, NaiveDateTime, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use tracing::{error, info};
 use uuid::Uuid;
 
-pub async fn create_user_token(
+/// Cria o token na mesma transação que enfileira o e-mail que o contém.
+/// Tokens anteriores do mesmo tipo deixam de valer.
+pub async fn create_user_token_in_tx(
     user_id: Uuid,
     token_type: &str,
-    db: &PgPool,
+    tx: &mut Transaction<'_, Postgres>,
 ) -> Result<String, AppError> {
-    let code: String = rand::thread_rng()
-        .sample_iter(&Alphanumeric)
-        .take(64)
-        .map(char::from)
-        .collect();
+    create_token_in_tx(user_id, token_type, None, tx).await
+}
+
+/// Token `change_email`: guarda o novo endereço até que ele seja confirmado
+pub async fn create_email_change_token_in_tx(
+    user_id: Uuid,
+    new_email: &str,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<String, AppError> {
+    create_token_in_tx(user_id, token_types::CHANGE_EMAIL, Some(new_email), tx).await
+}
 
-    let expires_at = Utc::now()
-        .checked_add_signed(Duration::minutes(180))
-        .expect("Erro ao calcular expiração")
-        .naive_utc();
+/// Retorna o código em claro, que só existe no e-mail; o banco guarda o hash
+async fn create_token_in_tx(
+    user_id: Uuid,
+    token_type: &str,
+    new_email: Option<&str>,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<String, AppError> {
+    let expires_at = expires_at(&get_settings().user_tokens, token_type)?;
+    token_repository::consume_pending_tokens_in_tx(user_id, token_type, tx).await?;
 
-    token_repository::create_token(db, user_id, &code, token_type, expires_at).await?;
+    let code = generate_random_code(64);
+    token_repository::create_token_in_tx(
+        user_id,
+        &sha256_hex(&code),
+        token_type,
+        expires_at,
+        new_email,
+        tx,
+    )
+    .await?;
 
     Ok(code)
 }
 
-pub async fn get_and_validate_token(
+fn expires_at(settings: &UserTokenSettings, token_type: &str) -> Result<NaiveDateTime, AppError> {
+    let minutes = match token_type {
+        token_types::CONFIRM_EMAIL => settings.confirm_email_expires_minutes,
+        token_types::CHANGE_PASSWORD => settings.change_password_expires_minutes,
+        token_types::CHANGE_EMAIL => settings.change_email_expires_minutes,
+        _ => {
+            return Err(AppError::InternalError(Some(format!(
+                "Tipo de token desconhecido: {}",
+                token_type
+            ))));
+        }
+    };
+
+    Ok((Utc::now() + Duration::minutes(minutes)).naive_utc())
+}
+
+/// Consome o código dentro da transação da ação que ele autoriza. Se a ação falhar, o
+/// rollback devolve o token.
+pub async fn consume_token_in_tx(
     code: &str,
     expected_type: &str,
-    db: &PgPool,
+    tx: &mut Transaction<'_, Postgres>,
 ) -> Result<UserToken, AppError> {
-    let token = token_repository::find_token_by_code(db, code)
+    let code_hash = sha256_hex(code);
+    let now = Utc::now().naive_utc();
+
+    if let Some(token) =
+        token_repository::consume_token_in_tx(&code_hash, expected_type, now, tx).await?
+    {
+        return Ok(token);
+    }
+
+    // não consumiu: descobre o motivo para responder com a mensagem certa
+    let token = token_repository::find_token_by_hash_in_tx(&code_hash, tx)
         .await?
         .ok_or_else(|| AppError::NotFound(Some("Token não encontrado".into())))?;
 
@@ -42,13 +94,39 @@ pub async fn get_and_validate_token(
         )));
     }
 
-    if token.expires_at < Utc::now().naive_utc() {
-        return Err(AppError::Unauthorized(Some("Token expirado".into())));
-    }
-
-    if token.consumed.unwrap_or(false) {
+    if token.consumed {
         return Err(AppError::Unauthorized(Some("Token já utilizado".into())));
     }
 
-    Ok(token)
+    Err(AppError::Unauthorized(Some("Token expirado".into())))
+}
+
+/// Apaga os tokens expirados e retorna quantos foram removidos
+pub async fn purge_expired_tokens(db: &PgPool) -> Result<u64, AppError> {
+    token_repository::delete_expired(Utc::now().naive_utc(), db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))
+}
+
+/// Inicia a limpeza periódica dos tokens expirados
+pub fn spawn_purge_job(db: PgPool, settings: UserTokenSettings) {
+    let interval = std::time::Duration::from_secs(settings.purge_interval_seconds);
+
+    tokio::spawn(async move {
+        info!(
+            purge_interval = settings.purge_interval_seconds,
+            "Limpeza de tokens expirados iniciada"
+        );
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+
+            match purge_expired_tokens(&db).await {
+                Ok(0) => {}
+                Ok(purged) => info!(purged, "Tokens expirados removidos"),
+                Err(err) => error!(error = %err, "Erro ao remover tokens expirados"),
+            }
+        }
+    });
 }
@@ -2,13 +2,20 @@ use crate::config::app_state::AppState;
 use crate::errors::app_error::AppError;
 use crate::log_fail;
 use crate::logs::model::LogLevel;
-use crate::models::user::{UpdateUserRequest, User, UserResponse, UserWithProfile};
+use crate::logs::service::log_event;
+use crate::models::user::{
+    ChangeEmailRequest, UpdatePasswordRequest, UpdateUserRequest, User, UserResponse,
+    UserWithProfile,
+};
 use crate::repositories::{profile_repository, user_repository};
+use crate::services::{auth_service, email_service, password_service, token_service};
 use crate::utils::jwt::calculate_remaining_expiration;
 use crate::utils::pagination::PaginatedResponse;
 use actix_web::web;
 use sqlx::Result;
+use tracing::info;
 use uuid::Uuid;
+use validator::Validate;
 
 pub async fn get_me_by_user_id(
     user_id: Uuid,
@@ -56,6 +63,7 @@ pub async fn get_me_by_user_id(
         user: user_with_profile,
         token,
         expires_in: remaining_seconds.to_string(),
+        refresh_token: None,
     })
 }
 
@@ -177,5 +185,142 @@ pub async fn delete_logged_user(
         return Err(AppError::NotFound(Some("Usuário não encontrado".into())));
     }
 
+    auth_service::revoke_all_sessions(user_id, db).await?;
+
+    Ok(())
+}
+
+/// Inicia a troca de e-mail: o novo endereço recebe o código de confirmação e o atual é
+/// avisado. O e-mail da conta só muda quando o código for confirmado.
+pub async fn request_email_change(
+    user_id: Uuid,
+    payload: ChangeEmailRequest,
+    state: &web::Data<AppState>,
+) -> Result<(), AppError> {
+    let db = &state.db;
+    let mongo_db = &state.mongo;
+
+    payload
+        .validate()
+        .map_err(|e| AppError::BadRequest(Some(format!("Dados inválidos: {}", e))))?;
+
+    let user = user_repository::find_user_by_id(user_id, db).await?;
+    if !user.verify_password(&payload.password).await.is_valid() {
+        return Err(AppError::Unauthorized(Some("❌ Senha incorreta".into())));
+    }
+
+    let new_email = payload.new_email;
+    if new_email == user.email {
+        return Err(AppError::BadRequest(Some(
+            "O novo e-mail deve ser diferente do atual".into(),
+        )));
+    }
+    if user_repository::find_user_by_email(&new_email, db)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::Conflict(Some("E-mail já está em uso".into())));
+    }
+
+    let profile = profile_repository::find_profile_by_user_id(user_id, db).await?;
+
+    let mut tx = db.begin().await.map_err(|err| {
+        AppError::DatabaseError(Some(format!("Erro ao iniciar transação: {}", err)))
+    })?;
+
+    let code = token_service::create_email_change_token_in_tx(user_id, &new_email, &mut tx).await?;
+    email_service::queue_change_email_in_tx(
+        &user,
+        &profile.preferred_language,
+        &new_email,
+        &code,
+        &mut tx,
+    )
+    .await?;
+    email_service::queue_email_change_notice_in_tx(
+        &user,
+        &profile.preferred_language,
+        &new_email,
+        &mut tx,
+    )
+    .await?;
+
+    if let Err(err) = tx.commit().await {
+        log_fail!(
+            err,
+            LogLevel::Error,
+            "Erro ao solicitar troca de e-mail",
+            "user_service",
+            Some(user_id),
+            mongo_db
+        );
+        return Err(AppError::DatabaseError(Some(
+            "Erro ao solicitar troca de e-mail".into(),
+        )));
+    }
+
+    info!(user_id = %user_id, "Troca de e-mail solicitada");
+    Ok(())
+}
+
+/// Troca a senha de quem está logado. As demais sessões são encerradas e o titular recebe
+/// um aviso por e-mail.
+pub async fn update_password(
+    user_id: Uuid,
+    session_id: Uuid,
+    payload: UpdatePasswordRequest,
+    state: &web::Data<AppState>,
+) -> Result<(), AppError> {
+    let db = &state.db;
+    let mongo_db = &state.mongo;
+
+    payload
+        .validate()
+        .map_err(|e| AppError::BadRequest(Some(format!("Dados inválidos: {}", e))))?;
+
+    let user = user_repository::find_user_by_id(user_id, db).await?;
+    if !user
+        .verify_password(&payload.current_password)
+        .await
+        .is_valid()
+    {
+        return Err(AppError::Unauthorized(Some("❌ Senha incorreta".into())));
+    }
+
+    let profile = profile_repository::find_profile_by_user_id(user_id, db).await?;
+
+    let mut tx = db.begin().await.map_err(|err| {
+        AppError::DatabaseError(Some(format!("Erro ao iniciar transação: {}", err)))
+    })?;
+
+    password_service::change_password_in_tx(&user, &payload.new_password, &mut tx).await?;
+    email_service::queue_password_changed_in_tx(&user, &profile.preferred_language, &mut tx)
+        .await?;
+
+    if let Err(err) = tx.commit().await {
+        log_fail!(
+            err,
+            LogLevel::Error,
+            "Erro ao alterar senha",
+            "user_service",
+            Some(user_id),
+            mongo_db
+        );
+        return Err(AppError::DatabaseError(Some(
+            "Erro ao alterar senha".into(),
+        )));
+    }
+
+    auth_service::revoke_other_sessions(user_id, session_id, db).await?;
+
+    log_event(
+        LogLevel::Info,
+        "Senha alterada pelo próprio usuário; demais sessões encerradas",
+        "user_service",
+        Some(user_id),
+        mongo_db,
+    )
+    .await;
+
     Ok(())
 }
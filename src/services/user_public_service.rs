@@ -5,26 +5,34 @@ use crate::log_fail;
 use crate::logs::model::LogLevel;
 use crate::models::auth::LoginRequest;
 use crate::models::{
+    mfa::LoginResponse,
     profile::Profile,
+    role::DEFAULT_ROLE,
+    session::SessionInfo,
+    token::token_types,
     user::{User, UserRequest, UserResponse, UserWithProfile},
 };
-use crate::repositories::{profile_repository, token_repository, user_repository};
-#[allow(unused_imports)]
-use crate::services::email_service::EmailService;
-use crate::services::token_service;
-use crate::utils::formatter;
-use crate::utils::jwt::generate_jwt;
+use crate::repositories::{profile_repository, role_repository, token_repository, user_repository};
+use crate::services::{
+    account_policy_service, auth_service, email_service, login_throttle_service, mfa_service,
+    password_service, profile_service, token_service, username_service,
+};
+use crate::utils::password::{self, PasswordMatch};
 use actix_web::web;
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use tracing::{error, info, warn};
 use uuid::Uuid;
 use validator::Validate;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use std::env;
 
+/// Tentativas de gravar a conta quando o username gerado é ocupado por um cadastro simultâneo
+const USERNAME_ATTEMPTS: u32 = 3;
+
 /// Cria user + profile com base em UserRequest
 pub async fn create_user_with_request(
     req: UserRequest,
+    session_info: SessionInfo,
     state: &web::Data<AppState>,
 ) -> Result<UserResponse, AppError> {
     info!(email = %req.email, "Criando novo usuário");
@@ -37,7 +45,7 @@ pub async fn create_user_with_request(
     }
 
     // Bloco para capturar qualquer erro inesperado
-    match create_user_with_request_inner(req, state).await {
+    match create_user_with_request_inner(req, session_info, state).await {
         Ok(res) => Ok(res),
         Err(e) => {
             println!("[DEBUG][create_user_with_request] Erro inesperado: {e:?}");
@@ -48,6 +56,7 @@ pub async fn create_user_with_request(
 
 async fn create_user_with_request_inner(
     req: UserRequest,
+    session_info: SessionInfo,
     state: &web::Data<AppState>,
 ) -> Result<UserResponse, AppError> {
     let db = &state.db;
@@ -56,22 +65,27 @@ async fn create_user_with_request_inner(
     let user_id = Uuid::new_v4();
     let now = Utc::now().naive_utc();
 
-    let user = User {
+    let mut user = User {
         id: user_id,
-        username: formatter::generate_username_from_email(&req.email),
+        username: username_service::generate_unique_username(&req.email, None, db).await?,
         email: req.email.clone(),
         first_name: req.first_name,
         last_name: req.last_name,
-        password: bcrypt::hash(&req.password, bcrypt::DEFAULT_COST).expect("Erro ao hashear senha"),
+        password: password::hash_password(&req.password).await?,
         dt_created: now,
         dt_updated: now,
         dt_deleted: None,
     };
 
+    if let Some(document) = req.profile.as_ref().and_then(|p| p.document.as_deref()) {
+        let unique = get_settings().account.unique_document;
+        profile_service::prepare_document(document, user_id, unique, db).await?;
+    }
     let profile = Profile::from_request(user_id, req.profile);
 
-    match create_user_and_profile(&user, &profile, &state).await {
+    match create_user_and_profile(&mut user, &profile, &[DEFAULT_ROLE.to_string()], state).await {
         Ok(p) => p,
+        Err(AppError::Conflict(message)) => return Err(AppError::Conflict(message)),
         Err(err) => {
             log_fail!(
                 err,
@@ -85,27 +99,66 @@ async fn create_user_with_request_inner(
         }
     };
 
-    let token_type: &str = "confirm_email";
-    let confirm_email_code = token_service::create_user_token(user_id, token_type, db).await?;
-    println!("confirm_email_code: {}", confirm_email_code);
 
     let user_with_profile = UserWithProfile::from_user_and_profile(user, profile);
 
-    let settings = get_settings();
-    let token = generate_jwt(&user_id.to_string(), "user").expect("Falha ao gerar token");
-    let expires_in = settings.jwt.expires_in.to_string();
+    let token_pair = auth_service::start_session(user_id, &session_info, db).await?;
 
     info!(
         user_id = %user_id,
         "Usuário criado com sucesso"
     );
 
-    Ok(UserResponse::from(user_with_profile, token, expires_in))
+    Ok(UserResponse::from_token_pair(user_with_profile, token_pair))
+}
+
+/// Gera o código de confirmação e enfileira o e-mail na transação informada
+pub async fn queue_email_confirmation_in_tx(
+    user: &User,
+    language: &str,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(), AppError> {
+    let code =
+        token_service::create_user_token_in_tx(user.id, token_types::CONFIRM_EMAIL, tx).await?;
+    email_service::queue_confirm_email_in_tx(user, language, &code, tx).await?;
+
+    Ok(())
 }
 
+/// Grava a conta. O username é escolhido antes da transação; se outro cadastro o ocupar nesse
+/// meio tempo, um novo é gerado.
 pub async fn create_user_and_profile(
+    user: &mut User,
+    profile: &Profile,
+    roles: &[String],
+    state: &web::Data<AppState>,
+) -> Result<(), AppError> {
+    let mut attempts = 1;
+    loop {
+        match insert_user_and_profile(user, profile, roles, state).await {
+            Err(AppError::Conflict(message)) if attempts < USERNAME_ATTEMPTS => {
+                // o documento em uso também responde `Conflict`, e não se resolve com outro username
+                let username_taken =
+                    user_repository::username_in_use(&user.username, user.id, &state.db)
+                        .await
+                        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+                if !username_taken {
+                    return Err(AppError::Conflict(message));
+                }
+                attempts += 1;
+                user.username =
+                    username_service::generate_unique_username(&user.email, None, &state.db)
+                        .await?;
+            }
+            result => return result,
+        }
+    }
+}
+
+async fn insert_user_and_profile(
     user: &User,
     profile: &Profile,
+    roles: &[String],
     state: &web::Data<AppState>,
 ) -> Result<(), AppError> {
     let db = &state.db;
@@ -118,8 +171,23 @@ pub async fn create_user_and_profile(
         AppError::DatabaseError(Some(format!("Erro ao iniciar transação: {}", err)))
     })?;
 
-    user_repository::create_user_in_tx(&user, &mut tx).await?;
-    profile_repository::create_profile_in_tx(&profile, &mut tx).await?;
+    if let Some(document) = &profile.document {
+        profile_service::ensure_document_available_in_tx(document, user.id, &mut tx).await?;
+    }
+    user_repository::create_user_in_tx(user, &mut tx).await?;
+    profile_repository::create_profile_in_tx(profile, &mut tx).await?;
+    password_service::record_password_in_tx(user.id, &user.password, &mut tx).await?;
+    for role in roles {
+        role_repository::assign_role_in_tx(user.id, role, &mut tx)
+            .await
+            .map_err(|err| {
+                AppError::DatabaseError(Some(format!("Erro ao atribuir papel: {}", err)))
+            })?;
+    }
+    // o e-mail de confirmação só existe se o cadastro for gravado
+    if !profile.confirm_email {
+        queue_email_confirmation_in_tx(user, &profile.preferred_language, &mut tx).await?;
+    }
 
     tx.commit().await.map_err(|err| {
         error!(
@@ -134,8 +202,9 @@ pub async fn create_user_and_profile(
 
 pub async fn login_user(
     payload: LoginRequest,
+    session_info: SessionInfo,
     state: &web::Data<AppState>,
-) -> Result<UserResponse, AppError> {
+) -> Result<LoginResponse, AppError> {
     let db = &state.db;
     let mongo_db = &state.mongo;
 
@@ -151,6 +220,10 @@ pub async fn login_user(
         return Err(AppError::BadRequest(Some(format!("Dados inválidos: {}", e))));
     }
 
+    // Conta ou IP bloqueados por excesso de falhas
+    let client_ip = session_info.ip_address.as_deref();
+    login_throttle_service::ensure_login_allowed(&payload.email, client_ip, state).await?;
+
     // 1. Buscar usuário por email
     let user = match user_repository::find_user_by_email(&payload.email, db).await {
         Ok(Some(user)) => user,
@@ -164,6 +237,8 @@ pub async fn login_user(
                 None,
                 mongo_db
             );
+            login_throttle_service::register_failed_login(&payload.email, client_ip, state)
+                .await?;
             return Err(AppError::Unauthorized(Some("Credenciais inválidas".into())));
         }
         Err(err) => {
@@ -181,7 +256,8 @@ pub async fn login_user(
     };
 
     // 2. Verificar senha
-    if !user.verify_password(&payload.password) {
+    let password_match = user.verify_password(&payload.password).await;
+    if !password_match.is_valid() {
         warn!(
             email = %payload.email,
             "Senha incorreta"
@@ -194,10 +270,29 @@ pub async fn login_user(
             Some(user.id), // ou None se não tiver ainda o user_id
             mongo_db
         );
+        login_throttle_service::register_failed_login(&payload.email, client_ip, state).await?;
         return Err(AppError::Unauthorized(Some("❌ Senha incorreta".into())));
     }
 
-    // 3. Buscar o perfil
+    // Só depois da senha, para não revelar quais contas estão suspensas
+    account_policy_service::ensure_not_suspended(user.id, db).await?;
+
+    // hashes bcrypt ou com parâmetros antigos são migrados no primeiro login
+    if password_match == PasswordMatch::ValidNeedsRehash {
+        password_service::rehash_password(user.id, &payload.password, db).await;
+    }
+
+    // 3. Com o TOTP ativo, o login só é concluído após o segundo fator
+    if mfa_service::is_totp_enabled(user.id, db).await? {
+        info!(user_id = %user.id, "Login aguardando segundo fator");
+        return Ok(LoginResponse::MfaRequired(mfa_service::create_challenge(
+            user.id,
+        )?));
+    }
+
+    login_throttle_service::register_successful_login(&payload.email, state).await;
+
+    // 4. Buscar o perfil
     let profile = profile_repository::find_profile_by_user_id(user.id, db)
         .await
         .map_err(|err| {
@@ -212,17 +307,9 @@ pub async fn login_user(
     // let fake_user_id = Uuid::new_v4();
     // let token = generate_jwt(&fake_user_id.to_string()).expect("Falha ao gerar token");
 
-    // 4. Gerar token JWT
-    let token = generate_jwt(&user.id.to_string(), &profile.access_level).map_err(|err| {
-        error!(
-            error = %err,
-            "Erro ao gerar token"
-        );
-        AppError::BadRequest(Some("Erro ao gerar token".into()))
-    })?;
+    // 5. Gerar access token JWT + refresh token
+    let token_pair = auth_service::start_session(user.id, &session_info, db).await?;
 
-    let settings = get_settings();
-    let expires_in = settings.jwt.expires_in.to_string();
     let user_with_profile = UserWithProfile::from_user_and_profile(user, profile);
 
     info!(
@@ -230,26 +317,113 @@ pub async fn login_user(
         "Login realizado com sucesso"
     );
 
-    Ok(UserResponse::from(user_with_profile, token, expires_in))
+    Ok(LoginResponse::Authenticated(Box::new(
+        UserResponse::from_token_pair(user_with_profile, token_pair),
+    )))
 }
 
-#[allow(unused_variables)]
 pub async fn confirm_email(code: &str, state: &web::Data<AppState>) -> Result<(), AppError> {
     let db = &state.db;
-    let mongo_db = &state.mongo;
-    // Valida o token de confirmação de email
-    let token = token_service::get_and_validate_token(code, "confirm_email", db).await?;
 
-    // Marca o token como consumido
-    token_repository::update_token(db, code).await?;
+    let mut tx = db.begin().await.map_err(|err| {
+        AppError::DatabaseError(Some(format!("Erro ao iniciar transação: {}", err)))
+    })?;
 
-    // Marca o email como confirmado no perfil
-    profile_repository::confirm_email(token.user_id, db).await?;
+    // Consome o token e marca o email como confirmado no perfil
+    let token =
+        token_service::consume_token_in_tx(code, token_types::CONFIRM_EMAIL, &mut tx).await?;
+    profile_repository::confirm_email_in_tx(token.user_id, &mut tx).await?;
+
+    tx.commit().await.map_err(|err| {
+        AppError::DatabaseError(Some(format!("Erro ao commitar transação: {}", err)))
+    })?;
 
     Ok(())
 }
 
-#[allow(unused_variables)]
+/// Reenvia o link de confirmação, invalidando os anteriores. Responde igual para e-mails
+/// desconhecidos ou já confirmados, para não revelar quais contas existem. Reenvios para a
+/// mesma conta respeitam `USER_TOKEN_CONFIRM_EMAIL_RESEND_SECONDS`.
+pub async fn resend_confirmation(email: &str, state: &web::Data<AppState>) -> Result<(), AppError> {
+    let db = &state.db;
+
+    let Some(user) = user_repository::find_user_by_email(email, db).await? else {
+        info!(email = %email, "Reenvio de confirmação para e-mail desconhecido");
+        return Ok(());
+    };
+    let profile = profile_repository::find_profile_by_user_id(user.id, db).await?;
+    if profile.confirm_email {
+        info!(user_id = %user.id, "Reenvio de confirmação para e-mail já confirmado");
+        return Ok(());
+    }
+
+    let latest = token_repository::find_latest_created_at(user.id, token_types::CONFIRM_EMAIL, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    if let Some(latest) = latest {
+        let resend_at =
+            latest + Duration::seconds(get_settings().user_tokens.confirm_email_resend_seconds);
+        let wait = (resend_at - Utc::now().naive_utc()).num_seconds();
+        if wait > 0 {
+            return Err(AppError::too_many_requests(
+                format!("Aguarde {} segundos para pedir um novo link", wait),
+                wait as u64,
+            ));
+        }
+    }
+
+    let mut tx = db.begin().await.map_err(|err| {
+        AppError::DatabaseError(Some(format!("Erro ao iniciar transação: {}", err)))
+    })?;
+
+    queue_email_confirmation_in_tx(&user, &profile.preferred_language, &mut tx).await?;
+
+    tx.commit().await.map_err(|err| {
+        AppError::DatabaseError(Some(format!("Erro ao commitar transação: {}", err)))
+    })?;
+
+    info!(user_id = %user.id, "E-mail de confirmação reenviado");
+    Ok(())
+}
+
+/// Conclui a troca de e-mail pedida em `POST /me/email/`
+pub async fn confirm_email_change(code: &str, state: &web::Data<AppState>) -> Result<(), AppError> {
+    let db = &state.db;
+
+    let mut tx = db.begin().await.map_err(|err| {
+        AppError::DatabaseError(Some(format!("Erro ao iniciar transação: {}", err)))
+    })?;
+
+    let token =
+        token_service::consume_token_in_tx(code, token_types::CHANGE_EMAIL, &mut tx).await?;
+    let new_email = token
+        .new_email
+        .ok_or_else(|| AppError::BadRequest(Some("Token inválido".into())))?;
+
+    let new_username = if get_settings().account.email_change_regenerates_username {
+        Some(username_service::generate_unique_username(&new_email, Some(token.user_id), db).await?)
+    } else {
+        None
+    };
+
+    // o endereço pode ter sido ocupado depois do pedido; o índice único responde com 409
+    let user = user_repository::update_email_in_tx(
+        token.user_id,
+        &new_email,
+        new_username.as_deref(),
+        &mut tx,
+    )
+    .await?;
+    profile_repository::confirm_email_in_tx(token.user_id, &mut tx).await?;
+
+    tx.commit().await.map_err(|err| {
+        AppError::DatabaseError(Some(format!("Erro ao commitar transação: {}", err)))
+    })?;
+
+    info!(user_id = %user.id, username = %user.username, "E-mail da conta alterado");
+    Ok(())
+}
+
 pub async fn forgot_password(email: &str, state: &web::Data<AppState>) -> Result<(), AppError> {
     let db = &state.db;
     let mongo_db = &state.mongo;
@@ -257,44 +431,60 @@ pub async fn forgot_password(email: &str, state: &web::Data<AppState>) -> Result
     let user = user_repository::find_user_by_email(email, db)
         .await?
         .ok_or_else(|| AppError::NotFound(Some("Usuário não encontrado".into())))?;
+    let profile = profile_repository::find_profile_by_user_id(user.id, db).await?;
 
-    // Cria um token para mudança de senha
-    let token_type = "change_password";
-    let code = token_service::create_user_token(user.id, token_type, db).await?;
-    println!("code change password: {}", code);
+    // Token e e-mail são gravados juntos: o worker do outbox faz a entrega
+    let mut tx = db.begin().await.map_err(|err| {
+        AppError::DatabaseError(Some(format!("Erro ao iniciar transação: {}", err)))
+    })?;
 
-    // let email_service = EmailService::new().await?;
-    // let email_link = format!("https://seusite.com/reset?token={}", code);
-    // email_service
-    //     .send_reset_password(email, &user.first_name, &email_link)
-    //     .await?;
+    let code =
+        token_service::create_user_token_in_tx(user.id, token_types::CHANGE_PASSWORD, &mut tx)
+            .await?;
+    email_service::queue_reset_password_in_tx(&user, &profile.preferred_language, &code, &mut tx)
+        .await?;
+
+    if let Err(err) = tx.commit().await {
+        log_fail!(
+            err,
+            LogLevel::Error,
+            "Erro ao enfileirar e-mail de redefinição de senha",
+            "user_service",
+            Some(user.id),
+            mongo_db
+        );
+        return Err(AppError::DatabaseError(Some(
+            "Erro ao solicitar redefinição de senha".into(),
+        )));
+    }
 
-    // Por enquanto apenas retorna Ok, depois implementaremos o envio de email
     Ok(())
 }
 
-#[allow(unused_variables)]
 pub async fn change_password(
     code: &str,
     new_password: &str,
-    // db: &PgPool,
-    // mongo_db: &Database,
     state: &web::Data<AppState>,
 ) -> Result<(), AppError> {
     let db = &state.db;
-    let mongo_db = &state.mongo;
-    // Valida o token de mudança de senha
-    let token = token_service::get_and_validate_token(code, "change_password", db).await?;
 
-    // Hash da nova senha
-    let hashed_password = bcrypt::hash(new_password, bcrypt::DEFAULT_COST)
-        .map_err(|_| AppError::InternalError(Some("Erro ao hashear senha".into())))?;
+    let mut tx = db.begin().await.map_err(|err| {
+        AppError::DatabaseError(Some(format!("Erro ao iniciar transação: {}", err)))
+    })?;
+
+    // Consome o token e troca a senha na mesma transação: se a senha for recusada, o
+    // rollback devolve o token
+    let token =
+        token_service::consume_token_in_tx(code, token_types::CHANGE_PASSWORD, &mut tx).await?;
+    let user = user_repository::find_user_by_id(token.user_id, db).await?;
+    password_service::change_password_in_tx(&user, new_password, &mut tx).await?;
 
-    // Atualiza a senha do usuário
-    user_repository::update_user_password(db, token.user_id, &hashed_password).await?;
+    tx.commit().await.map_err(|err| {
+        AppError::DatabaseError(Some(format!("Erro ao commitar transação: {}", err)))
+    })?;
 
-    // Marca o token como consumido
-    token_repository::update_token(db, code).await?;
+    // Invalida as sessões abertas com a senha antiga
+    auth_service::revoke_all_sessions(token.user_id, db).await?;
 
     Ok(())
 }
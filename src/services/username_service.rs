@@ -0,0 +1,98 @@
+use crate::config::app_state::AppState;
+use crate::errors::app_error::AppError;
+use crate::models::user::{ChangeUsernameRequest, PublicProfile, UsernameResponse};
+use crate::repositories::{course_repository, user_repository};
+use crate::utils::formatter;
+use crate::utils::username::{normalize_username, username_violation};
+use actix_web::web;
+use sqlx::PgPool;
+use std::collections::HashSet;
+use tracing::info;
+use uuid::Uuid;
+
+/// Username livre derivado do e-mail: a base ou, se ocupada ou reservada, `base_2`, `base_3`...
+/// `except_user_id` ignora os usernames da própria conta (troca de e-mail).
+pub async fn generate_unique_username(
+    email: &str,
+    except_user_id: Option<Uuid>,
+    db: &PgPool,
+) -> Result<String, AppError> {
+    let base = formatter::generate_username_from_email(email);
+    let taken: HashSet<String> =
+        user_repository::find_usernames_with_base(&base, except_user_id, db)
+            .await
+            .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?
+            .into_iter()
+            .collect();
+
+    let username = std::iter::once(base.clone())
+        .chain((2..).map(|n| format!("{}_{}", base, n)))
+        .find(|candidate| username_violation(candidate).is_none() && !taken.contains(candidate))
+        .expect("a sequência de sufixos é infinita");
+
+    Ok(username)
+}
+
+/// Troca o username do usuário logado, validando formato, nomes reservados e disponibilidade
+pub async fn change_username(
+    user_id: Uuid,
+    payload: ChangeUsernameRequest,
+    state: &web::Data<AppState>,
+) -> Result<UsernameResponse, AppError> {
+    let db = &state.db;
+    let username = normalize_username(&payload.username);
+
+    if let Some(violation) = username_violation(&username) {
+        return Err(AppError::BadRequest(Some(violation)));
+    }
+
+    let user = user_repository::find_user_by_id(user_id, db).await?;
+    if user.username == username {
+        return Ok(UsernameResponse { username });
+    }
+
+    let in_use = user_repository::username_in_use(&username, user_id, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+    if in_use {
+        return Err(AppError::Conflict(Some(
+            "Nome de usuário já está em uso".into(),
+        )));
+    }
+
+    // a checagem acima e a gravação não são atômicas; o índice único em LOWER(username)
+    // responde com 409 a quem perder a corrida
+    user_repository::update_username(user_id, &username, db).await?;
+    info!(%user_id, old = %user.username, new = %username, "Username alterado");
+
+    Ok(UsernameResponse { username })
+}
+
+/// Perfil público de uma conta ativa; contas excluídas ou suspensas respondem 404
+pub async fn get_public_profile(
+    username: &str,
+    state: &web::Data<AppState>,
+) -> Result<PublicProfile, AppError> {
+    let db = &state.db;
+    let not_found = || AppError::NotFound(Some("Usuário não encontrado".into()));
+
+    let username = normalize_username(username);
+    let user = user_repository::find_public_user_by_username(&username, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?
+        .ok_or_else(not_found)?;
+
+    let courses = course_repository::list_public_courses_by_author(user.id, db)
+        .await
+        .map_err(|e| AppError::DatabaseError(Some(e.to_string())))?;
+
+    Ok(PublicProfile {
+        username: user.username,
+        first_name: user.first_name,
+        bio: user.bio,
+        profession: user.profession,
+        avatar: user.avatar,
+        member_since: user.dt_created.date(),
+        courses,
+    })
+}
@@ -0,0 +1,36 @@
+use super::{SmsMessage, SmsSender};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::path::PathBuf;
+use tracing::info;
+use uuid::Uuid;
+
+/// Grava cada mensagem como `.txt` em um diretório (desenvolvimento)
+pub struct FileSmsSender {
+    dir: PathBuf,
+}
+
+impl FileSmsSender {
+    pub fn new(dir: &str) -> Self {
+        Self {
+            dir: PathBuf::from(dir),
+        }
+    }
+}
+
+#[async_trait]
+impl SmsSender for FileSmsSender {
+    async fn send(&self, message: &SmsMessage) -> anyhow::Result<String> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let file_name = format!(
+            "{}-{}.txt",
+            Utc::now().format("%Y%m%d%H%M%S"),
+            Uuid::new_v4()
+        );
+        let path = self.dir.join(&file_name);
+        tokio::fs::write(&path, format!("To: {}\n\n{}\n", message.to, message.body)).await?;
+
+        info!(to = %message.to, path = %path.display(), "SMS gravado em arquivo");
+        Ok(file_name)
+    }
+}
@@ -0,0 +1,72 @@
+use super::{SmsMessage, SmsSender};
+use crate::config::SmsSettings;
+use anyhow::Context;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Envia por um gateway HTTP: `POST SMS_HTTP_URL` com `{"from", "to", "body"}` em JSON e,
+/// se houver, `Authorization: Bearer SMS_HTTP_TOKEN`
+pub struct HttpSmsSender {
+    client: reqwest::Client,
+    url: String,
+    token: Option<String>,
+    from: String,
+}
+
+/// Campos de identificação que os provedores costumam devolver
+#[derive(Deserialize)]
+struct ProviderResponse {
+    id: Option<String>,
+    sid: Option<String>,
+    message_id: Option<String>,
+}
+
+impl HttpSmsSender {
+    pub fn new(settings: &SmsSettings) -> anyhow::Result<Self> {
+        let url = settings
+            .http_url
+            .clone()
+            .context("SMS_HTTP_URL não definida")?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()?;
+
+        Ok(Self {
+            client,
+            url,
+            token: settings.http_token.clone(),
+            from: settings.from.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl SmsSender for HttpSmsSender {
+    async fn send(&self, message: &SmsMessage) -> anyhow::Result<String> {
+        let mut request = self.client.post(&self.url).json(&serde_json::json!({
+            "from": self.from,
+            "to": message.to,
+            "body": message.body,
+        }));
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .context("Falha ao contatar o provedor de SMS")?
+            .error_for_status()
+            .context("Provedor de SMS recusou a mensagem")?;
+
+        // o corpo é opcional: sem um identificador, geramos um para o log
+        let id = response
+            .json::<ProviderResponse>()
+            .await
+            .ok()
+            .and_then(|body| body.id.or(body.sid).or(body.message_id));
+        Ok(id.unwrap_or_else(|| Uuid::new_v4().to_string()))
+    }
+}
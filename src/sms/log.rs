@@ -0,0 +1,15 @@
+use super::{SmsMessage, SmsSender};
+use async_trait::async_trait;
+use tracing::info;
+use uuid::Uuid;
+
+/// Apenas registra a mensagem no log da aplicação (desenvolvimento)
+pub struct LogSmsSender;
+
+#[async_trait]
+impl SmsSender for LogSmsSender {
+    async fn send(&self, message: &SmsMessage) -> anyhow::Result<String> {
+        info!(to = %message.to, body = %message.body, "SMS registrado no log");
+        Ok(Uuid::new_v4().to_string())
+    }
+}
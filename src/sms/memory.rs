@@ -0,0 +1,35 @@
+use super::{SmsMessage, SmsSender};
+use async_trait::async_trait;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Guarda as mensagens enviadas para que os testes possam inspecioná-las
+#[derive(Default)]
+pub struct InMemorySmsSender {
+    messages: Mutex<Vec<SmsMessage>>,
+}
+
+impl InMemorySmsSender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mensagens enviadas para um número, na ordem de envio
+    pub fn messages_to(&self, to: &str) -> Vec<SmsMessage> {
+        self.messages
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|message| message.to == to)
+            .cloned()
+            .collect()
+    }
+}
+
+#[async_trait]
+impl SmsSender for InMemorySmsSender {
+    async fn send(&self, message: &SmsMessage) -> anyhow::Result<String> {
+        self.messages.lock().unwrap().push(message.clone());
+        Ok(Uuid::new_v4().to_string())
+    }
+}
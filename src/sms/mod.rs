@@ -0,0 +1,36 @@
+pub mod file;
+pub mod http;
+pub mod log;
+pub mod memory;
+
+use crate::config::{SmsBackend, SmsSettings};
+use async_trait::async_trait;
+use serde::Serialize;
+use std::sync::Arc;
+
+pub use file::FileSmsSender;
+pub use http::HttpSmsSender;
+pub use log::LogSmsSender;
+pub use memory::InMemorySmsSender;
+
+/// Mensagem de texto para um número em E.164
+#[derive(Debug, Clone, Serialize)]
+pub struct SmsMessage {
+    pub to: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait SmsSender: Send + Sync {
+    /// Entrega a mensagem e retorna o identificador atribuído pelo provedor
+    async fn send(&self, message: &SmsMessage) -> anyhow::Result<String>;
+}
+
+pub fn build_sms_sender(settings: &SmsSettings) -> anyhow::Result<Arc<dyn SmsSender>> {
+    Ok(match settings.backend {
+        SmsBackend::Log => Arc::new(LogSmsSender),
+        SmsBackend::File => Arc::new(FileSmsSender::new(&settings.file_dir)),
+        SmsBackend::Http => Arc::new(HttpSmsSender::new(settings)?),
+        SmsBackend::Memory => Arc::new(InMemorySmsSender::new()),
+    })
+}
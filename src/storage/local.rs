@@ -0,0 +1,61 @@
+use super::{BlobStore, StoredBlob, content_type_for_key, is_valid_key};
+use anyhow::bail;
+use async_trait::async_trait;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+/// Grava os objetos em um diretório local; a API os serve em `/media/{chave}`
+pub struct LocalBlobStore {
+    dir: PathBuf,
+    public_url: String,
+}
+
+impl LocalBlobStore {
+    pub fn new(dir: &str, public_url: String) -> Self {
+        Self {
+            dir: PathBuf::from(dir),
+            public_url: public_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn path(&self, key: &str) -> anyhow::Result<PathBuf> {
+        if !is_valid_key(key) {
+            bail!("Chave de objeto inválida: {}", key);
+        }
+        Ok(self.dir.join(key))
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, key: &str, _content_type: &str, data: Vec<u8>) -> anyhow::Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, data).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<StoredBlob>> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(data) => Ok(Some(StoredBlob {
+                content_type: content_type_for_key(key).to_string(),
+                data,
+            })),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn public_url(&self) -> &str {
+        &self.public_url
+    }
+}
@@ -0,0 +1,59 @@
+use super::{BlobStore, StoredBlob};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Guarda os objetos em memória para que os testes possam inspecioná-los
+pub struct InMemoryBlobStore {
+    public_url: String,
+    blobs: Mutex<HashMap<String, StoredBlob>>,
+}
+
+impl Default for InMemoryBlobStore {
+    fn default() -> Self {
+        Self {
+            public_url: "memory://blobs".to_string(),
+            blobs: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl InMemoryBlobStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Chaves armazenadas, em ordem alfabética
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.blobs.lock().unwrap().keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+}
+
+#[async_trait]
+impl BlobStore for InMemoryBlobStore {
+    async fn put(&self, key: &str, content_type: &str, data: Vec<u8>) -> anyhow::Result<()> {
+        self.blobs.lock().unwrap().insert(
+            key.to_string(),
+            StoredBlob {
+                content_type: content_type.to_string(),
+                data,
+            },
+        );
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<StoredBlob>> {
+        Ok(self.blobs.lock().unwrap().get(key).cloned())
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        self.blobs.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    fn public_url(&self) -> &str {
+        &self.public_url
+    }
+}
@@ -0,0 +1,86 @@
+pub mod local;
+pub mod memory;
+pub mod s3;
+
+use crate::config::{StorageBackend, StorageSettings};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+pub use local::LocalBlobStore;
+pub use memory::InMemoryBlobStore;
+pub use s3::S3BlobStore;
+
+/// Conteúdo de um objeto armazenado
+#[derive(Debug, Clone)]
+pub struct StoredBlob {
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    /// Grava o objeto, substituindo o anterior com a mesma chave
+    async fn put(&self, key: &str, content_type: &str, data: Vec<u8>) -> anyhow::Result<()>;
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<StoredBlob>>;
+
+    /// Remove o objeto; chaves inexistentes não são erro
+    async fn delete(&self, key: &str) -> anyhow::Result<()>;
+
+    /// Prefixo das URLs públicas, sem a barra final
+    fn public_url(&self) -> &str;
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{}", self.public_url(), key)
+    }
+
+    /// Chave de um objeto a partir da URL pública gerada por este backend
+    fn key_from_url(&self, url: &str) -> Option<String> {
+        url.strip_prefix(self.public_url())
+            .and_then(|rest| rest.strip_prefix('/'))
+            .filter(|key| is_valid_key(key))
+            .map(str::to_string)
+    }
+}
+
+/// Chaves são caminhos relativos (`avatars/<id>/...`), sem `..` nem barras duplicadas
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        })
+}
+
+/// Tipo de conteúdo deduzido da extensão, para backends que não guardam metadados
+pub(crate) fn content_type_for_key(key: &str) -> &'static str {
+    match key.rsplit_once('.').map(|(_, ext)| ext) {
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("png") => "image/png",
+        Some("webp") => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+pub fn build_blob_store(
+    settings: &StorageSettings,
+    app_url: &str,
+) -> anyhow::Result<Arc<dyn BlobStore>> {
+    Ok(match settings.backend {
+        StorageBackend::Local => Arc::new(LocalBlobStore::new(
+            &settings.local_dir,
+            settings
+                .public_url
+                .clone()
+                .unwrap_or_else(|| format!("{}/api/v1/media", app_url.trim_end_matches('/'))),
+        )),
+        StorageBackend::S3 => {
+            Arc::new(S3BlobStore::new(&settings.s3, settings.public_url.clone())?)
+        }
+        StorageBackend::Memory => Arc::new(InMemoryBlobStore::new()),
+    })
+}
@@ -0,0 +1,118 @@
+use super::{BlobStore, StoredBlob};
+use crate::config::S3Settings;
+use anyhow::Context;
+use async_trait::async_trait;
+use aws_sdk_s3::Client;
+use aws_sdk_s3::config::{
+    BehaviorVersion, Config, Credentials, Region, RequestChecksumCalculation,
+    ResponseChecksumValidation,
+};
+use aws_sdk_s3::primitives::ByteStream;
+use std::env;
+
+/// Backend para qualquer serviço compatível com S3
+pub struct S3BlobStore {
+    client: Client,
+    bucket: String,
+    public_url: String,
+}
+
+impl S3BlobStore {
+    /// Sem `S3_ACCESS_KEY_ID`/`S3_SECRET_ACCESS_KEY`, usa as credenciais `AWS_*`
+    pub fn new(settings: &S3Settings, public_url: Option<String>) -> anyhow::Result<Self> {
+        let access_key = match &settings.access_key_id {
+            Some(key) => key.clone(),
+            None => env::var("AWS_ACCESS_KEY_ID").context("S3_ACCESS_KEY_ID não definida")?,
+        };
+        let secret_key = match &settings.secret_access_key {
+            Some(key) => key.clone(),
+            None => {
+                env::var("AWS_SECRET_ACCESS_KEY").context("S3_SECRET_ACCESS_KEY não definida")?
+            }
+        };
+
+        let mut builder = Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .credentials_provider(Credentials::new(
+                access_key, secret_key, None, None, "static",
+            ))
+            .region(Region::new(settings.region.clone()))
+            .force_path_style(settings.force_path_style)
+            // Checksums só quando a operação exige: nem todo serviço compatível os aceita
+            .request_checksum_calculation(RequestChecksumCalculation::WhenRequired)
+            .response_checksum_validation(ResponseChecksumValidation::WhenRequired);
+        if let Some(endpoint) = &settings.endpoint {
+            builder = builder.endpoint_url(endpoint);
+        }
+
+        let public_url = public_url.unwrap_or_else(|| match &settings.endpoint {
+            Some(endpoint) if settings.force_path_style => {
+                format!("{}/{}", endpoint.trim_end_matches('/'), settings.bucket)
+            }
+            Some(endpoint) => endpoint.trim_end_matches('/').to_string(),
+            None => format!(
+                "https://{}.s3.{}.amazonaws.com",
+                settings.bucket, settings.region
+            ),
+        });
+
+        Ok(Self {
+            client: Client::from_conf(builder.build()),
+            bucket: settings.bucket.clone(),
+            public_url: public_url.trim_end_matches('/').to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl BlobStore for S3BlobStore {
+    async fn put(&self, key: &str, content_type: &str, data: Vec<u8>) -> anyhow::Result<()> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .content_type(content_type)
+            .body(ByteStream::from(data))
+            .send()
+            .await
+            .with_context(|| format!("Falha ao gravar {} no S3", key))?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<StoredBlob>> {
+        let output = match self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(output) => output,
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_no_such_key()) => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("Falha ao ler {} do S3", key)),
+        };
+
+        let content_type = output
+            .content_type()
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        let data = output.body.collect().await?.into_bytes().to_vec();
+        Ok(Some(StoredBlob { content_type, data }))
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .with_context(|| format!("Falha ao remover {} do S3", key))?;
+        Ok(())
+    }
+
+    fn public_url(&self) -> &str {
+        &self.public_url
+    }
+}
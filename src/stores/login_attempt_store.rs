@@ -0,0 +1,186 @@
+use async_trait::async_trait;
+use redis::AsyncCommands;
+use redis::Script;
+use redis::aio::ConnectionManager;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Acima desse número de chaves o backend em memória descarta as janelas e bloqueios vencidos
+const MEMORY_PRUNE_THRESHOLD: usize = 10_000;
+
+/// Contadores de falhas de login e bloqueios temporários, indexados por chave
+/// (ex: `login:email:<email>`, `login:ip:<ip>`)
+#[async_trait]
+pub trait LoginAttemptStore: Send + Sync {
+    /// Registra uma falha e retorna o total de falhas na janela atual
+    async fn register_failure(&self, key: &str, window_seconds: u64) -> anyhow::Result<u32>;
+
+    /// Bloqueia a chave pelo tempo informado
+    async fn lock(&self, key: &str, seconds: u64) -> anyhow::Result<()>;
+
+    /// Segundos restantes de bloqueio, se a chave estiver bloqueada
+    async fn lock_remaining(&self, key: &str) -> anyhow::Result<Option<u64>>;
+
+    /// Zera o contador e remove o bloqueio da chave
+    async fn reset(&self, key: &str) -> anyhow::Result<()>;
+}
+
+#[derive(Default)]
+struct AttemptEntry {
+    failures: u32,
+    window_ends_at: Option<Instant>,
+    locked_until: Option<Instant>,
+}
+
+impl AttemptEntry {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.window_ends_at.is_none_or(|ends_at| ends_at <= now)
+            && self
+                .locked_until
+                .is_none_or(|locked_until| locked_until <= now)
+    }
+}
+
+/// Backend em memória, usado nos testes e em desenvolvimento (não é compartilhado entre instâncias)
+#[derive(Default)]
+pub struct InMemoryLoginAttemptStore {
+    entries: Mutex<HashMap<String, AttemptEntry>>,
+}
+
+impl InMemoryLoginAttemptStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Quantidade de chaves mantidas em memória
+    pub fn tracked_keys(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    // as chaves vêm do usuário (e-mails digitados), então o mapa não pode crescer sem limite
+    fn prune_expired(entries: &mut HashMap<String, AttemptEntry>, now: Instant) {
+        if entries.len() > MEMORY_PRUNE_THRESHOLD {
+            entries.retain(|_, entry| !entry.is_expired(now));
+        }
+    }
+}
+
+#[async_trait]
+impl LoginAttemptStore for InMemoryLoginAttemptStore {
+    async fn register_failure(&self, key: &str, window_seconds: u64) -> anyhow::Result<u32> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        Self::prune_expired(&mut entries, now);
+        let entry = entries.entry(key.to_string()).or_default();
+
+        if entry.window_ends_at.is_none_or(|ends_at| ends_at <= now) {
+            entry.failures = 0;
+            entry.window_ends_at = Some(now + Duration::from_secs(window_seconds));
+        }
+        entry.failures += 1;
+
+        Ok(entry.failures)
+    }
+
+    async fn lock(&self, key: &str, seconds: u64) -> anyhow::Result<()> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        Self::prune_expired(&mut entries, now);
+        let entry = entries.entry(key.to_string()).or_default();
+        entry.locked_until = Some(now + Duration::from_secs(seconds));
+        Ok(())
+    }
+
+    async fn lock_remaining(&self, key: &str) -> anyhow::Result<Option<u64>> {
+        let now = Instant::now();
+        let entries = self.entries.lock().unwrap();
+
+        Ok(entries
+            .get(key)
+            .and_then(|entry| entry.locked_until)
+            .filter(|locked_until| *locked_until > now)
+            .map(|locked_until| (locked_until - now).as_secs().max(1)))
+    }
+
+    async fn reset(&self, key: &str) -> anyhow::Result<()> {
+        self.entries.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/// Script atômico do contador: incrementa e, na primeira falha, abre a janela com o TTL
+const REGISTER_FAILURE_SCRIPT: &str = r#"
+local failures = redis.call('INCR', KEYS[1])
+if failures == 1 then
+    redis.call('EXPIRE', KEYS[1], tonumber(ARGV[1]))
+end
+return failures
+"#;
+
+/// Backend Redis, compartilhado entre as instâncias da aplicação em produção
+pub struct RedisLoginAttemptStore {
+    connection: ConnectionManager,
+    script: Script,
+}
+
+impl RedisLoginAttemptStore {
+    pub fn new(connection: ConnectionManager) -> Self {
+        Self {
+            connection,
+            script: Script::new(REGISTER_FAILURE_SCRIPT),
+        }
+    }
+
+    fn counter_key(key: &str) -> String {
+        format!("{}:failures", key)
+    }
+
+    fn lock_key(key: &str) -> String {
+        format!("{}:lock", key)
+    }
+}
+
+#[async_trait]
+impl LoginAttemptStore for RedisLoginAttemptStore {
+    async fn register_failure(&self, key: &str, window_seconds: u64) -> anyhow::Result<u32> {
+        let mut conn = self.connection.clone();
+
+        let failures: u32 = self
+            .script
+            .key(Self::counter_key(key))
+            .arg(window_seconds)
+            .invoke_async(&mut conn)
+            .await?;
+
+        Ok(failures)
+    }
+
+    async fn lock(&self, key: &str, seconds: u64) -> anyhow::Result<()> {
+        let mut conn = self.connection.clone();
+        conn.set_ex::<_, _, ()>(Self::lock_key(key), 1, seconds)
+            .await?;
+        Ok(())
+    }
+
+    async fn lock_remaining(&self, key: &str) -> anyhow::Result<Option<u64>> {
+        let mut conn = self.connection.clone();
+        let ttl: i64 = conn.ttl(Self::lock_key(key)).await?;
+        Ok((ttl > 0).then_some(ttl as u64))
+    }
+
+    async fn reset(&self, key: &str) -> anyhow::Result<()> {
+        let mut conn = self.connection.clone();
+        conn.del::<_, ()>(&[Self::counter_key(key), Self::lock_key(key)])
+            .await?;
+        Ok(())
+    }
+}
+
+/// Usa o Redis quando houver conexão configurada e, caso contrário, o backend em memória
+pub fn build_login_attempt_store(redis: Option<ConnectionManager>) -> Arc<dyn LoginAttemptStore> {
+    match redis {
+        Some(connection) => Arc::new(RedisLoginAttemptStore::new(connection)),
+        None => Arc::new(InMemoryLoginAttemptStore::new()),
+    }
+}
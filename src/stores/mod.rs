@@ -0,0 +1,2 @@
+pub mod login_attempt_store;
+pub mod rate_limit_store;
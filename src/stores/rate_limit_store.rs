@@ -0,0 +1,183 @@
+use async_trait::async_trait;
+use redis::Script;
+use redis::aio::ConnectionManager;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Acima desse número de chaves o backend em memória descarta os buckets já cheios
+const MEMORY_PRUNE_THRESHOLD: usize = 10_000;
+
+/// Resultado de uma tentativa de consumir um token do bucket
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Segundos até haver um token disponível (0 quando permitido)
+    pub retry_after: u64,
+    /// Segundos até o bucket voltar a ficar cheio
+    pub reset_after: u64,
+}
+
+impl RateLimitDecision {
+    fn from_tokens(allowed: bool, tokens: f64, capacity: u32, refill_per_second: f64) -> Self {
+        let retry_after = if allowed {
+            0
+        } else {
+            ((1.0 - tokens) / refill_per_second).ceil().max(1.0) as u64
+        };
+
+        Self {
+            allowed,
+            limit: capacity,
+            remaining: tokens.floor().max(0.0) as u32,
+            retry_after,
+            reset_after: ((capacity as f64 - tokens) / refill_per_second).ceil() as u64,
+        }
+    }
+}
+
+/// Armazenamento dos token buckets do rate limiter
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Consome um token do bucket da chave. O bucket comporta `capacity` tokens
+    /// e recebe `refill_per_second` tokens por segundo.
+    async fn take(
+        &self,
+        key: &str,
+        capacity: u32,
+        refill_per_second: f64,
+    ) -> anyhow::Result<RateLimitDecision>;
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// Backend em memória, usado nos testes e em instância única
+#[derive(Default)]
+pub struct InMemoryRateLimitStore {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl InMemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for InMemoryRateLimitStore {
+    async fn take(
+        &self,
+        key: &str,
+        capacity: u32,
+        refill_per_second: f64,
+    ) -> anyhow::Result<RateLimitDecision> {
+        let now = Instant::now();
+        let capacity_f = capacity as f64;
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if buckets.len() > MEMORY_PRUNE_THRESHOLD {
+            buckets.retain(|_, bucket| {
+                let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+                bucket.tokens + elapsed * refill_per_second < capacity_f
+            });
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity_f,
+            updated_at: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_second).min(capacity_f);
+        bucket.updated_at = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+
+        Ok(RateLimitDecision::from_tokens(
+            allowed,
+            bucket.tokens,
+            capacity,
+            refill_per_second,
+        ))
+    }
+}
+
+/// Script atômico do token bucket: recarrega pelo tempo decorrido, consome um token
+/// e expira a chave quando o bucket voltaria a ficar cheio
+const TOKEN_BUCKET_SCRIPT: &str = r#"
+local capacity = tonumber(ARGV[1])
+local rate = tonumber(ARGV[2])
+local now = tonumber(ARGV[3])
+local data = redis.call('HMGET', KEYS[1], 'tokens', 'ts')
+local tokens = tonumber(data[1]) or capacity
+local ts = tonumber(data[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - ts) / 1000 * rate)
+local allowed = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+    allowed = 1
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'ts', tostring(now))
+redis.call('PEXPIRE', KEYS[1], math.ceil((capacity - tokens) / rate * 1000) + 1000)
+return {allowed, tostring(tokens)}
+"#;
+
+/// Backend Redis, compartilhado entre as instâncias da aplicação
+pub struct RedisRateLimitStore {
+    connection: ConnectionManager,
+    script: Script,
+}
+
+impl RedisRateLimitStore {
+    pub fn new(connection: ConnectionManager) -> Self {
+        Self {
+            connection,
+            script: Script::new(TOKEN_BUCKET_SCRIPT),
+        }
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+    async fn take(
+        &self,
+        key: &str,
+        capacity: u32,
+        refill_per_second: f64,
+    ) -> anyhow::Result<RateLimitDecision> {
+        let mut conn = self.connection.clone();
+        let now_ms = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+
+        let (allowed, tokens): (i64, String) = self
+            .script
+            .key(key)
+            .arg(capacity)
+            .arg(refill_per_second)
+            .arg(now_ms)
+            .invoke_async(&mut conn)
+            .await?;
+
+        Ok(RateLimitDecision::from_tokens(
+            allowed == 1,
+            tokens.parse()?,
+            capacity,
+            refill_per_second,
+        ))
+    }
+}
+
+/// Usa o Redis quando houver conexão configurada e, caso contrário, o backend em memória
+pub fn build_rate_limit_store(redis: Option<ConnectionManager>) -> Arc<dyn RateLimitStore> {
+    match redis {
+        Some(connection) => Arc::new(RedisRateLimitStore::new(connection)),
+        None => Arc::new(InMemoryRateLimitStore::new()),
+    }
+}
@@ -0,0 +1,107 @@
+use crate::errors::app_error::AppError;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, Limits, RgbImage};
+use std::io::Cursor;
+use tracing::error;
+
+/// Tipos aceitos no envio de avatar
+pub const ALLOWED_CONTENT_TYPES: [&str; 3] = ["image/jpeg", "image/png", "image/webp"];
+
+/// Maior lado aceito na imagem original, para barrar "bombas" de descompressão
+const MAX_DIMENSION: u32 = 8192;
+const JPEG_QUALITY: u8 = 85;
+
+/// Miniatura quadrada em JPEG
+#[derive(Debug, Clone)]
+pub struct Thumbnail {
+    pub size: u32,
+    pub data: Vec<u8>,
+}
+
+fn format_for_content_type(content_type: &str) -> Option<ImageFormat> {
+    match content_type {
+        "image/jpeg" => Some(ImageFormat::Jpeg),
+        "image/png" => Some(ImageFormat::Png),
+        "image/webp" => Some(ImageFormat::WebP),
+        _ => None,
+    }
+}
+
+fn invalid_image() -> AppError {
+    AppError::BadRequest(Some("Arquivo de imagem inválido".into()))
+}
+
+/// Compõe a transparência sobre fundo branco, já que o JPEG não tem canal alfa
+fn flatten(image: &DynamicImage) -> RgbImage {
+    let rgba = image.to_rgba8();
+    RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let blend = |c: u8| ((c as u16 * a as u16 + 255 * (255 - a as u16)) / 255) as u8;
+        image::Rgb([blend(r), blend(g), blend(b)])
+    })
+}
+
+/// Confere o tipo declarado contra o conteúdo, aplica a orientação do EXIF e gera uma
+/// miniatura por tamanho. A imagem é recodificada, então EXIF, GPS e demais metadados
+/// do arquivo original não chegam ao armazenamento. Custoso: use `process_avatar` no
+/// runtime async.
+pub fn process_avatar_blocking(
+    data: &[u8],
+    content_type: &str,
+    sizes: &[u32],
+) -> Result<Vec<Thumbnail>, AppError> {
+    let declared = format_for_content_type(content_type).ok_or_else(|| {
+        AppError::BadRequest(Some(format!(
+            "Tipo de arquivo não suportado. Use: {}",
+            ALLOWED_CONTENT_TYPES.join(", ")
+        )))
+    })?;
+    let detected = image::guess_format(data).map_err(|_| invalid_image())?;
+    if detected != declared {
+        return Err(AppError::BadRequest(Some(
+            "O conteúdo do arquivo não corresponde ao tipo informado".into(),
+        )));
+    }
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+
+    let mut reader = ImageReader::with_format(Cursor::new(data), detected);
+    reader.limits(limits);
+    let mut decoder = reader.into_decoder().map_err(|_| invalid_image())?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut image = DynamicImage::from_decoder(decoder).map_err(|_| invalid_image())?;
+    image.apply_orientation(orientation);
+
+    sizes
+        .iter()
+        .map(|&size| {
+            let resized = flatten(&image.resize_to_fill(size, size, FilterType::Lanczos3));
+            let mut data = Vec::new();
+            resized
+                .write_with_encoder(JpegEncoder::new_with_quality(&mut data, JPEG_QUALITY))
+                .map_err(|e| {
+                    error!(error = %e, "Erro ao gerar miniatura do avatar");
+                    AppError::InternalError(Some("Erro ao processar imagem".into()))
+                })?;
+            Ok(Thumbnail { size, data })
+        })
+        .collect()
+}
+
+/// Processa a imagem em uma thread de bloqueio, sem travar o runtime
+pub async fn process_avatar(
+    data: Vec<u8>,
+    content_type: String,
+    sizes: Vec<u32>,
+) -> Result<Vec<Thumbnail>, AppError> {
+    tokio::task::spawn_blocking(move || process_avatar_blocking(&data, &content_type, &sizes))
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Erro ao processar avatar");
+            AppError::InternalError(Some("Erro ao processar imagem".into()))
+        })?
+}
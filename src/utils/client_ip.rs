@@ -0,0 +1,46 @@
+use crate::config::get_settings;
+use actix_web::HttpRequest;
+use ipnet::IpNet;
+use std::net::IpAddr;
+
+/// IP do cliente usado no rate limit, no bloqueio de login e nas sessões. `X-Forwarded-For`
+/// só é lido quando a conexão vem de um proxy em `TRUSTED_PROXIES`; sem isso qualquer cliente
+/// trocaria de IP a cada requisição só mudando o header.
+pub fn client_ip(req: &HttpRequest) -> Option<String> {
+    let forwarded_for = req
+        .headers()
+        .get("X-Forwarded-For")
+        .and_then(|h| h.to_str().ok());
+
+    resolve_client_ip(
+        req.peer_addr().map(|addr| addr.ip()),
+        forwarded_for,
+        &get_settings().server.trusted_proxies,
+    )
+    .map(|ip| ip.to_string())
+}
+
+/// Percorre o `X-Forwarded-For` da direita para a esquerda enquanto os saltos forem proxies
+/// confiáveis; o primeiro endereço fora da lista é o cliente.
+pub fn resolve_client_ip(
+    peer: Option<IpAddr>,
+    forwarded_for: Option<&str>,
+    trusted_proxies: &[IpNet],
+) -> Option<IpAddr> {
+    let is_trusted = |ip: &IpAddr| trusted_proxies.iter().any(|net| net.contains(ip));
+
+    let mut client = peer?;
+    if !is_trusted(&client) {
+        return Some(client);
+    }
+    for hop in forwarded_for.unwrap_or("").rsplit(',') {
+        let Ok(ip) = hop.trim().parse::<IpAddr>() else {
+            break;
+        };
+        client = ip;
+        if !is_trusted(&ip) {
+            break;
+        }
+    }
+    Some(client)
+}
@@ -0,0 +1,24 @@
+use rand::{Rng, distributions::Alphanumeric};
+use sha2::{Digest, Sha256};
+
+/// Gera um código aleatório alfanumérico com o tamanho informado
+pub fn generate_random_code(length: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect()
+}
+
+/// Gera um código numérico aleatório (ex.: códigos enviados por SMS)
+pub fn generate_numeric_code(length: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..length)
+        .map(|_| char::from(b'0' + rng.gen_range(0..10)))
+        .collect()
+}
+
+/// Retorna o hash SHA-256 (hex) de um valor, usado para guardar tokens no banco
+pub fn sha256_hex(value: &str) -> String {
+    hex::encode(Sha256::digest(value.as_bytes()))
+}
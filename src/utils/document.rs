@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+
+/// Documentos aceitos no perfil
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentType {
+    Cpf,
+    Cnpj,
+}
+
+impl DocumentType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DocumentType::Cpf => "cpf",
+            DocumentType::Cnpj => "cnpj",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Option<Self> {
+        match value {
+            "cpf" => Some(DocumentType::Cpf),
+            "cnpj" => Some(DocumentType::Cnpj),
+            _ => None,
+        }
+    }
+}
+
+/// Documento já validado, com apenas os dígitos
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Document {
+    pub kind: DocumentType,
+    pub number: String,
+}
+
+/// Dígito verificador do módulo 11 usado por CPF e CNPJ
+fn check_digit(digits: &[u32], weights: &[u32]) -> u32 {
+    let sum: u32 = digits.iter().zip(weights).map(|(d, w)| d * w).sum();
+    match sum % 11 {
+        0 | 1 => 0,
+        rest => 11 - rest,
+    }
+}
+
+fn has_valid_check_digits(digits: &[u32], first_weights: &[u32], second_weights: &[u32]) -> bool {
+    let base = digits.len() - 2;
+    // sequências como 111.111.111-11 passam no módulo 11, mas não são emitidas
+    if digits.iter().all(|&d| d == digits[0]) {
+        return false;
+    }
+    let first = check_digit(&digits[..base], first_weights);
+    let second = check_digit(&digits[..=base], second_weights);
+    digits[base] == first && digits[base + 1] == second
+}
+
+pub fn is_valid_cpf(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    digits.len() == 11
+        && number.len() == 11
+        && has_valid_check_digits(
+            &digits,
+            &[10, 9, 8, 7, 6, 5, 4, 3, 2],
+            &[11, 10, 9, 8, 7, 6, 5, 4, 3, 2],
+        )
+}
+
+pub fn is_valid_cnpj(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    digits.len() == 14
+        && number.len() == 14
+        && has_valid_check_digits(
+            &digits,
+            &[5, 4, 3, 2, 9, 8, 7, 6, 5, 4, 3, 2],
+            &[6, 5, 4, 3, 2, 9, 8, 7, 6, 5, 4, 3, 2],
+        )
+}
+
+/// Aceita CPF ou CNPJ com ou sem pontuação (`.`, `-`, `/` e espaços) e devolve só os dígitos.
+/// O tipo é deduzido pela quantidade de dígitos: 11 para CPF e 14 para CNPJ.
+pub fn parse_document(value: &str) -> Option<Document> {
+    if !value
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '/' | ' '))
+    {
+        return None;
+    }
+    let number: String = value.chars().filter(char::is_ascii_digit).collect();
+
+    match number.len() {
+        11 if is_valid_cpf(&number) => Some(Document {
+            kind: DocumentType::Cpf,
+            number,
+        }),
+        14 if is_valid_cnpj(&number) => Some(Document {
+            kind: DocumentType::Cnpj,
+            number,
+        }),
+        _ => None,
+    }
+}
+
+/// Aplica a máscara de exibição: `000.000.000-00` ou `00.000.000/0000-00`
+pub fn format_document(kind: DocumentType, number: &str) -> String {
+    let d = number;
+    match kind {
+        DocumentType::Cpf if d.len() == 11 => {
+            format!("{}.{}.{}-{}", &d[..3], &d[3..6], &d[6..9], &d[9..])
+        }
+        DocumentType::Cnpj if d.len() == 14 => format!(
+            "{}.{}.{}/{}-{}",
+            &d[..2],
+            &d[2..5],
+            &d[5..8],
+            &d[8..12],
+            &d[12..]
+        ),
+        _ => d.to_string(),
+    }
+}
+
+/// Esconde o início e os dígitos verificadores: `***.456.789-**` ou `**.345.678/0001-**`
+pub fn mask_document(kind: DocumentType, number: &str) -> String {
+    let formatted = format_document(kind, number);
+    let prefix = match kind {
+        DocumentType::Cpf => 3,
+        DocumentType::Cnpj => 2,
+    };
+    let suffix_start = formatted.len().saturating_sub(2);
+
+    formatted
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if c.is_ascii_digit() && (i < prefix || i >= suffix_start) {
+                '*'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
@@ -1,8 +1,31 @@
+use crate::utils::username::{MAX_LENGTH, MIN_LENGTH};
+
+/// Sufixos numéricos (`_2`, `_10`...) cabem depois da base sem passar de `MAX_LENGTH`
+const BASE_MAX_LENGTH: usize = MAX_LENGTH - 6;
+
+/// Base do username a partir do e-mail: minúsculas, apenas `[a-z0-9_]`. Não garante
+/// unicidade; veja `username_service::generate_unique_username`.
 pub fn generate_username_from_email(email: &str) -> String {
     let prefix = email.split('@').next().unwrap_or("");
 
-    prefix
+    let username: String = prefix
         .chars()
-        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
-        .collect()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .take(BASE_MAX_LENGTH)
+        .collect();
+    let username = username.trim_matches('_');
+
+    if username.len() < MIN_LENGTH {
+        format!("user_{}", username)
+            .trim_end_matches('_')
+            .to_string()
+    } else {
+        username.to_string()
+    }
 }
@@ -1,14 +1,257 @@
-use crate::config::get_settings;
-use crate::models::auth::Claims;
+use crate::config::{JwtAlgorithm, JwtSettings, get_settings};
+use crate::models::auth::{ACCESS_TOKEN_AUDIENCE, Claims};
+use crate::models::email_preferences::{
+    UNSUBSCRIBE_AUDIENCE, UNSUBSCRIBE_TOKEN_EXPIRES_IN, UnsubscribeClaims,
+};
+use crate::models::mfa::{MFA_CHALLENGE_AUDIENCE, MfaChallengeClaims};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use chrono::{Duration, Utc};
+use jsonwebtoken::errors::{Error, ErrorKind};
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+    OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
 use jsonwebtoken::{
-    Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation, decode, encode,
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header, encode,
 };
+use rsa::RsaPublicKey;
+use rsa::pkcs8::DecodePublicKey;
+use rsa::traits::PublicKeyParts;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+static JWT_KEYS: OnceLock<JwtKeys> = OnceLock::new();
+
+/// Claims acrescidas da audiência (`aud`), que separa os tipos de token assinados pela mesma chave
+#[derive(Serialize)]
+struct WithAudience<'a, T> {
+    #[serde(flatten)]
+    claims: &'a T,
+    aud: &'a str,
+}
+
+/// Chaves de assinatura e verificação dos tokens emitidos pela API.
+///
+/// Apenas a chave ativa assina; todas as chaves em `decoding_keys` são aceitas na
+/// verificação, o que permite rotacionar sem invalidar os tokens já emitidos.
+pub struct JwtKeys {
+    algorithm: Algorithm,
+    active_kid: String,
+    encoding_key: EncodingKey,
+    decoding_keys: HashMap<String, DecodingKey>,
+    jwks: JwkSet,
+}
+
+impl JwtKeys {
+    /// HS256 com segredo compartilhado. Não publica nada no JWKS.
+    pub fn hmac(kid: &str, secret: &str) -> Self {
+        let mut decoding_keys = HashMap::new();
+        decoding_keys.insert(kid.to_string(), DecodingKey::from_secret(secret.as_bytes()));
+
+        Self {
+            algorithm: Algorithm::HS256,
+            active_kid: kid.to_string(),
+            encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+            decoding_keys,
+            jwks: JwkSet { keys: Vec::new() },
+        }
+    }
+
+    /// RS256/EdDSA a partir de PEMs PKCS#8 (privada) e SPKI (públicas).
+    /// A chave pública do `active_kid` precisa estar entre as `public_keys`.
+    pub fn asymmetric(
+        algorithm: JwtAlgorithm,
+        active_kid: &str,
+        private_key_pem: &str,
+        public_keys: &[(String, String)],
+    ) -> Result<Self, String> {
+        let (algorithm, encoding_key) = match algorithm {
+            JwtAlgorithm::RS256 => (
+                Algorithm::RS256,
+                EncodingKey::from_rsa_pem(private_key_pem.as_bytes()),
+            ),
+            JwtAlgorithm::EdDSA => (
+                Algorithm::EdDSA,
+                EncodingKey::from_ed_pem(private_key_pem.as_bytes()),
+            ),
+            JwtAlgorithm::HS256 => return Err("HS256 não usa par de chaves".into()),
+        };
+        let encoding_key = encoding_key.map_err(|e| format!("Chave privada inválida: {}", e))?;
+
+        let mut decoding_keys = HashMap::new();
+        let mut jwks = Vec::new();
+        for (kid, public_key_pem) in public_keys {
+            let (decoding_key, jwk) = match algorithm {
+                Algorithm::RS256 => (
+                    DecodingKey::from_rsa_pem(public_key_pem.as_bytes()),
+                    rsa_jwk(kid, public_key_pem)?,
+                ),
+                _ => (
+                    DecodingKey::from_ed_pem(public_key_pem.as_bytes()),
+                    ed25519_jwk(kid, public_key_pem)?,
+                ),
+            };
+            let decoding_key =
+                decoding_key.map_err(|e| format!("Chave pública '{}' inválida: {}", kid, e))?;
+
+            decoding_keys.insert(kid.clone(), decoding_key);
+            jwks.push(jwk);
+        }
+
+        if !decoding_keys.contains_key(active_kid) {
+            return Err(format!(
+                "A chave pública do kid ativo '{}' não foi informada",
+                active_kid
+            ));
+        }
+
+        Ok(Self {
+            algorithm,
+            active_kid: active_kid.to_string(),
+            encoding_key,
+            decoding_keys,
+            jwks: JwkSet { keys: jwks },
+        })
+    }
+
+    pub fn from_settings(settings: &JwtSettings) -> Result<Self, String> {
+        if settings.algorithm == JwtAlgorithm::HS256 {
+            return Ok(Self::hmac(&settings.key_id, &settings.secret));
+        }
+
+        let private_key_path = settings
+            .private_key_path
+            .as_deref()
+            .ok_or("JWT_PRIVATE_KEY_PATH é obrigatória para RS256/EdDSA")?;
+        let private_key_pem = read_pem(private_key_path)?;
+
+        let public_keys = settings
+            .public_keys
+            .iter()
+            .map(|(kid, path)| Ok((kid.clone(), read_pem(path)?)))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Self::asymmetric(
+            settings.algorithm,
+            &settings.key_id,
+            &private_key_pem,
+            &public_keys,
+        )
+    }
+
+    pub fn sign<T: Serialize>(&self, claims: &T, audience: &str) -> Result<String, Error> {
+        let mut header = Header::new(self.algorithm);
+        header.kid = Some(self.active_kid.clone());
+
+        let claims = WithAudience {
+            claims,
+            aud: audience,
+        };
+        encode(&header, &claims, &self.encoding_key)
+    }
+
+    /// Verificador único de todos os tokens da API: confere algoritmo, `kid`, assinatura, `exp`
+    /// e `aud`, que precisa ser a audiência esperada pelo chamador
+    pub fn verify<T: DeserializeOwned>(&self, token: &str, audience: &str) -> Result<T, Error> {
+        let header = decode_header(token)?;
+        if header.alg != self.algorithm {
+            return Err(ErrorKind::InvalidAlgorithm.into());
+        }
+
+        let key = match header.kid.as_deref() {
+            Some(kid) => self.decoding_keys.get(kid),
+            // tokens HS256 emitidos antes do `kid` continuam válidos até expirar
+            None if self.algorithm == Algorithm::HS256 => self.decoding_keys.get(&self.active_kid),
+            None => None,
+        }
+        .ok_or(Error::from(ErrorKind::InvalidToken))?;
+
+        let mut validation = Validation::new(self.algorithm);
+        validation.set_audience(&[audience]);
+        validation.set_required_spec_claims(&["exp", "aud"]);
+
+        Ok(decode::<T>(token, key, &validation)?.claims)
+    }
+
+    pub fn jwks(&self) -> &JwkSet {
+        &self.jwks
+    }
+}
+
+fn read_pem(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("Erro ao ler chave '{}': {}", path, e))
+}
+
+fn common_parameters(kid: &str, algorithm: KeyAlgorithm) -> CommonParameters {
+    CommonParameters {
+        public_key_use: Some(PublicKeyUse::Signature),
+        key_algorithm: Some(algorithm),
+        key_id: Some(kid.to_string()),
+        ..Default::default()
+    }
+}
+
+fn rsa_jwk(kid: &str, public_key_pem: &str) -> Result<Jwk, String> {
+    let public_key = RsaPublicKey::from_public_key_pem(public_key_pem)
+        .map_err(|e| format!("Chave pública '{}' inválida: {}", kid, e))?;
+
+    Ok(Jwk {
+        common: common_parameters(kid, KeyAlgorithm::RS256),
+        algorithm: AlgorithmParameters::RSA(RSAKeyParameters {
+            key_type: RSAKeyType::RSA,
+            n: URL_SAFE_NO_PAD.encode(public_key.n().to_bytes_be()),
+            e: URL_SAFE_NO_PAD.encode(public_key.e().to_bytes_be()),
+        }),
+    })
+}
+
+fn ed25519_jwk(kid: &str, public_key_pem: &str) -> Result<Jwk, String> {
+    let der = pem::parse(public_key_pem)
+        .map_err(|e| format!("Chave pública '{}' inválida: {}", kid, e))?;
+    // o SPKI de uma chave Ed25519 termina com os 32 bytes da chave pública
+    let public_key = der
+        .contents()
+        .len()
+        .checked_sub(32)
+        .map(|start| &der.contents()[start..])
+        .ok_or_else(|| format!("Chave pública '{}' não é Ed25519", kid))?;
+
+    Ok(Jwk {
+        common: common_parameters(kid, KeyAlgorithm::EdDSA),
+        algorithm: AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+            key_type: OctetKeyPairType::OctetKeyPair,
+            curve: EllipticCurve::Ed25519,
+            x: URL_SAFE_NO_PAD.encode(public_key),
+        }),
+    })
+}
+
+/// Carrega as chaves na inicialização para falhar cedo com configuração inválida
+pub fn init_jwt_keys() -> Result<(), String> {
+    let keys = JwtKeys::from_settings(&get_settings().jwt)?;
+    JWT_KEYS
+        .set(keys)
+        .map_err(|_| "Chaves JWT já inicializadas".to_string())
+}
+
+pub fn jwt_keys() -> &'static JwtKeys {
+    JWT_KEYS.get_or_init(|| {
+        JwtKeys::from_settings(&get_settings().jwt).expect("Falha ao carregar chaves JWT")
+    })
+}
 
 pub fn generate_jwt(
     user_id: &str,
-    access_level: &str,
-) -> Result<String, jsonwebtoken::errors::Error> {
+    roles: Vec<String>,
+    permissions: Vec<String>,
+    jti: &str,
+    sid: &str,
+) -> Result<String, Error> {
     let settings = get_settings();
     let expires_in = settings.jwt.expires_in;
 
@@ -20,52 +263,69 @@ pub fn generate_jwt(
     let claims = Claims {
         sub: user_id.to_string(),
         exp,
-        access_level: access_level.to_string(),
+        roles,
+        permissions,
+        jti: jti.to_string(),
+        sid: sid.to_string(),
+        api_key: false,
     };
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(settings.jwt.secret.as_bytes()),
-    )
+    jwt_keys().sign(&claims, ACCESS_TOKEN_AUDIENCE)
 }
 
-#[allow(dead_code)]
-pub fn verify_jwt(token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
-    let settings = get_settings();
+pub fn verify_jwt(token: &str) -> Result<Claims, Error> {
+    jwt_keys().verify(token, ACCESS_TOKEN_AUDIENCE)
+}
 
-    let token_data = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(settings.jwt.secret.as_ref()),
-        &Validation::default(),
-    )?;
+pub fn calculate_remaining_expiration(token: &str) -> Result<i64, String> {
+    let claims = verify_jwt(token).map_err(|e| format!("Erro ao decodificar token: {}", e))?;
 
-    Ok(token_data.claims)
+    let exp_timestamp = claims.exp as i64;
+    let now_timestamp = Utc::now().timestamp();
+
+    Ok(exp_timestamp.saturating_sub(now_timestamp))
 }
 
-pub fn decode_token(token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
-    let settings = get_settings();
-    let token_data: TokenData<Claims> = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(settings.jwt.secret.as_bytes()),
-        &Validation::new(Algorithm::HS256),
-    )?;
+/// Gera o token de desafio de curta duração emitido quando o login exige o segundo fator
+pub fn generate_mfa_challenge(user_id: &str, expires_in: u64) -> Result<String, Error> {
+    let exp = Utc::now()
+        .checked_add_signed(Duration::seconds(expires_in as i64))
+        .expect("Erro ao calcular expiração do token")
+        .timestamp() as usize;
 
-    Ok(token_data.claims)
+    let claims = MfaChallengeClaims {
+        sub: user_id.to_string(),
+        exp,
+        jti: Uuid::new_v4().to_string(),
+    };
+
+    jwt_keys().sign(&claims, MFA_CHALLENGE_AUDIENCE)
 }
 
-pub fn calculate_remaining_expiration(token: &str) -> Result<i64, String> {
-    let settings = get_settings();
+pub fn decode_mfa_challenge(token: &str) -> Result<MfaChallengeClaims, String> {
+    jwt_keys()
+        .verify(token, MFA_CHALLENGE_AUDIENCE)
+        .map_err(|e| format!("Erro ao decodificar token: {}", e))
+}
 
-    let token_data: TokenData<Claims> = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(settings.jwt.secret.as_bytes()),
-        &Validation::new(Algorithm::HS256),
-    )
-    .map_err(|e| format!("Erro ao decodificar token: {}", e))?;
+/// Gera o token dos links de descadastro, que funcionam sem login
+pub fn generate_unsubscribe_token(user_id: &str, category: &str) -> Result<String, Error> {
+    let exp = Utc::now()
+        .checked_add_signed(Duration::seconds(UNSUBSCRIBE_TOKEN_EXPIRES_IN as i64))
+        .expect("Erro ao calcular expiração do token")
+        .timestamp() as usize;
 
-    let exp_timestamp = token_data.claims.exp as i64;
-    let now_timestamp = Utc::now().timestamp();
+    let claims = UnsubscribeClaims {
+        sub: user_id.to_string(),
+        exp,
+        category: category.to_string(),
+    };
 
-    Ok(exp_timestamp.saturating_sub(now_timestamp))
+    jwt_keys().sign(&claims, UNSUBSCRIBE_AUDIENCE)
+}
+
+pub fn decode_unsubscribe_token(token: &str) -> Result<UnsubscribeClaims, String> {
+    jwt_keys()
+        .verify(token, UNSUBSCRIBE_AUDIENCE)
+        .map_err(|e| format!("Erro ao decodificar token: {}", e))
 }
@@ -1,7 +1,16 @@
+pub mod avatar_image;
+pub mod client_ip;
+pub mod crypto;
+pub mod document;
 pub mod formatter;
 pub mod jwt;
 pub mod logging;
 pub mod pagination;
+pub mod password;
+pub mod password_policy;
+pub mod phone;
+pub mod totp;
+pub mod username;
 pub mod validation;
 
 pub use logging::*;
@@ -17,10 +17,10 @@ pub struct PaginationParams {
     pub offset: i64,
 }
 
-fn default_limit() -> i64 {
+pub fn default_limit() -> i64 {
     10
 }
 
-fn default_offset() -> i64 {
+pub fn default_offset() -> i64 {
     0
 }
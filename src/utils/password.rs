@@ -0,0 +1,118 @@
+use crate::config::{PasswordSettings, get_settings};
+use crate::errors::app_error::AppError;
+use argon2::password_hash::{
+    PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng,
+};
+use argon2::{Algorithm, Argon2, Params, Version};
+use tracing::error;
+
+/// Resultado da verificação de uma senha contra o hash guardado
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordMatch {
+    Invalid,
+    Valid,
+    /// Senha correta, mas o hash é bcrypt ou usa parâmetros antigos do Argon2id
+    ValidNeedsRehash,
+}
+
+impl PasswordMatch {
+    pub fn is_valid(&self) -> bool {
+        !matches!(self, PasswordMatch::Invalid)
+    }
+}
+
+fn argon2(settings: &PasswordSettings) -> Result<Argon2<'static>, AppError> {
+    let params = Params::new(
+        settings.argon2_memory_kib,
+        settings.argon2_iterations,
+        settings.argon2_parallelism,
+        None,
+    )
+    .map_err(|e| {
+        error!(error = %e, "Parâmetros do Argon2id inválidos");
+        AppError::InternalError(Some("Erro ao hashear senha".into()))
+    })?;
+
+    Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+}
+
+/// Gera o hash Argon2id (formato PHC). Custoso: use `hash_password` no runtime async.
+pub fn hash_password_blocking(
+    password: &str,
+    settings: &PasswordSettings,
+) -> Result<String, AppError> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    argon2(settings)?
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| {
+            error!(error = %e, "Erro ao hashear senha");
+            AppError::InternalError(Some("Erro ao hashear senha".into()))
+        })
+}
+
+/// Aceita hashes Argon2id e os bcrypt gravados antes da migração para o Argon2id
+pub fn verify_password_blocking(
+    password: &str,
+    hash: &str,
+    settings: &PasswordSettings,
+) -> PasswordMatch {
+    if hash.starts_with("$2") {
+        return match bcrypt::verify(password, hash) {
+            Ok(true) => PasswordMatch::ValidNeedsRehash,
+            _ => PasswordMatch::Invalid,
+        };
+    }
+
+    let Ok(parsed) = PasswordHash::new(hash) else {
+        return PasswordMatch::Invalid;
+    };
+    let Ok(hasher) = argon2(settings) else {
+        return PasswordMatch::Invalid;
+    };
+    if hasher
+        .verify_password(password.as_bytes(), &parsed)
+        .is_err()
+    {
+        return PasswordMatch::Invalid;
+    }
+
+    let current = Params::try_from(&parsed).ok();
+    let up_to_date = parsed.algorithm == Algorithm::Argon2id.ident()
+        && current.is_some_and(|params| {
+            params.m_cost() == settings.argon2_memory_kib
+                && params.t_cost() == settings.argon2_iterations
+                && params.p_cost() == settings.argon2_parallelism
+        });
+
+    if up_to_date {
+        PasswordMatch::Valid
+    } else {
+        PasswordMatch::ValidNeedsRehash
+    }
+}
+
+/// Gera o hash em uma thread de bloqueio, sem travar o runtime
+pub async fn hash_password(password: &str) -> Result<String, AppError> {
+    let password = password.to_string();
+    let settings = get_settings().password.clone();
+
+    tokio::task::spawn_blocking(move || hash_password_blocking(&password, &settings))
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Erro ao hashear senha");
+            AppError::InternalError(Some("Erro ao hashear senha".into()))
+        })?
+}
+
+/// Verifica a senha em uma thread de bloqueio, sem travar o runtime
+pub async fn verify_password(password: &str, hash: &str) -> PasswordMatch {
+    let password = password.to_string();
+    let hash = hash.to_string();
+    let settings = get_settings().password.clone();
+
+    tokio::task::spawn_blocking(move || verify_password_blocking(&password, &hash, &settings))
+        .await
+        .unwrap_or(PasswordMatch::Invalid)
+}
@@ -0,0 +1,51 @@
+use crate::config::PasswordSettings;
+use lazy_static::lazy_static;
+use std::collections::HashSet;
+
+lazy_static! {
+    static ref COMMON_PASSWORDS: HashSet<&'static str> =
+        include_str!("../data/common_passwords.txt")
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+}
+
+/// Regras da política que a senha não cumpre; vazio quando a senha é aceita
+pub fn password_violations(password: &str, settings: &PasswordSettings) -> Vec<String> {
+    let mut violations = Vec::new();
+    let length = password.chars().count();
+
+    if length < settings.min_length {
+        violations.push(format!(
+            "A senha deve ter pelo menos {} caracteres",
+            settings.min_length
+        ));
+    }
+    if length > settings.max_length {
+        violations.push(format!(
+            "A senha deve ter no máximo {} caracteres",
+            settings.max_length
+        ));
+    }
+    if settings.require_letter && !password.chars().any(char::is_alphabetic) {
+        violations.push("A senha deve conter pelo menos uma letra".into());
+    }
+    if settings.require_uppercase && !password.chars().any(char::is_uppercase) {
+        violations.push("A senha deve conter pelo menos uma letra maiúscula".into());
+    }
+    if settings.require_lowercase && !password.chars().any(char::is_lowercase) {
+        violations.push("A senha deve conter pelo menos uma letra minúscula".into());
+    }
+    if settings.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+        violations.push("A senha deve conter pelo menos um número".into());
+    }
+    if settings.require_symbol && password.chars().all(char::is_alphanumeric) {
+        violations.push("A senha deve conter pelo menos um símbolo".into());
+    }
+    if settings.deny_common && COMMON_PASSWORDS.contains(password.to_lowercase().as_str()) {
+        violations.push("A senha é muito comum".into());
+    }
+
+    violations
+}
@@ -0,0 +1,17 @@
+use phonenumber::Mode;
+use phonenumber::country::Id;
+
+/// Interpreta o telefone e devolve em E.164 (`+5511987654321`). Números sem DDI são lidos
+/// como da região padrão (`PHONE_DEFAULT_REGION`, código ISO como `BR`).
+pub fn normalize_phone(value: &str, default_region: &str) -> Option<String> {
+    let region = default_region.parse::<Id>().ok();
+    let number = phonenumber::parse(region, value).ok()?;
+    if !phonenumber::is_valid(&number) {
+        return None;
+    }
+    Some(number.format().mode(Mode::E164).to_string())
+}
+
+pub fn is_valid_region(region: &str) -> bool {
+    region.parse::<Id>().is_ok()
+}
@@ -0,0 +1,62 @@
+use crate::config::get_settings;
+use chrono::Utc;
+use rand::RngCore;
+use totp_rs::{Algorithm, Secret, TOTP};
+
+/// Duração de cada janela do TOTP, em segundos
+pub const TOTP_STEP: u64 = 30;
+const TOTP_DIGITS: usize = 6;
+/// Janelas aceitas antes/depois da atual, para tolerar relógios dessincronizados
+const TOTP_SKEW: i64 = 1;
+
+fn build_totp(secret: &str, account_name: &str) -> Result<TOTP, String> {
+    let bytes = Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .map_err(|e| format!("Segredo TOTP inválido: {:?}", e))?;
+
+    TOTP::new(
+        Algorithm::SHA1,
+        TOTP_DIGITS,
+        1,
+        TOTP_STEP,
+        bytes,
+        Some(get_settings().mfa.issuer.clone()),
+        account_name.to_string(),
+    )
+    .map_err(|e| format!("Erro ao montar TOTP: {}", e))
+}
+
+/// Gera um novo segredo aleatório (160 bits) codificado em base32
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 20];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    Secret::Raw(bytes.to_vec()).to_encoded().to_string()
+}
+
+/// Monta a URI `otpauth://` usada pelos aplicativos autenticadores
+pub fn otpauth_uri(secret: &str, account_name: &str) -> Result<String, String> {
+    Ok(build_totp(secret, account_name)?.get_url())
+}
+
+/// Gera o código TOTP para o instante informado (segundos desde a época Unix)
+pub fn generate_code(secret: &str, time: u64) -> Result<String, String> {
+    Ok(build_totp(secret, "")?.generate(time))
+}
+
+/// Verifica o código no instante informado e retorna a janela (step) em que ele é válido.
+/// O chamador deve persistir a janela para impedir a reutilização do mesmo código.
+pub fn verify_code(secret: &str, code: &str, time: u64) -> Option<u64> {
+    let totp = build_totp(secret, "").ok()?;
+    let current_step = (time / TOTP_STEP) as i64;
+
+    (-TOTP_SKEW..=TOTP_SKEW)
+        .map(|offset| current_step + offset)
+        .filter(|step| *step >= 0)
+        .find(|step| totp.generate(*step as u64 * TOTP_STEP) == code)
+        .map(|step| step as u64)
+}
+
+/// Instante atual em segundos desde a época Unix
+pub fn now() -> u64 {
+    Utc::now().timestamp() as u64
+}
@@ -0,0 +1,47 @@
+use lazy_static::lazy_static;
+use std::collections::HashSet;
+
+pub const MIN_LENGTH: usize = 3;
+pub const MAX_LENGTH: usize = 30;
+
+lazy_static! {
+    static ref RESERVED_USERNAMES: HashSet<&'static str> =
+        include_str!("../data/reserved_usernames.txt")
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+}
+
+/// Usernames são gravados e comparados em minúsculas; um `@` inicial é ignorado
+pub fn normalize_username(value: &str) -> String {
+    value.trim().trim_start_matches('@').to_lowercase()
+}
+
+pub fn is_reserved_username(username: &str) -> bool {
+    RESERVED_USERNAMES.contains(username)
+}
+
+/// Motivo pelo qual o username (já normalizado) é recusado; `None` quando é aceito
+pub fn username_violation(username: &str) -> Option<String> {
+    let length = username.chars().count();
+    if !(MIN_LENGTH..=MAX_LENGTH).contains(&length) {
+        return Some(format!(
+            "O nome de usuário deve ter entre {} e {} caracteres",
+            MIN_LENGTH, MAX_LENGTH
+        ));
+    }
+    if !username
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Some("Use apenas letras sem acento, números e '_' no nome de usuário".into());
+    }
+    if username.starts_with('_') || username.ends_with('_') {
+        return Some("O nome de usuário não pode começar nem terminar com '_'".into());
+    }
+    if is_reserved_username(username) {
+        return Some("Nome de usuário reservado".into());
+    }
+    None
+}
@@ -1,3 +1,8 @@
+use crate::config::get_settings;
+use crate::models::profile::languages;
+use crate::utils::document::parse_document;
+use crate::utils::password_policy::password_violations;
+use crate::utils::phone::normalize_phone;
 use lazy_static::lazy_static;
 use regex::Regex;
 use validator::ValidationError;
@@ -5,9 +10,6 @@ use validator::ValidationError;
 lazy_static! {
     static ref EMAIL_REGEX: Regex =
         Regex::new(r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$").unwrap();
-    static ref PASSWORD_REGEX: Regex = Regex::new(r"[A-Za-z\d@$!%*#?&]{8,}").unwrap();
-    static ref PHONE_REGEX: Regex = Regex::new(r"^\+?[1-9]\d{1,14}$").unwrap();
-    static ref DOCUMENT_REGEX: Regex = Regex::new(r"^\d{3}\.\d{3}\.\d{3}-\d{2}$").unwrap();
 }
 
 pub fn validate_email(email: &str) -> Result<(), ValidationError> {
@@ -19,32 +21,20 @@ pub fn validate_email(email: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
+/// Aplica a política de senhas configurada (`PASSWORD_*`)
 pub fn validate_password(password: &str) -> Result<(), ValidationError> {
-    if !PASSWORD_REGEX.is_match(password) {
+    let violations = password_violations(password, &get_settings().password);
+    if !violations.is_empty() {
         let mut err = ValidationError::new("password_validation");
-        err.message = Some("A senha deve ter pelo menos 8 caracteres e conter letras e números".into());
+        err.message = Some(violations.join("; ").into());
         return Err(err);
     }
-
-    // Verifica se contém pelo menos uma letra
-    if !password.chars().any(|c| c.is_alphabetic()) {
-        let mut err = ValidationError::new("password_validation");
-        err.message = Some("A senha deve conter pelo menos uma letra".into());
-        return Err(err);
-    }
-
-    // Verifica se contém pelo menos um número
-    if !password.chars().any(|c| c.is_numeric()) {
-        let mut err = ValidationError::new("password_validation");
-        err.message = Some("A senha deve conter pelo menos um número".into());
-        return Err(err);
-    }
-
     Ok(())
 }
 
+/// Telefone com ou sem pontuação; sem DDI é lido como da região `PHONE_DEFAULT_REGION`
 pub fn validate_phone(phone: &str) -> Result<(), ValidationError> {
-    if !PHONE_REGEX.is_match(phone) {
+    if normalize_phone(phone, &get_settings().phone.default_region).is_none() {
         let mut err = ValidationError::new("invalid_phone");
         err.message = Some("Número de telefone inválido".into());
         return Err(err);
@@ -52,20 +42,32 @@ pub fn validate_phone(phone: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
+/// CPF ou CNPJ, com ou sem pontuação, conferindo os dígitos verificadores
 pub fn validate_document(document: &str) -> Result<(), ValidationError> {
-    if !DOCUMENT_REGEX.is_match(document) {
+    if parse_document(document).is_none() {
         let mut err = ValidationError::new("invalid_document");
-        err.message = Some("CPF inválido. Use o formato: 000.000.000-00".into());
+        err.message = Some("Documento inválido: informe um CPF ou CNPJ válido".into());
         return Err(err);
     }
     Ok(())
 }
 
 pub fn validate_birth_date(birth_date: &str) -> Result<(), ValidationError> {
-    if let Err(_) = chrono::NaiveDate::parse_from_str(birth_date, "%Y-%m-%d") {
+    if chrono::NaiveDate::parse_from_str(birth_date, "%Y-%m-%d").is_err() {
         let mut err = ValidationError::new("invalid_birth_date");
         err.message = Some("Data de nascimento inválida. Use o formato: YYYY-MM-DD".into());
         return Err(err);
     }
     Ok(())
 }
+
+pub fn validate_language(language: &str) -> Result<(), ValidationError> {
+    if !languages::SUPPORTED.contains(&language) {
+        let mut err = ValidationError::new("invalid_language");
+        err.message = Some(
+            format!("Idioma inválido. Use um de: {}", languages::SUPPORTED.join(", ")).into(),
+        );
+        return Err(err);
+    }
+    Ok(())
+}
@@ -1,14 +1,15 @@
 use super::session::WsSession;
 use crate::config::app_state::AppState;
-use crate::models::auth::Claims;
-use crate::utils::jwt::decode_token;
+use crate::middleware::rate_limit_middleware::{RateLimitGroup, RateLimiter};
+use crate::models::auth::{Claims, protected_actions};
+use crate::services::{account_policy_service, auth_service};
 // use crate::websocket::server::WsServer;
 // use actix::Addr;
 use actix_web::{Error, HttpRequest, HttpResponse, get, web};
 use actix_web_actors::ws;
 use uuid::Uuid;
 
-#[get("/ws/")]
+#[get("/ws/", wrap = "RateLimiter::new(RateLimitGroup::Public)")]
 pub async fn websocket_entry(
     req: HttpRequest,
     stream: web::Payload,
@@ -34,8 +35,8 @@ pub async fn websocket_entry(
 
     println!("token: {}", token);
 
-    // Decodificar token
-    let claims: Claims = match decode_token(&token) {
+    // Decodificar token e conferir a lista de revogação
+    let claims: Claims = match auth_service::validate_access_token(&token, &state.db).await {
         Ok(c) => c,
         Err(_) => return Ok(HttpResponse::Unauthorized().body("Token inválido")),
     };
@@ -45,6 +46,13 @@ pub async fn websocket_entry(
         Err(_) => return Ok(HttpResponse::Unauthorized().body("ID inválido no token")),
     };
 
+    account_policy_service::ensure_email_confirmed(
+        user_id,
+        protected_actions::WEBSOCKET,
+        &state.db,
+    )
+    .await?;
+
     ws::start(
         WsSession {
             user_id,
@@ -48,17 +48,23 @@ impl WsServer {
 
     pub fn send_message_to_user(&self, user_id: Uuid, message: String) {
         if let Some(recipient) = self.sessions.get(&user_id) {
-            let _ = recipient.do_send(WsMessage(message.clone()));
+            recipient.do_send(WsMessage(message.clone()));
         }
     }
 
     pub fn broadcast(&self, message: String) {
         for recipient in self.sessions.values() {
-            let _ = recipient.do_send(WsMessage(message.clone()));
+            recipient.do_send(WsMessage(message.clone()));
         }
     }
 }
 
+impl Default for WsServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Actor for WsServer {
     type Context = Context<Self>;
 }
@@ -88,7 +94,7 @@ impl Handler<UserMessage> for WsServer {
 
     fn handle(&mut self, msg: UserMessage, _ctx: &mut Self::Context) {
         if let Some(recipient) = self.sessions.get(&msg.user_id) {
-            let _ = recipient.do_send(WsMessage(msg.message.clone()));
+            recipient.do_send(WsMessage(msg.message.clone()));
         }
     }
 }
@@ -98,7 +104,7 @@ impl Handler<BroadcastMessage> for WsServer {
 
     fn handle(&mut self, msg: BroadcastMessage, _ctx: &mut Self::Context) {
         for recipient in self.sessions.values() {
-            let _ = recipient.do_send(WsMessage(msg.0.clone()));
+            recipient.do_send(WsMessage(msg.0.clone()));
         }
     }
 }
@@ -1,40 +1,62 @@
-use actix_web::{App, http::StatusCode, test};
-use rust_usecases::models::user::UserRequest;
-use rust_usecases::routes::configure::api_v1_scope;
-use rust_usecases::config::app_state::AppState;
-use rust_usecases::config::init_settings;
-use rust_usecases::db::test_db::setup_test_db;
-use rust_usecases::db::mongo::init_mongodb;
-use rust_usecases::db::elasticsearch::get_elastic_client;
-use rust_usecases::websocket::server::WsServer;
-use std::sync::Arc;
-use actix::Actor;
-use std::sync::Once;
-
-static INIT: Once = Once::new();
+mod common;
 
-fn init() {
-    INIT.call_once(|| {
-        dotenvy::dotenv().ok();
-        init_settings().expect("Falha ao inicializar settings");
-    });
-}
+use actix_web::{App, HttpResponse, HttpServer, http::StatusCode, test, web};
+use common::{init, setup_app_state, setup_app_state_with};
+use rust_usecases::{
+    config::{JwtAlgorithm, OidcProviderSettings, PasswordSettings, RateLimitRule, S3Settings},
+    errors::app_error::AppError,
+    mailer::{
+        EmailMessage, EmailSender, EmailTemplate, EmailTemplates, FileEmailSender,
+        InMemoryEmailSender,
+    },
+    middleware::rate_limit_middleware::{RateLimitGroup, RateLimiter},
+    models::{
+        auth::{ACCESS_TOKEN_AUDIENCE, Claims, protected_actions},
+        course::Course,
+        email_outbox::email_status,
+        email_preferences::UNSUBSCRIBE_AUDIENCE,
+        identity::OidcCallbackQuery,
+        mfa::MFA_CHALLENGE_AUDIENCE,
+        notification::ObjCodeType,
+        session::SessionInfo,
+        token::token_types,
+        user::UserRequest,
+    },
+    repositories::{
+        email_outbox_repository, identity_repository, notification_repository, profile_repository,
+        role_repository, user_repository,
+    },
+    routes::{configure::api_v1_scope, well_known_routes},
+    services::{
+        account_deletion_service,
+        account_policy_service::ConfirmedEmailPolicy,
+        auth_service,
+        email_outbox_service::{self, RetryPolicy},
+        email_service, oidc_service, profile_service, token_service,
+    },
+    sms::InMemorySmsSender,
+    storage::{BlobStore, InMemoryBlobStore, S3BlobStore},
+    stores::login_attempt_store::{InMemoryLoginAttemptStore, LoginAttemptStore},
+    utils::{
+        client_ip::resolve_client_ip,
+        crypto::sha256_hex,
+        document::{DocumentType, mask_document, parse_document},
+        formatter::generate_username_from_email,
+        jwt::JwtKeys,
+        password_policy::password_violations,
+        phone::normalize_phone,
+        totp,
+        username::username_violation,
+    },
+};
+use sqlx::PgPool;
+use std::sync::Arc;
 
 #[actix_web::test]
 async fn test_create_user_success() {
     init();
 
-    let db = setup_test_db().await;
-    let mongo = init_mongodb().await.unwrap();
-    let es = get_elastic_client().unwrap();
-    let ws_server = WsServer::new().start();
-
-    let app_state = Arc::new(AppState {
-        db,
-        mongo,
-        es,
-        ws_server,
-    });
+    let app_state = setup_app_state().await;
 
     let app = test::init_service(
         App::new()
@@ -65,17 +87,7 @@ async fn test_create_user_success() {
 async fn test_create_user_invalid_email() {
     init();
 
-    let db = setup_test_db().await;
-    let mongo = init_mongodb().await.unwrap();
-    let es = get_elastic_client().unwrap();
-    let ws_server = WsServer::new().start();
-
-    let app_state = Arc::new(AppState {
-        db,
-        mongo,
-        es,
-        ws_server,
-    });
+    let app_state = setup_app_state().await;
 
     let app = test::init_service(
         App::new()
@@ -106,17 +118,7 @@ async fn test_create_user_invalid_email() {
 async fn test_create_user_password_short() {
     init();
 
-    let db = setup_test_db().await;
-    let mongo = init_mongodb().await.unwrap();
-    let es = get_elastic_client().unwrap();
-    let ws_server = WsServer::new().start();
-
-    let app_state = Arc::new(AppState {
-        db,
-        mongo,
-        es,
-        ws_server,
-    });
+    let app_state = setup_app_state().await;
 
     let app = test::init_service(
         App::new()
@@ -147,17 +149,7 @@ async fn test_create_user_password_short() {
 async fn test_login_invalid_credentials() {
     init();
 
-    let db = setup_test_db().await;
-    let mongo = init_mongodb().await.unwrap();
-    let es = get_elastic_client().unwrap();
-    let ws_server = WsServer::new().start();
-
-    let app_state = Arc::new(AppState {
-        db,
-        mongo,
-        es,
-        ws_server,
-    });
+    let app_state = setup_app_state().await;
 
     let app = test::init_service(
         App::new()
@@ -180,3 +172,4047 @@ async fn test_login_invalid_credentials() {
 
     assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
 }
+
+#[actix_web::test]
+async fn test_refresh_token_rotation_and_reuse_detection() {
+    init();
+
+    let app_state = setup_app_state().await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let payload = UserRequest {
+        email: "refresh@exemplo.com".to_string(),
+        password: "Senha123456".to_string(),
+        first_name: "Refresh".to_string(),
+        last_name: "User".to_string(),
+        profile: None,
+    };
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(&payload)
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let first_refresh = body["refresh_token"].as_str().unwrap().to_string();
+
+    // Rotação: o refresh token gera um novo par
+    let req = test::TestRequest::post()
+        .uri("/api/v1/token/refresh/")
+        .set_json(serde_json::json!({ "refresh_token": first_refresh }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let second_token = body["token"].as_str().unwrap().to_string();
+    assert_ne!(body["refresh_token"].as_str().unwrap(), first_refresh);
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/")
+        .insert_header(("Authorization", format!("Token {}", second_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Reuso do refresh token antigo revoga toda a família
+    let req = test::TestRequest::post()
+        .uri("/api/v1/token/refresh/")
+        .set_json(serde_json::json!({ "refresh_token": first_refresh }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/")
+        .insert_header(("Authorization", format!("Token {}", second_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // A limpeza remove refresh tokens expirados e revogações fora da folga do `exp`
+    let user_id: uuid::Uuid = sqlx::query_scalar("SELECT id FROM users WHERE email = $1")
+        .bind(&payload.email)
+        .fetch_one(&app_state.db)
+        .await
+        .unwrap();
+    sqlx::query(
+        "UPDATE refresh_tokens SET expires_at = NOW() - INTERVAL '1 day' WHERE token_hash = $1",
+    )
+    .bind(sha256_hex(&first_refresh))
+    .execute(&app_state.db)
+    .await
+    .unwrap();
+    sqlx::query(
+        "UPDATE revoked_tokens SET expires_at = NOW() - INTERVAL '10 seconds' WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .execute(&app_state.db)
+    .await
+    .unwrap();
+
+    let purged = auth_service::purge_expired_tokens(&app_state.db)
+        .await
+        .unwrap();
+    assert!(purged >= 1);
+    let refresh_hashes: Vec<String> =
+        sqlx::query_scalar("SELECT token_hash FROM refresh_tokens WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_all(&app_state.db)
+            .await
+            .unwrap();
+    assert_eq!(refresh_hashes.len(), 1);
+    assert!(!refresh_hashes.contains(&sha256_hex(&first_refresh)));
+    let revoked: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM revoked_tokens WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_one(&app_state.db)
+        .await
+        .unwrap();
+    assert!(revoked >= 1);
+
+    sqlx::query(
+        "UPDATE revoked_tokens SET expires_at = NOW() - INTERVAL '1 day' WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .execute(&app_state.db)
+    .await
+    .unwrap();
+    auth_service::purge_expired_tokens(&app_state.db)
+        .await
+        .unwrap();
+    let revoked: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM revoked_tokens WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_one(&app_state.db)
+        .await
+        .unwrap();
+    assert_eq!(revoked, 0);
+}
+
+#[actix_web::test]
+async fn test_logout_ends_session() {
+    init();
+
+    let app_state = setup_app_state().await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let payload = UserRequest {
+        email: "logout@exemplo.com".to_string(),
+        password: "Senha123456".to_string(),
+        first_name: "Logout".to_string(),
+        last_name: "User".to_string(),
+        profile: None,
+    };
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .insert_header(("User-Agent", "api-tests"))
+        .set_json(&payload)
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let token = body["token"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/sessions/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let sessions: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(sessions.as_array().unwrap().len(), 1);
+    assert_eq!(sessions[0]["current"], true);
+    assert_eq!(sessions[0]["user_agent"], "api-tests");
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/logout/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_signup_ignores_privileged_fields() {
+    init();
+
+    let app_state = setup_app_state().await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let payload = serde_json::json!({
+        "email": "escalada@exemplo.com",
+        "password": "Senha123456",
+        "first_name": "Escalada",
+        "last_name": "User",
+        "profile": {
+            "access_level": "admin",
+            "confirm_email": true,
+            "unsubscribe": true
+        }
+    });
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(&payload)
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["user"]["profile"]["access_level"], "user");
+    assert_eq!(body["user"]["profile"]["confirm_email"], false);
+    assert_eq!(body["user"]["profile"]["unsubscribe"], false);
+    let token = body["token"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/admin/roles/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/admin/users/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({
+            "email": "provisionado@exemplo.com",
+            "password": "Senha123456",
+            "first_name": "Provisionado",
+            "last_name": "User",
+            "roles": ["admin"]
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[actix_web::test]
+async fn test_admin_provisions_user_with_privileged_fields() {
+    init();
+
+    let app_state = setup_app_state().await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let payload = UserRequest {
+        email: "admin.provisiona@exemplo.com".to_string(),
+        password: "Senha123456".to_string(),
+        first_name: "Admin".to_string(),
+        last_name: "User".to_string(),
+        profile: None,
+    };
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(&payload)
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let admin_id: uuid::Uuid = body["user"]["id"].as_str().unwrap().parse().unwrap();
+
+    role_repository::assign_role(admin_id, "admin", &app_state.db)
+        .await
+        .unwrap();
+
+    // As permissões são embutidas no token, então é preciso autenticar novamente
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({
+            "email": payload.email,
+            "password": payload.password
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let token = body["token"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/admin/users/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({
+            "email": "provisionado.admin@exemplo.com",
+            "password": "Senha123456",
+            "first_name": "Provisionado",
+            "last_name": "User",
+            "confirm_email": true,
+            "roles": ["user", "admin"]
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["profile"]["confirm_email"], true);
+
+    let provisioned_id: uuid::Uuid = body["id"].as_str().unwrap().parse().unwrap();
+    let roles = role_repository::find_role_names_by_user(provisioned_id, &app_state.db)
+        .await
+        .unwrap();
+    assert!(roles.contains(&"admin".to_string()));
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/admin/users/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({
+            "email": "papel.invalido@exemplo.com",
+            "password": "Senha123456",
+            "first_name": "Papel",
+            "last_name": "Invalido",
+            "roles": ["superuser"]
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // `access_level` não concede permissões; em vez de ignorá-lo, o provisionamento recusa
+    let req = test::TestRequest::post()
+        .uri("/api/v1/admin/users/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({
+            "email": "nivel.acesso@exemplo.com",
+            "password": "Senha123456",
+            "first_name": "Nivel",
+            "last_name": "Acesso",
+            "access_level": "admin"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE email = $1)")
+        .bind("nivel.acesso@exemplo.com")
+        .fetch_one(&app_state.db)
+        .await
+        .unwrap();
+    assert!(!exists);
+
+    // Remover um papel derruba na hora o token que ainda carrega as permissões antigas
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({
+            "email": "provisionado.admin@exemplo.com",
+            "password": "Senha123456"
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let demoted_token = body["token"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/admin/roles/")
+        .insert_header(("Authorization", format!("Token {}", demoted_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!(
+            "/api/v1/admin/users/{}/roles/admin/",
+            provisioned_id
+        ))
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/admin/roles/")
+        .insert_header(("Authorization", format!("Token {}", demoted_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // Atribuir um papel também encerra as sessões abertas
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({
+            "email": "provisionado.admin@exemplo.com",
+            "password": "Senha123456"
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let promoted_token = body["token"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/admin/users/{}/roles/", provisioned_id))
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({ "role": "admin" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/")
+        .insert_header(("Authorization", format!("Token {}", promoted_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_totp_rfc6238_vectors_with_fixed_clock() {
+    init();
+
+    // Segredo ASCII "12345678901234567890" do apêndice B da RFC 6238, em base32
+    let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    assert_eq!(totp::generate_code(secret, 59).unwrap(), "287082");
+    assert_eq!(totp::generate_code(secret, 1111111109).unwrap(), "081804");
+    assert_eq!(totp::generate_code(secret, 1234567890).unwrap(), "005924");
+    assert_eq!(totp::generate_code(secret, 2000000000).unwrap(), "279037");
+
+    // Aceita uma janela de tolerância e rejeita códigos fora dela
+    assert_eq!(totp::verify_code(secret, "287082", 59), Some(1));
+    assert_eq!(totp::verify_code(secret, "287082", 89), Some(1));
+    assert_eq!(totp::verify_code(secret, "287082", 120), None);
+    assert_eq!(totp::verify_code(secret, "000000", 59), None);
+}
+
+#[actix_web::test]
+async fn test_totp_two_step_login() {
+    init();
+
+    let app_state = setup_app_state().await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let payload = UserRequest {
+        email: "totp@exemplo.com".to_string(),
+        password: "Senha123456".to_string(),
+        first_name: "Totp".to_string(),
+        last_name: "User".to_string(),
+        profile: None,
+    };
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(&payload)
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let token = body["token"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/me/mfa/totp/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let secret = body["secret"].as_str().unwrap().to_string();
+    assert!(
+        body["otpauth_uri"]
+            .as_str()
+            .unwrap()
+            .starts_with("otpauth://totp/")
+    );
+
+    let code = totp::generate_code(&secret, totp::now()).unwrap();
+    let req = test::TestRequest::post()
+        .uri("/api/v1/me/mfa/totp/verify/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({ "code": code }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let recovery_codes = body["recovery_codes"].as_array().unwrap().clone();
+    assert_eq!(recovery_codes.len(), 10);
+
+    // Primeira etapa: a senha correta só devolve o desafio
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({
+            "email": payload.email,
+            "password": payload.password
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["mfa_required"], true);
+    assert!(body.get("token").is_none());
+    let mfa_token = body["mfa_token"].as_str().unwrap().to_string();
+
+    // O desafio não serve como access token
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/")
+        .insert_header(("Authorization", format!("Token {}", mfa_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // O mesmo código TOTP não pode ser reutilizado
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/mfa/")
+        .set_json(serde_json::json!({ "mfa_token": mfa_token, "code": code }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let recovery_code = recovery_codes[0].as_str().unwrap();
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/mfa/")
+        .set_json(serde_json::json!({ "mfa_token": mfa_token, "code": recovery_code }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert!(body["token"].as_str().is_some());
+
+    // Códigos de recuperação são de uso único
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/mfa/")
+        .set_json(serde_json::json!({ "mfa_token": mfa_token, "code": recovery_code }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // O desafio já abriu uma sessão e não serve para outra, mesmo com um código válido
+    let mfa_login = |mfa_token: &str, code: &str| {
+        test::TestRequest::post()
+            .uri("/api/v1/login/mfa/")
+            .set_json(serde_json::json!({ "mfa_token": mfa_token, "code": code }))
+            .to_request()
+    };
+    let resp = test::call_service(
+        &app,
+        mfa_login(&mfa_token, recovery_codes[1].as_str().unwrap()),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // Depois de 3 códigos errados o desafio é encerrado
+    let login = || {
+        test::TestRequest::post()
+            .uri("/api/v1/login/")
+            .set_json(serde_json::json!({
+                "email": payload.email,
+                "password": payload.password
+            }))
+            .to_request()
+    };
+    let body: serde_json::Value = test::call_and_read_body_json(&app, login()).await;
+    let mfa_token = body["mfa_token"].as_str().unwrap().to_string();
+    for _ in 0..3 {
+        let resp = test::call_service(&app, mfa_login(&mfa_token, "000000")).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+    let resp = test::call_service(
+        &app,
+        mfa_login(&mfa_token, recovery_codes[1].as_str().unwrap()),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // Um novo desafio continua funcionando com o código que não foi gasto
+    let body: serde_json::Value = test::call_and_read_body_json(&app, login()).await;
+    let mfa_token = body["mfa_token"].as_str().unwrap().to_string();
+    let resp = test::call_service(
+        &app,
+        mfa_login(&mfa_token, recovery_codes[1].as_str().unwrap()),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_login_lockout_and_admin_unlock() {
+    init();
+
+    let app_state = setup_app_state().await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let payload = UserRequest {
+        email: "bloqueio@exemplo.com".to_string(),
+        password: "Senha123456".to_string(),
+        first_name: "Bloqueio".to_string(),
+        last_name: "User".to_string(),
+        profile: None,
+    };
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(&payload)
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let user_id = body["user"]["id"].as_str().unwrap().to_string();
+
+    // Com o limite padrão de 5 falhas, a quinta já bloqueia a conta
+    for attempt in 1..=5 {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/login/")
+            .set_json(serde_json::json!({
+                "email": payload.email,
+                "password": "SenhaErrada123"
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        if attempt < 5 {
+            assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        } else {
+            assert_eq!(resp.status(), StatusCode::LOCKED);
+        }
+    }
+
+    // Mesmo com a senha correta a conta continua bloqueada
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({
+            "email": payload.email,
+            "password": payload.password
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::LOCKED);
+    assert!(resp.headers().contains_key("Retry-After"));
+
+    let admin = UserRequest {
+        email: "admin.desbloqueio@exemplo.com".to_string(),
+        password: "Senha123456".to_string(),
+        first_name: "Admin".to_string(),
+        last_name: "User".to_string(),
+        profile: None,
+    };
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(&admin)
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let admin_id: uuid::Uuid = body["user"]["id"].as_str().unwrap().parse().unwrap();
+    role_repository::assign_role(admin_id, "admin", &app_state.db)
+        .await
+        .unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({
+            "email": admin.email,
+            "password": admin.password
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let admin_token = body["token"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/admin/users/{}/unlock/", user_id))
+        .insert_header(("Authorization", format!("Token {}", admin_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({
+            "email": payload.email,
+            "password": payload.password
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_login_lockout_per_ip() {
+    init();
+
+    let app_state = setup_app_state().await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let client: std::net::SocketAddr = "203.0.113.7:40000".parse().unwrap();
+    // E-mails diferentes a partir do mesmo IP: o limite por IP (20) é atingido antes do por conta
+    for attempt in 1..=20 {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/login/")
+            .peer_addr(client)
+            .set_json(serde_json::json!({
+                "email": format!("inexistente{}@exemplo.com", attempt),
+                "password": "SenhaErrada123"
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        if attempt < 20 {
+            assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        } else {
+            assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+        }
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .peer_addr(client)
+        // Um X-Forwarded-For forjado não escapa do bloqueio
+        .insert_header(("X-Forwarded-For", "192.0.2.50"))
+        .set_json(serde_json::json!({
+            "email": "outro@exemplo.com",
+            "password": "SenhaErrada123"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(resp.headers().contains_key("Retry-After"));
+
+    // Outro IP não é afetado
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .peer_addr("198.51.100.1:40000".parse().unwrap())
+        .set_json(serde_json::json!({
+            "email": "outro@exemplo.com",
+            "password": "SenhaErrada123"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_in_memory_login_attempts_are_pruned() {
+    let store = InMemoryLoginAttemptStore::new();
+
+    // e-mails aleatórios de tentativas cujas janelas já venceram
+    for n in 0..10_000 {
+        store
+            .register_failure(&format!("login:email:{}@exemplo.com", n), 0)
+            .await
+            .unwrap();
+    }
+    store
+        .lock("login:email:bloqueado@exemplo.com", 600)
+        .await
+        .unwrap();
+    assert_eq!(store.tracked_keys(), 10_001);
+
+    // acima do limite, a próxima gravação descarta as chaves vencidas e mantém as ativas
+    store
+        .register_failure("login:email:ativo@exemplo.com", 600)
+        .await
+        .unwrap();
+    assert_eq!(store.tracked_keys(), 2);
+    assert!(
+        store
+            .lock_remaining("login:email:bloqueado@exemplo.com")
+            .await
+            .unwrap()
+            .is_some()
+    );
+    assert_eq!(
+        store
+            .register_failure("login:email:ativo@exemplo.com", 600)
+            .await
+            .unwrap(),
+        2
+    );
+}
+
+#[actix_web::test]
+async fn test_rate_limiter_token_bucket() {
+    init();
+
+    let app_state = setup_app_state().await;
+
+    // Limite fixo de 3 requisições por minuto, independente das configurações
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(
+                web::scope("/limited")
+                    .wrap(RateLimiter::with_rule(
+                        RateLimitGroup::Public,
+                        RateLimitRule {
+                            requests: 3,
+                            period_seconds: 60,
+                        },
+                    ))
+                    .route("/", web::get().to(HttpResponse::Ok)),
+            )
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let client: std::net::SocketAddr = "203.0.113.10:40000".parse().unwrap();
+    for expected_remaining in ["2", "1", "0"] {
+        let req = test::TestRequest::get()
+            .uri("/limited/")
+            .peer_addr(client)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get("X-RateLimit-Limit").unwrap(), "3");
+        assert_eq!(
+            resp.headers().get("X-RateLimit-Remaining").unwrap(),
+            expected_remaining
+        );
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/limited/")
+        .peer_addr(client)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    // Um token a cada 20 segundos
+    assert_eq!(resp.headers().get("Retry-After").unwrap(), "20");
+    assert_eq!(resp.headers().get("X-RateLimit-Remaining").unwrap(), "0");
+
+    // sem TRUSTED_PROXIES o X-Forwarded-For do cliente não abre um bucket novo
+    let req = test::TestRequest::get()
+        .uri("/limited/")
+        .peer_addr(client)
+        .insert_header(("X-Forwarded-For", "198.51.100.99"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // Cada IP tem o seu próprio bucket
+    let req = test::TestRequest::get()
+        .uri("/limited/")
+        .peer_addr("198.51.100.10:40000".parse().unwrap())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // atrás de um proxy confiável vale o primeiro salto fora da lista
+    let proxies: Vec<ipnet::IpNet> = vec!["10.0.0.0/8".parse().unwrap()];
+    let proxy = Some("10.0.0.5".parse().unwrap());
+    assert_eq!(
+        resolve_client_ip(proxy, Some("1.2.3.4, 203.0.113.7, 10.0.0.9"), &proxies),
+        Some("203.0.113.7".parse().unwrap())
+    );
+    assert_eq!(
+        resolve_client_ip(
+            Some("203.0.113.7".parse().unwrap()),
+            Some("1.2.3.4"),
+            &proxies
+        ),
+        Some("203.0.113.7".parse().unwrap())
+    );
+    assert_eq!(resolve_client_ip(proxy, None, &proxies), proxy);
+    assert_eq!(resolve_client_ip(proxy, Some("lixo"), &proxies), proxy);
+
+    // As rotas da API expõem os headers do grupo configurado
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({
+            "email": "limite@exemplo.com",
+            "password": "Senha123456"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.headers().contains_key("X-RateLimit-Limit"));
+}
+
+/// Provedor OIDC local: o token devolvido é o próprio `code`, e o userinfo responde
+/// conforme esse token
+async fn start_mock_oidc_provider(existing_email: &'static str) -> OidcProviderSettings {
+    let server = HttpServer::new(move || {
+        App::new()
+            .route(
+                "/token",
+                web::post().to(
+                    |form: web::Form<std::collections::HashMap<String, String>>| async move {
+                        if form.get("grant_type").map(String::as_str) != Some("authorization_code")
+                            || !form.contains_key("code_verifier")
+                        {
+                            return HttpResponse::BadRequest().finish();
+                        }
+                        HttpResponse::Ok().json(serde_json::json!({
+                            "access_token": form["code"],
+                            "token_type": "Bearer"
+                        }))
+                    },
+                ),
+            )
+            .route(
+                "/userinfo",
+                web::get().to(move |req: actix_web::HttpRequest| async move {
+                    let token = req
+                        .headers()
+                        .get("Authorization")
+                        .and_then(|h| h.to_str().ok())
+                        .and_then(|h| h.strip_prefix("Bearer "))
+                        .unwrap_or("");
+                    let body = match token {
+                        "existing" => serde_json::json!({
+                            "sub": "sub-existing",
+                            "email": existing_email,
+                            "email_verified": true
+                        }),
+                        "unverified" => serde_json::json!({
+                            "sub": "sub-unverified",
+                            "email": existing_email,
+                            "email_verified": false
+                        }),
+                        "new" => serde_json::json!({
+                            "sub": "sub-new",
+                            "email": "novo.social@exemplo.com",
+                            "email_verified": true,
+                            "given_name": "Novo",
+                            "family_name": "Social"
+                        }),
+                        _ => return HttpResponse::Unauthorized().finish(),
+                    };
+                    HttpResponse::Ok().json(body)
+                }),
+            )
+    })
+    .workers(1)
+    .bind(("127.0.0.1", 0))
+    .unwrap();
+
+    let base_url = format!("http://{}", server.addrs()[0]);
+    actix_web::rt::spawn(server.run());
+
+    OidcProviderSettings {
+        name: "mock".to_string(),
+        client_id: "client-id".to_string(),
+        client_secret: "client-secret".to_string(),
+        authorization_url: format!("{}/authorize", base_url),
+        token_url: format!("{}/token", base_url),
+        userinfo_url: format!("{}/userinfo", base_url),
+        redirect_uri: "http://localhost:8080/api/v1/auth/mock/callback/".to_string(),
+        scopes: "openid email profile".to_string(),
+    }
+}
+
+fn oauth_state_from(authorization_url: &str) -> String {
+    let url = reqwest::Url::parse(authorization_url).unwrap();
+    assert!(
+        url.query_pairs()
+            .any(|(k, v)| k == "code_challenge_method" && v == "S256")
+    );
+    url.query_pairs()
+        .find(|(k, _)| k == "state")
+        .map(|(_, v)| v.to_string())
+        .unwrap()
+}
+
+#[actix_web::test]
+async fn test_oidc_login_links_and_creates_accounts() {
+    init();
+
+    let app_state = setup_app_state().await;
+    let state = web::Data::from(app_state.clone());
+
+    let app = test::init_service(App::new().app_data(state.clone()).service(api_v1_scope())).await;
+
+    let payload = UserRequest {
+        email: "social@exemplo.com".to_string(),
+        password: "Senha123456".to_string(),
+        first_name: "Social".to_string(),
+        last_name: "User".to_string(),
+        profile: None,
+    };
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(&payload)
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let existing_id = body["user"]["id"].as_str().unwrap().to_string();
+
+    // Provedores não configurados não existem
+    let req = test::TestRequest::get()
+        .uri("/api/v1/auth/desconhecido/start/")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let provider = start_mock_oidc_provider("social@exemplo.com").await;
+    let session_info = || SessionInfo {
+        user_agent: Some("oidc-tests".to_string()),
+        ip_address: None,
+    };
+    let callback = |code: &str, oauth_state: &str| OidcCallbackQuery {
+        code: Some(code.to_string()),
+        state: Some(oauth_state.to_string()),
+        error: None,
+    };
+
+    // E-mail verificado pelo provedor vincula a conta existente
+    let url = oidc_service::start_authorization(&provider, &state.db)
+        .await
+        .unwrap();
+    let oauth_state = oauth_state_from(&url);
+    let response = oidc_service::complete_authorization(
+        &provider,
+        callback("existing", &oauth_state),
+        session_info(),
+        &state,
+    )
+    .await
+    .unwrap();
+    let body = serde_json::to_value(&response).unwrap();
+    assert_eq!(body["user"]["id"], existing_id);
+    assert!(body["token"].as_str().is_some());
+
+    // O state é de uso único
+    let replay = oidc_service::complete_authorization(
+        &provider,
+        callback("existing", &oauth_state),
+        session_info(),
+        &state,
+    )
+    .await;
+    assert!(matches!(replay, Err(AppError::Unauthorized(_))));
+
+    // E-mail não verificado não vincula contas existentes
+    let url = oidc_service::start_authorization(&provider, &state.db)
+        .await
+        .unwrap();
+    let result = oidc_service::complete_authorization(
+        &provider,
+        callback("unverified", &oauth_state_from(&url)),
+        session_info(),
+        &state,
+    )
+    .await;
+    assert!(matches!(result, Err(AppError::Conflict(_))));
+
+    // E-mail novo cria a conta já confirmada
+    let url = oidc_service::start_authorization(&provider, &state.db)
+        .await
+        .unwrap();
+    let response = oidc_service::complete_authorization(
+        &provider,
+        callback("new", &oauth_state_from(&url)),
+        session_info(),
+        &state,
+    )
+    .await
+    .unwrap();
+    let body = serde_json::to_value(&response).unwrap();
+    assert_eq!(body["user"]["email"], "novo.social@exemplo.com");
+    assert_eq!(body["user"]["first_name"], "Novo");
+    assert_eq!(body["user"]["profile"]["confirm_email"], true);
+
+    let identity = identity_repository::find_identity("mock", "sub-new", &state.db)
+        .await
+        .unwrap();
+    assert!(identity.is_some());
+}
+
+fn jwt_fixture(name: &str) -> String {
+    std::fs::read_to_string(format!("tests/fixtures/jwt/{}", name)).unwrap()
+}
+
+fn sample_claims() -> Claims {
+    Claims {
+        sub: "user-1".to_string(),
+        exp: (chrono::Utc::now().timestamp() + 300) as usize,
+        roles: vec!["user".to_string()],
+        permissions: vec![],
+        jti: "jti-1".to_string(),
+        sid: "sid-1".to_string(),
+        api_key: false,
+    }
+}
+
+#[actix_web::test]
+async fn test_jwt_rs256_key_rotation() {
+    let old_public = ("2025-01".to_string(), jwt_fixture("rs256-2025-01.pub.pem"));
+    let new_public = ("2025-07".to_string(), jwt_fixture("rs256-2025-07.pub.pem"));
+
+    let old_keys = JwtKeys::asymmetric(
+        JwtAlgorithm::RS256,
+        "2025-01",
+        &jwt_fixture("rs256-2025-01.pem"),
+        std::slice::from_ref(&old_public),
+    )
+    .unwrap();
+    let old_token = old_keys
+        .sign(&sample_claims(), ACCESS_TOKEN_AUDIENCE)
+        .unwrap();
+
+    let header = jsonwebtoken::decode_header(&old_token).unwrap();
+    assert_eq!(header.alg, jsonwebtoken::Algorithm::RS256);
+    assert_eq!(header.kid.as_deref(), Some("2025-01"));
+
+    // durante a rotação a nova chave assina e a anterior segue aceita
+    let rotated_keys = JwtKeys::asymmetric(
+        JwtAlgorithm::RS256,
+        "2025-07",
+        &jwt_fixture("rs256-2025-07.pem"),
+        &[new_public.clone(), old_public],
+    )
+    .unwrap();
+    let new_token = rotated_keys
+        .sign(&sample_claims(), ACCESS_TOKEN_AUDIENCE)
+        .unwrap();
+
+    let claims: Claims = rotated_keys
+        .verify(&old_token, ACCESS_TOKEN_AUDIENCE)
+        .unwrap();
+    assert_eq!(claims.sub, "user-1");
+    assert!(
+        rotated_keys
+            .verify::<Claims>(&new_token, ACCESS_TOKEN_AUDIENCE)
+            .is_ok()
+    );
+    assert_eq!(rotated_keys.jwks().keys.len(), 2);
+
+    // removida a chave anterior, os tokens dela deixam de valer
+    let final_keys = JwtKeys::asymmetric(
+        JwtAlgorithm::RS256,
+        "2025-07",
+        &jwt_fixture("rs256-2025-07.pem"),
+        &[new_public],
+    )
+    .unwrap();
+    assert!(
+        final_keys
+            .verify::<Claims>(&old_token, ACCESS_TOKEN_AUDIENCE)
+            .is_err()
+    );
+    assert!(
+        final_keys
+            .verify::<Claims>(&new_token, ACCESS_TOKEN_AUDIENCE)
+            .is_ok()
+    );
+
+    // token HS256 não pode ser aceito por um verificador RS256
+    let hmac_token = JwtKeys::hmac("2025-07", "segredo")
+        .sign(&sample_claims(), ACCESS_TOKEN_AUDIENCE)
+        .unwrap();
+    assert!(
+        final_keys
+            .verify::<Claims>(&hmac_token, ACCESS_TOKEN_AUDIENCE)
+            .is_err()
+    );
+
+    let jwk = &final_keys.jwks().keys[0];
+    assert_eq!(jwk.common.key_id.as_deref(), Some("2025-07"));
+    assert!(jsonwebtoken::DecodingKey::from_jwk(jwk).is_ok());
+}
+
+#[actix_web::test]
+async fn test_jwt_eddsa_and_jwks_endpoint() {
+    init();
+
+    let keys = JwtKeys::asymmetric(
+        JwtAlgorithm::EdDSA,
+        "ed-2025-01",
+        &jwt_fixture("eddsa-2025-01.pem"),
+        &[(
+            "ed-2025-01".to_string(),
+            jwt_fixture("eddsa-2025-01.pub.pem"),
+        )],
+    )
+    .unwrap();
+    let token = keys.sign(&sample_claims(), ACCESS_TOKEN_AUDIENCE).unwrap();
+    assert!(keys.verify::<Claims>(&token, ACCESS_TOKEN_AUDIENCE).is_ok());
+
+    // a mesma chave assina outros tipos de token, separados pela audiência
+    for audience in [MFA_CHALLENGE_AUDIENCE, UNSUBSCRIBE_AUDIENCE] {
+        let other = keys.sign(&sample_claims(), audience).unwrap();
+        assert!(
+            keys.verify::<Claims>(&other, ACCESS_TOKEN_AUDIENCE)
+                .is_err()
+        );
+        assert!(keys.verify::<Claims>(&other, audience).is_ok());
+    }
+
+    // um terceiro valida o token só com o JWKS publicado
+    let jwks = serde_json::to_value(keys.jwks()).unwrap();
+    assert_eq!(jwks["keys"][0]["kty"], "OKP");
+    assert_eq!(jwks["keys"][0]["crv"], "Ed25519");
+    let jwk = &keys.jwks().keys[0];
+    let decoding_key = jsonwebtoken::DecodingKey::from_jwk(jwk).unwrap();
+    let mut validation = jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::EdDSA);
+    validation.set_audience(&[ACCESS_TOKEN_AUDIENCE]);
+    let decoded = jsonwebtoken::decode::<Claims>(&token, &decoding_key, &validation).unwrap();
+    assert_eq!(decoded.claims.sub, "user-1");
+
+    // com HS256 (padrão dos testes) o segredo nunca é publicado
+    let app = test::init_service(App::new().service(well_known_routes::jwks)).await;
+    let req = test::TestRequest::get()
+        .uri("/.well-known/jwks.json")
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert!(body["keys"].is_array());
+    assert!(
+        body["keys"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|key| key["kty"] != "oct")
+    );
+}
+
+#[actix_web::test]
+async fn test_api_key_lifecycle_and_scopes() {
+    init();
+
+    let app_state = setup_app_state().await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let payload = UserRequest {
+        email: "chaves.api@exemplo.com".to_string(),
+        password: "Senha123456".to_string(),
+        first_name: "Chaves".to_string(),
+        last_name: "Api".to_string(),
+        profile: None,
+    };
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(&payload)
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let user_id: uuid::Uuid = body["user"]["id"].as_str().unwrap().parse().unwrap();
+
+    role_repository::assign_role(user_id, "admin", &app_state.db)
+        .await
+        .unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({
+            "email": payload.email,
+            "password": payload.password
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let token = body["token"].as_str().unwrap().to_string();
+
+    // escopo fora das permissões do dono é recusado
+    let req = test::TestRequest::post()
+        .uri("/api/v1/me/api-keys/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({ "name": "job", "scopes": ["nao:existe"] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let create_key = |scopes: serde_json::Value| {
+        test::TestRequest::post()
+            .uri("/api/v1/me/api-keys/")
+            .insert_header(("Authorization", format!("Token {}", token)))
+            .set_json(serde_json::json!({ "name": "job noturno", "scopes": scopes }))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, create_key(serde_json::json!(["roles:manage"]))).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let scoped_key = body["key"].as_str().unwrap().to_string();
+    let scoped_key_id = body["id"].as_str().unwrap().to_string();
+    assert!(scoped_key.starts_with(body["prefix"].as_str().unwrap()));
+    assert!(body.get("key_hash").is_none());
+
+    let resp = test::call_service(&app, create_key(serde_json::json!([]))).await;
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let unscoped_key = body["key"].as_str().unwrap().to_string();
+
+    // chaves não gerenciam chaves nem a própria conta, mesmo com todos os escopos
+    let with_key = |req: test::TestRequest| {
+        req.insert_header(("Authorization", format!("ApiKey {}", scoped_key)))
+            .to_request()
+    };
+    let account_routes = [
+        test::TestRequest::get().uri("/api/v1/me/api-keys/"),
+        test::TestRequest::post()
+            .uri("/api/v1/me/api-keys/")
+            .set_json(serde_json::json!({ "name": "escalada", "scopes": ["users:manage"] })),
+        test::TestRequest::post()
+            .uri(&format!("/api/v1/admin/users/{}/api-keys/", user_id))
+            .set_json(serde_json::json!({ "name": "escalada", "scopes": [] })),
+        test::TestRequest::patch()
+            .uri("/api/v1/users/")
+            .set_json(serde_json::json!({ "first_name": "Invasor" })),
+        test::TestRequest::post()
+            .uri("/api/v1/me/email/")
+            .set_json(serde_json::json!({ "new_email": "invasor@exemplo.com", "password": "Senha123456" })),
+        test::TestRequest::post()
+            .uri("/api/v1/me/password/")
+            .set_json(serde_json::json!({ "current_password": "Senha123456", "new_password": "OutraSenha123" })),
+        test::TestRequest::get().uri("/api/v1/me/export/"),
+        test::TestRequest::delete().uri("/api/v1/users/"),
+    ];
+    for req in account_routes {
+        let resp = test::call_service(&app, with_key(req)).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    // nem as sessões interativas do dono, com ou sem escopos
+    for key in [&scoped_key, &unscoped_key] {
+        let session_routes = [
+            test::TestRequest::get().uri("/api/v1/me/sessions/"),
+            test::TestRequest::delete()
+                .uri(&format!("/api/v1/me/sessions/{}/", uuid::Uuid::new_v4())),
+            test::TestRequest::post().uri("/api/v1/logout/"),
+            test::TestRequest::post().uri("/api/v1/logout-all/"),
+        ];
+        for req in session_routes {
+            let req = req
+                .insert_header(("Authorization", format!("ApiKey {}", key)))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        }
+    }
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // a chave autentica como o dono, com as permissões limitadas aos escopos
+    let admin_roles = |key: &str| {
+        test::TestRequest::get()
+            .uri("/api/v1/admin/roles/")
+            .insert_header(("Authorization", format!("ApiKey {}", key)))
+            .to_request()
+    };
+    let resp = test::call_service(&app, admin_roles(&scoped_key)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = test::call_service(&app, admin_roles(&unscoped_key)).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/api-keys/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let listed = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|key| key["id"] == scoped_key_id.as_str())
+        .unwrap();
+    assert!(listed["last_used_at"].is_string());
+    assert_eq!(listed["scopes"], serde_json::json!(["roles:manage"]));
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/me/api-keys/{}/", scoped_key_id))
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({ "expires_at": "2000-01-01T00:00:00" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/v1/me/api-keys/{}/", scoped_key_id))
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let resp = test::call_service(&app, admin_roles(&scoped_key)).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let resp = test::call_service(&app, admin_roles("rk_invalida_0000")).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_confirm_and_reset_emails_are_sent() {
+    init();
+
+    let mailer = Arc::new(InMemoryEmailSender::new());
+    let app_state = setup_app_state_with(|state| state.mailer = mailer.clone()).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let email = "emails.enviados@exemplo.com";
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": email,
+            "password": "Senha123456",
+            "first_name": "Carteiro",
+            "last_name": "User"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // o cadastro só enfileira; a entrega é feita pelo outbox
+    assert!(mailer.messages_to(email).is_empty());
+    deliver_queued_emails(email, mailer.as_ref(), &app_state.db).await;
+
+    let messages = mailer.messages_to(email);
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].html.contains("Olá, Carteiro!"));
+
+    // o link aponta para a própria API de confirmação
+    // o Tera escapa `/` como `&#x2F;` no HTML
+    let html = messages[0].html.replace("&#x2F;", "/");
+    let confirm_path = html
+        .split("href=\"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .and_then(|link| link.find("/api/v1/").map(|start| link[start..].to_string()))
+        .unwrap();
+    let req = test::TestRequest::get().uri(&confirm_path).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/forgot-password/")
+        .set_json(serde_json::json!({ "email": email }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    deliver_queued_emails(email, mailer.as_ref(), &app_state.db).await;
+
+    let messages = mailer.messages_to(email);
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[1].subject, "🔐 Redefina sua senha");
+    let code = messages[1]
+        .html
+        .split("token=")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .unwrap()
+        .to_string();
+
+    // depois da entrega o corpo com o código não fica guardado no banco
+    let sent =
+        email_outbox_repository::list(Some(email_status::SENT), Some(email), 10, 0, &app_state.db)
+            .await
+            .unwrap();
+    assert_eq!(sent.len(), 2);
+    for outbox in &sent {
+        assert!(outbox.html_body.is_empty());
+        assert!(outbox.text_body.is_none());
+    }
+    let stored: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM email_outbox
+         WHERE to_email = $1
+           AND (html_body LIKE '%' || $2 || '%' OR text_body LIKE '%' || $2 || '%')",
+    )
+    .bind(email)
+    .bind(&code)
+    .fetch_one(&app_state.db)
+    .await
+    .unwrap();
+    assert_eq!(stored, 0);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/change-password/")
+        .set_json(serde_json::json!({ "code": code, "password": "NovaSenha123456" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({ "email": email, "password": "NovaSenha123456" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+/// Entrega apenas os e-mails enfileirados para `to`, sem disputar a fila com outros testes
+async fn deliver_queued_emails(to: &str, mailer: &dyn EmailSender, db: &PgPool) {
+    let policy = RetryPolicy {
+        retry_base_seconds: 0,
+        retry_max_seconds: 0,
+    };
+    let queued = email_outbox_repository::list(Some(email_status::QUEUED), Some(to), 100, 0, db)
+        .await
+        .unwrap();
+    // a listagem vem da mais nova para a mais antiga
+    for email in queued.into_iter().rev() {
+        email_outbox_service::deliver_email(email.id, mailer, &policy, db)
+            .await
+            .unwrap();
+    }
+}
+
+struct FailingEmailSender;
+
+#[async_trait::async_trait]
+impl EmailSender for FailingEmailSender {
+    async fn send(&self, _message: &EmailMessage) -> anyhow::Result<String> {
+        Err(anyhow::anyhow!("provedor indisponível"))
+    }
+}
+
+#[actix_web::test]
+async fn test_email_outbox_retries_and_dead_letter() {
+    init();
+
+    let mailer = Arc::new(InMemoryEmailSender::new());
+    let app_state = setup_app_state_with(|state| state.mailer = mailer.clone()).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let email = "outbox.falha@exemplo.com";
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": email,
+            "password": "Senha123456",
+            "first_name": "Fila",
+            "last_name": "User"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let queued = email_outbox_repository::list(
+        Some(email_status::QUEUED),
+        Some(email),
+        10,
+        0,
+        &app_state.db,
+    )
+    .await
+    .unwrap();
+    assert_eq!(queued.len(), 1);
+    let email_id = queued[0].id;
+    let max_attempts = queued[0].max_attempts;
+
+    // sem atraso entre as tentativas para não depender do relógio
+    let policy = RetryPolicy {
+        retry_base_seconds: 0,
+        retry_max_seconds: 0,
+    };
+    for attempt in 1..=max_attempts {
+        let outbox = email_outbox_service::deliver_email(
+            email_id,
+            &FailingEmailSender,
+            &policy,
+            &app_state.db,
+        )
+        .await
+        .unwrap();
+        assert_eq!(outbox.attempts, attempt);
+        assert_eq!(outbox.last_error.as_deref(), Some("provedor indisponível"));
+        let expected = if attempt < max_attempts {
+            email_status::QUEUED
+        } else {
+            email_status::FAILED
+        };
+        assert_eq!(outbox.status, expected);
+    }
+
+    // em dead letter o worker não tenta mais
+    let outbox =
+        email_outbox_service::deliver_email(email_id, mailer.as_ref(), &policy, &app_state.db)
+            .await
+            .unwrap();
+    assert_eq!(outbox.status, email_status::FAILED);
+    assert_eq!(outbox.attempts, max_attempts);
+    assert!(mailer.messages_to(email).is_empty());
+
+    let admin = UserRequest {
+        email: "admin.outbox@exemplo.com".to_string(),
+        password: "Senha123456".to_string(),
+        first_name: "Admin".to_string(),
+        last_name: "User".to_string(),
+        profile: None,
+    };
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(&admin)
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let admin_id: uuid::Uuid = body["user"]["id"].as_str().unwrap().parse().unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({
+            "email": admin.email,
+            "password": admin.password
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let token = body["token"].as_str().unwrap().to_string();
+
+    // sem a permissão emails:manage
+    let req = test::TestRequest::get()
+        .uri("/api/v1/admin/emails/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    role_repository::assign_role(admin_id, "admin", &app_state.db)
+        .await
+        .unwrap();
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({
+            "email": admin.email,
+            "password": admin.password
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let admin_token = body["token"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/api/v1/admin/emails/?status=failed&to_email={}",
+            email
+        ))
+        .insert_header(("Authorization", format!("Token {}", admin_token)))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["count"], 1);
+    assert_eq!(body["results"][0]["id"], email_id.to_string());
+    assert!(body["results"][0].get("html_body").is_none());
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/admin/emails/?status=desconhecido")
+        .insert_header(("Authorization", format!("Token {}", admin_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/admin/emails/{}/", email_id))
+        .insert_header(("Authorization", format!("Token {}", admin_token)))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["status"], "failed");
+    assert_eq!(body["last_error"], "provedor indisponível");
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/admin/emails/{}/retry/", email_id))
+        .insert_header(("Authorization", format!("Token {}", admin_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["status"], "queued");
+    assert_eq!(body["attempts"], 0);
+
+    // só mensagens em dead letter podem ser devolvidas à fila
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/admin/emails/{}/retry/", email_id))
+        .insert_header(("Authorization", format!("Token {}", admin_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let outbox =
+        email_outbox_service::deliver_email(email_id, mailer.as_ref(), &policy, &app_state.db)
+            .await
+            .unwrap();
+    assert_eq!(outbox.status, email_status::SENT);
+    assert!(outbox.provider_message_id.is_some());
+    assert!(outbox.sent_at.is_some());
+    assert_eq!(mailer.messages_to(email).len(), 1);
+}
+
+#[actix_web::test]
+async fn test_emails_follow_preferred_language() {
+    init();
+
+    let mailer = Arc::new(InMemoryEmailSender::new());
+    let app_state = setup_app_state_with(|state| state.mailer = mailer.clone()).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": "idioma.invalido@exemplo.com",
+            "password": "Senha123456",
+            "first_name": "Idioma",
+            "last_name": "User",
+            "profile": { "preferred_language": "fr" }
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let email = "idioma.ingles@exemplo.com";
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": email,
+            "password": "Senha123456",
+            "first_name": "Idioma",
+            "last_name": "User",
+            "profile": { "preferred_language": "en" }
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["user"]["profile"]["preferred_language"], "en");
+    let token = body["token"].as_str().unwrap().to_string();
+
+    deliver_queued_emails(email, mailer.as_ref(), &app_state.db).await;
+    let messages = mailer.messages_to(email);
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].subject, "✅ Confirm your e-mail");
+    assert!(messages[0].html.contains("Hi, Idioma!"));
+    // a parte em texto puro não é escapada
+    let text = messages[0].text.as_deref().unwrap();
+    assert!(text.contains("Hi, Idioma!"));
+    assert!(text.contains("/api/v1/confirm-email/"));
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/profiles/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({ "preferred_language": "fr" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/profiles/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({ "preferred_language": "pt-BR" }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["user"]["profile"]["preferred_language"], "pt-BR");
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/forgot-password/")
+        .set_json(serde_json::json!({ "email": email }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    deliver_queued_emails(email, mailer.as_ref(), &app_state.db).await;
+    let messages = mailer.messages_to(email);
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[1].subject, "🔐 Redefina sua senha");
+    assert!(
+        messages[1]
+            .text
+            .as_deref()
+            .unwrap()
+            .contains("Olá, Idioma!")
+    );
+
+    // os templates embutidos são os mesmos do diretório e idiomas sem template usam pt-BR
+    let embedded = EmailTemplates::embedded().unwrap();
+    let from_dir = EmailTemplates::from_dir("src/templates/emails").unwrap();
+    let mut ctx = tera::Context::new();
+    ctx.insert("name", "Maria");
+    ctx.insert("link", "https://exemplo.com/link");
+    ctx.insert("course_name", "Rust");
+    ctx.insert("unsubscribe_link", "https://exemplo.com/unsubscribe");
+    ctx.insert("new_email", "maria.nova@exemplo.com");
+    for template in EmailTemplate::ALL {
+        for language in ["pt-BR", "en"] {
+            let a = embedded.render(template, language, &ctx).unwrap();
+            let b = from_dir.render(template, language, &ctx).unwrap();
+            assert_eq!((a.subject, a.html, a.text), (b.subject, b.html, b.text));
+        }
+    }
+    let fallback = embedded
+        .render(EmailTemplate::ConfirmEmail, "es", &ctx)
+        .unwrap();
+    assert_eq!(fallback.subject, "✅ Confirme seu e-mail");
+    assert!(EmailTemplates::from_dir("tests/fixtures").is_err());
+}
+
+async fn queue_test_course_announcement(name: &str, db: &PgPool) {
+    let now = chrono::Utc::now().naive_utc();
+    let course = Course {
+        id: uuid::Uuid::new_v4(),
+        name: name.to_string(),
+        description: None,
+        is_active: true,
+        price: 10.0,
+        month_duration: 1,
+        author_id: uuid::Uuid::new_v4(),
+        dt_start: now.date(),
+        dt_created: now,
+        dt_updated: now,
+        dt_deleted: None,
+    };
+
+    email_service::queue_course_announcement(&course, 100, db)
+        .await
+        .unwrap();
+}
+
+#[actix_web::test]
+async fn test_email_preferences_and_one_click_unsubscribe() {
+    init();
+
+    let mailer = Arc::new(InMemoryEmailSender::new());
+    let app_state = setup_app_state_with(|state| state.mailer = mailer.clone()).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let email = "preferencias.email@exemplo.com";
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": email,
+            "password": "Senha123456",
+            "first_name": "Preferencia",
+            "last_name": "User"
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let user_id: uuid::Uuid = body["user"]["id"].as_str().unwrap().parse().unwrap();
+    let token = body["token"].as_str().unwrap().to_string();
+    // anúncios só vão para e-mails confirmados
+    profile_repository::confirm_email(user_id, &app_state.db)
+        .await
+        .unwrap();
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/profiles/email-preferences/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["transactional"], true);
+    assert_eq!(body["course_announcements"], true);
+    assert_eq!(body["marketing"], false);
+    assert_eq!(body["unsubscribe"], false);
+
+    let req = test::TestRequest::patch()
+        .uri("/api/v1/profiles/email-preferences/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({ "transactional": false }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::patch()
+        .uri("/api/v1/profiles/email-preferences/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({ "marketing": true }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["marketing"], true);
+    assert_eq!(body["course_announcements"], true);
+
+    queue_test_course_announcement("Curso de Preferências", &app_state.db).await;
+    deliver_queued_emails(email, mailer.as_ref(), &app_state.db).await;
+
+    let messages = mailer.messages_to(email);
+    assert_eq!(messages.len(), 2);
+    // o e-mail transacional não tem descadastro
+    assert!(messages[0].list_unsubscribe.is_none());
+    let announcement = &messages[1];
+    assert_eq!(announcement.subject, "📚 Novo curso disponível");
+    assert!(
+        announcement
+            .text
+            .as_deref()
+            .unwrap()
+            .contains("Curso de Preferências")
+    );
+    assert!(
+        announcement
+            .text
+            .as_deref()
+            .unwrap()
+            .contains("/unsubscribe?token=")
+    );
+    let unsubscribe_url = announcement.list_unsubscribe.clone().unwrap();
+    let unsubscribe_path = &unsubscribe_url[unsubscribe_url.find("/api/v1/").unwrap()..];
+
+    // cabeçalhos da RFC 8058 na mensagem MIME
+    let dir = std::env::temp_dir().join("rust_usecases_test_emails");
+    let file_sender = FileEmailSender::new(dir.to_str().unwrap(), "noreply@exemplo.com");
+    let file_name = file_sender.send(announcement).await.unwrap();
+    let eml = std::fs::read_to_string(dir.join(file_name)).unwrap();
+    assert!(eml.contains(&format!("List-Unsubscribe: <{}>", unsubscribe_url)));
+    assert!(eml.contains("List-Unsubscribe-Post: List-Unsubscribe=One-Click"));
+    assert!(eml.contains("multipart/alternative"));
+
+    // o token de acesso não serve como link de descadastro
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/unsubscribe/?token={}", token))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // nem o link de descadastro serve como token de acesso
+    let unsubscribe_token = unsubscribe_url.split("token=").nth(1).unwrap();
+    let req = test::TestRequest::get()
+        .uri("/api/v1/profiles/email-preferences/")
+        .insert_header(("Authorization", format!("Token {}", unsubscribe_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // o POST de um clique funciona sem login e pode ser repetido
+    for _ in 0..2 {
+        let req = test::TestRequest::post()
+            .uri(unsubscribe_path)
+            .insert_header(("Content-Type", "application/x-www-form-urlencoded"))
+            .set_payload("List-Unsubscribe=One-Click")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["category"], "course_announcements");
+        assert_eq!(body["unsubscribed"], true);
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/profiles/email-preferences/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["course_announcements"], false);
+    assert_eq!(body["marketing"], true);
+
+    // descadastrado, não entra mais nos anúncios
+    queue_test_course_announcement("Curso Ignorado", &app_state.db).await;
+    let queued = email_outbox_repository::list(
+        Some(email_status::QUEUED),
+        Some(email),
+        10,
+        0,
+        &app_state.db,
+    )
+    .await
+    .unwrap();
+    assert!(queued.is_empty());
+
+    // quem sai da lista depois do enfileiramento é descartado na entrega
+    let req = test::TestRequest::patch()
+        .uri("/api/v1/profiles/email-preferences/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({ "course_announcements": true }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    queue_test_course_announcement("Curso Suprimido", &app_state.db).await;
+
+    let req = test::TestRequest::patch()
+        .uri("/api/v1/profiles/email-preferences/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({ "unsubscribe": true }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let queued = email_outbox_repository::list(
+        Some(email_status::QUEUED),
+        Some(email),
+        10,
+        0,
+        &app_state.db,
+    )
+    .await
+    .unwrap();
+    assert_eq!(queued.len(), 1);
+    let policy = RetryPolicy {
+        retry_base_seconds: 0,
+        retry_max_seconds: 0,
+    };
+    let outbox =
+        email_outbox_service::deliver_email(queued[0].id, mailer.as_ref(), &policy, &app_state.db)
+            .await
+            .unwrap();
+    assert_eq!(outbox.status, email_status::SUPPRESSED);
+    assert_eq!(mailer.messages_to(email).len(), 2);
+}
+
+#[actix_web::test]
+async fn test_email_change_requires_confirmation_of_new_address() {
+    init();
+
+    let mailer = Arc::new(InMemoryEmailSender::new());
+    let app_state = setup_app_state_with(|state| state.mailer = mailer.clone()).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let old_email = "troca.antigo@exemplo.com";
+    let new_email = "troca.novo@exemplo.com";
+    let taken_email = "troca.ocupado@exemplo.com";
+
+    for email in [old_email, taken_email] {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/users/")
+            .set_json(serde_json::json!({
+                "email": email,
+                "password": "Senha123456",
+                "first_name": "Troca",
+                "last_name": "User"
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({ "email": old_email, "password": "Senha123456" }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let token = body["token"].as_str().unwrap().to_string();
+    let username = body["user"]["username"].as_str().unwrap().to_string();
+    deliver_queued_emails(old_email, mailer.as_ref(), &app_state.db).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/me/email/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({ "new_email": new_email, "password": "SenhaErrada1" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/me/email/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({ "new_email": taken_email, "password": "Senha123456" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/me/email/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({ "new_email": new_email, "password": "Senha123456" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+
+    // o endereço atual só recebe o aviso; o link vai para o novo
+    deliver_queued_emails(old_email, mailer.as_ref(), &app_state.db).await;
+    deliver_queued_emails(new_email, mailer.as_ref(), &app_state.db).await;
+    let notices = mailer.messages_to(old_email);
+    assert_eq!(notices.len(), 2);
+    assert_eq!(
+        notices[1].subject,
+        "⚠️ O e-mail da sua conta está sendo alterado"
+    );
+    assert!(notices[1].text.as_deref().unwrap().contains(new_email));
+    assert!(!notices[1].html.contains("confirm-email-change"));
+
+    let messages = mailer.messages_to(new_email);
+    assert_eq!(messages.len(), 1);
+    let text = messages[0].text.as_deref().unwrap();
+    let confirm_path = text
+        .find("/api/v1/confirm-email-change/")
+        .map(|start| text[start..].split_whitespace().next().unwrap().to_string())
+        .unwrap();
+
+    // antes da confirmação, nada muda
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({ "email": new_email, "password": "Senha123456" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::get().uri(&confirm_path).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // o código é de uso único
+    let req = test::TestRequest::get().uri(&confirm_path).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_client_error());
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({ "email": old_email, "password": "Senha123456" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({ "email": new_email, "password": "Senha123456" }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["user"]["email"], new_email);
+    // por padrão o username é mantido (`EMAIL_CHANGE_REGENERATE_USERNAME=false`)
+    assert_eq!(body["user"]["username"], username);
+    assert_eq!(body["user"]["profile"]["confirm_email"], true);
+}
+
+#[actix_web::test]
+async fn test_resend_confirmation_and_confirmed_email_policy() {
+    init();
+
+    let mailer = Arc::new(InMemoryEmailSender::new());
+    let app_state = setup_app_state_with(|state| state.mailer = mailer.clone()).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let email = "reenvio.confirmacao@exemplo.com";
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": email,
+            "password": "Senha123456",
+            "first_name": "Reenvio",
+            "last_name": "User"
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let user_id = uuid::Uuid::parse_str(body["user"]["id"].as_str().unwrap()).unwrap();
+
+    let confirm_path = |text: &str| {
+        text.find("/api/v1/confirm-email/")
+            .map(|start| text[start..].split_whitespace().next().unwrap().to_string())
+            .unwrap()
+    };
+
+    deliver_queued_emails(email, mailer.as_ref(), &app_state.db).await;
+    let first_path = confirm_path(mailer.messages_to(email)[0].text.as_deref().unwrap());
+
+    // e-mails desconhecidos recebem a mesma resposta
+    let req = test::TestRequest::post()
+        .uri("/api/v1/resend-confirmation/")
+        .set_json(serde_json::json!({ "email": "ninguem.reenvio@exemplo.com" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+
+    // logo após o cadastro o reenvio ainda está em espera
+    let req = test::TestRequest::post()
+        .uri("/api/v1/resend-confirmation/")
+        .set_json(serde_json::json!({ "email": email }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(resp.headers().contains_key("Retry-After"));
+
+    sqlx::query(
+        "UPDATE user_tokens SET created_at = created_at - INTERVAL '1 hour' WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .execute(&app_state.db)
+    .await
+    .unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/resend-confirmation/")
+        .set_json(serde_json::json!({ "email": email }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+
+    deliver_queued_emails(email, mailer.as_ref(), &app_state.db).await;
+    let messages = mailer.messages_to(email);
+    assert_eq!(messages.len(), 2);
+    let second_path = confirm_path(messages[1].text.as_deref().unwrap());
+    assert_ne!(first_path, second_path);
+
+    let policy = ConfirmedEmailPolicy {
+        actions: vec![protected_actions::CREATE_COURSE.to_string()],
+    };
+    let err = policy
+        .ensure_allowed(user_id, protected_actions::CREATE_COURSE, &app_state.db)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AppError::EmailNotConfirmed(_)));
+    assert_eq!(
+        actix_web::ResponseError::error_response(&err).status(),
+        StatusCode::FORBIDDEN
+    );
+    // ações fora da política não dependem da confirmação
+    policy
+        .ensure_allowed(user_id, protected_actions::WEBSOCKET, &app_state.db)
+        .await
+        .unwrap();
+
+    // o reenvio invalida o link anterior
+    let req = test::TestRequest::get().uri(&first_path).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_client_error());
+
+    let req = test::TestRequest::get().uri(&second_path).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    policy
+        .ensure_allowed(user_id, protected_actions::CREATE_COURSE, &app_state.db)
+        .await
+        .unwrap();
+
+    // com o e-mail confirmado, o reenvio não manda nada
+    let req = test::TestRequest::post()
+        .uri("/api/v1/resend-confirmation/")
+        .set_json(serde_json::json!({ "email": email }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+    deliver_queued_emails(email, mailer.as_ref(), &app_state.db).await;
+    assert_eq!(mailer.messages_to(email).len(), 2);
+}
+
+#[actix_web::test]
+async fn test_user_tokens_are_hashed_single_use_and_purged() {
+    init();
+
+    let mailer = Arc::new(InMemoryEmailSender::new());
+    let app_state = setup_app_state_with(|state| state.mailer = mailer.clone()).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let email = "tokens.hash@exemplo.com";
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": email,
+            "password": "Senha123456",
+            "first_name": "Tokens",
+            "last_name": "User"
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let user_id = uuid::Uuid::parse_str(body["user"]["id"].as_str().unwrap()).unwrap();
+
+    deliver_queued_emails(email, mailer.as_ref(), &app_state.db).await;
+    let text = mailer.messages_to(email)[0].text.clone().unwrap();
+    let code = text
+        .split("/api/v1/confirm-email/")
+        .nth(1)
+        .and_then(|rest| rest.split('/').next())
+        .unwrap()
+        .to_string();
+
+    // o banco guarda só o hash do código
+    let hashes: Vec<String> =
+        sqlx::query_scalar("SELECT code_hash FROM user_tokens WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_all(&app_state.db)
+            .await
+            .unwrap();
+    assert_eq!(hashes, vec![sha256_hex(&code)]);
+
+    // duas confirmações simultâneas: só uma consome o código
+    let consume = |code: String| {
+        let db = app_state.db.clone();
+        async move {
+            let mut tx = db.begin().await.unwrap();
+            let result =
+                token_service::consume_token_in_tx(&code, token_types::CONFIRM_EMAIL, &mut tx)
+                    .await;
+            tx.commit().await.unwrap();
+            result
+        }
+    };
+    let (first, second) = tokio::join!(consume(code.clone()), consume(code.clone()));
+    assert_eq!(
+        [first.is_ok(), second.is_ok()]
+            .iter()
+            .filter(|ok| **ok)
+            .count(),
+        1
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/confirm-email/{}/", code))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/forgot-password/")
+        .set_json(serde_json::json!({ "email": email }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    deliver_queued_emails(email, mailer.as_ref(), &app_state.db).await;
+    let text = mailer.messages_to(email)[1].text.clone().unwrap();
+    let reset_code = text
+        .split("token=")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .unwrap()
+        .to_string();
+
+    sqlx::query(
+        "UPDATE user_tokens SET expires_at = NOW() - INTERVAL '1 day' WHERE code_hash = $1",
+    )
+    .bind(sha256_hex(&reset_code))
+    .execute(&app_state.db)
+    .await
+    .unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/change-password/")
+        .set_json(serde_json::json!({ "code": reset_code, "password": "NovaSenha123456" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // a limpeza remove os expirados e mantém os demais
+    let purged = token_service::purge_expired_tokens(&app_state.db)
+        .await
+        .unwrap();
+    assert!(purged >= 1);
+    let remaining: Vec<String> =
+        sqlx::query_scalar("SELECT token_type FROM user_tokens WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_all(&app_state.db)
+            .await
+            .unwrap();
+    assert_eq!(remaining, vec![token_types::CONFIRM_EMAIL.to_string()]);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/change-password/")
+        .set_json(serde_json::json!({ "code": reset_code, "password": "NovaSenha123456" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn test_password_policy_history_and_argon2_rehash() {
+    init();
+
+    let strict = PasswordSettings {
+        min_length: 10,
+        max_length: 64,
+        require_letter: true,
+        require_uppercase: true,
+        require_lowercase: true,
+        require_digit: true,
+        require_symbol: true,
+        deny_common: true,
+        history_size: 3,
+        argon2_memory_kib: 19456,
+        argon2_iterations: 2,
+        argon2_parallelism: 1,
+    };
+    assert!(password_violations("Forte#Senha2025", &strict).is_empty());
+    assert_eq!(password_violations("forte#senha2025", &strict).len(), 1);
+    assert_eq!(password_violations("Curta#1", &strict).len(), 1);
+    assert_eq!(password_violations("SemSimbolo2025", &strict).len(), 1);
+    // a lista de senhas comuns não diferencia maiúsculas
+    let relaxed = PasswordSettings {
+        require_lowercase: false,
+        require_symbol: false,
+        ..strict.clone()
+    };
+    assert_eq!(
+        password_violations("PASSWORD123", &relaxed),
+        vec!["A senha é muito comum".to_string()]
+    );
+
+    let mailer = Arc::new(InMemoryEmailSender::new());
+    let app_state = setup_app_state_with(|state| state.mailer = mailer.clone()).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let email = "politica.senha@exemplo.com";
+    for password in ["password123", "somenteletras"] {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/users/")
+            .set_json(serde_json::json!({
+                "email": email,
+                "password": password,
+                "first_name": "Politica",
+                "last_name": "Senha"
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": email,
+            "password": "Senha123456",
+            "first_name": "Politica",
+            "last_name": "Senha"
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let user_id = uuid::Uuid::parse_str(body["user"]["id"].as_str().unwrap()).unwrap();
+
+    let stored_hash = || async {
+        sqlx::query_scalar::<_, String>("SELECT password FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&app_state.db)
+            .await
+            .unwrap()
+    };
+    assert!(stored_hash().await.starts_with("$argon2id$"));
+
+    // contas antigas com bcrypt entram normalmente e são migradas no login
+    let legacy = bcrypt::hash("Senha123456", 4).unwrap();
+    sqlx::query("UPDATE users SET password = $1 WHERE id = $2")
+        .bind(&legacy)
+        .bind(user_id)
+        .execute(&app_state.db)
+        .await
+        .unwrap();
+    for _ in 0..2 {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/login/")
+            .set_json(serde_json::json!({ "email": email, "password": "Senha123456" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(stored_hash().await.starts_with("$argon2id$"));
+    }
+
+    let request_reset_code = |index: usize| {
+        let app_state = app_state.clone();
+        let mailer = mailer.clone();
+        let app = &app;
+        async move {
+            let req = test::TestRequest::post()
+                .uri("/api/v1/forgot-password/")
+                .set_json(serde_json::json!({ "email": email }))
+                .to_request();
+            let resp = test::call_service(app, req).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+            deliver_queued_emails(email, mailer.as_ref(), &app_state.db).await;
+            let text = mailer.messages_to(email)[index].text.clone().unwrap();
+            text.split("token=")
+                .nth(1)
+                .and_then(|rest| rest.split_whitespace().next())
+                .unwrap()
+                .to_string()
+        }
+    };
+    let change_password = |code: String, password: &'static str| {
+        let app = &app;
+        async move {
+            let req = test::TestRequest::post()
+                .uri("/api/v1/change-password/")
+                .set_json(serde_json::json!({ "code": code, "password": password }))
+                .to_request();
+            test::call_service(app, req).await.status()
+        }
+    };
+
+    // o e-mail de confirmação do cadastro é o primeiro
+    let code = request_reset_code(1).await;
+    assert_eq!(
+        change_password(code.clone(), "Senha123456").await,
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        change_password(code.clone(), "curta1").await,
+        StatusCode::BAD_REQUEST
+    );
+    // senha recusada não consome o token
+    assert_eq!(
+        change_password(code, "OutraSenha2025").await,
+        StatusCode::OK
+    );
+    assert!(stored_hash().await.starts_with("$argon2id$"));
+
+    let code = request_reset_code(2).await;
+    assert_eq!(
+        change_password(code.clone(), "Senha123456").await,
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        change_password(code, "TerceiraSenha2025").await,
+        StatusCode::OK
+    );
+
+    let history: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM password_history WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&app_state.db)
+            .await
+            .unwrap();
+    assert_eq!(history, 3);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({ "email": email, "password": "TerceiraSenha2025" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_logged_user_changes_password_and_other_sessions_end() {
+    init();
+
+    let mailer = Arc::new(InMemoryEmailSender::new());
+    let app_state = setup_app_state_with(|state| state.mailer = mailer.clone()).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let email = "senha.logado@exemplo.com";
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": email,
+            "password": "Senha123456",
+            "first_name": "Senha",
+            "last_name": "Logado"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    deliver_queued_emails(email, mailer.as_ref(), &app_state.db).await;
+
+    // duas sessões: a que troca a senha e outra em um segundo dispositivo
+    let mut tokens = Vec::new();
+    for _ in 0..2 {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/login/")
+            .set_json(serde_json::json!({ "email": email, "password": "Senha123456" }))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        tokens.push(body["token"].as_str().unwrap().to_string());
+    }
+    let (current, other) = (&tokens[0], &tokens[1]);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/me/password/")
+        .insert_header(("Authorization", format!("Token {}", current)))
+        .set_json(serde_json::json!({
+            "current_password": "SenhaErrada1",
+            "new_password": "NovaSenha123456"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/me/password/")
+        .insert_header(("Authorization", format!("Token {}", current)))
+        .set_json(serde_json::json!({
+            "current_password": "Senha123456",
+            "new_password": "somenteletras"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // a senha atual conta como histórico
+    let req = test::TestRequest::post()
+        .uri("/api/v1/me/password/")
+        .insert_header(("Authorization", format!("Token {}", current)))
+        .set_json(serde_json::json!({
+            "current_password": "Senha123456",
+            "new_password": "Senha123456"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // nenhuma tentativa recusada derrubou a outra sessão
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/")
+        .insert_header(("Authorization", format!("Token {}", other)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/me/password/")
+        .insert_header(("Authorization", format!("Token {}", current)))
+        .set_json(serde_json::json!({
+            "current_password": "Senha123456",
+            "new_password": "NovaSenha123456"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/")
+        .insert_header(("Authorization", format!("Token {}", current)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/")
+        .insert_header(("Authorization", format!("Token {}", other)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    deliver_queued_emails(email, mailer.as_ref(), &app_state.db).await;
+    let messages = mailer.messages_to(email);
+    let notice = messages.last().unwrap();
+    assert_eq!(notice.subject, "🔐 Sua senha foi alterada");
+    assert!(notice.text.as_deref().unwrap().contains("/forgot-password"));
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({ "email": email, "password": "Senha123456" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({ "email": email, "password": "NovaSenha123456" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_account_deletion_restore_export_and_anonymization() {
+    init();
+
+    let mailer = Arc::new(InMemoryEmailSender::new());
+    let app_state = setup_app_state_with(|state| state.mailer = mailer.clone()).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let email = "exclusao.conta@exemplo.com";
+    let recent_email = "exclusao.recente@exemplo.com";
+    let mut user_ids = Vec::new();
+    let mut tokens = Vec::new();
+    for address in [email, recent_email] {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/users/")
+            .set_json(serde_json::json!({
+                "email": address,
+                "password": "Senha123456",
+                "first_name": "Exclusao",
+                "last_name": "Conta",
+                "profile": { "phone": "11999998888", "bio": "Bio pessoal" }
+            }))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        user_ids.push(
+            body["user"]["id"]
+                .as_str()
+                .unwrap()
+                .parse::<uuid::Uuid>()
+                .unwrap(),
+        );
+        tokens.push(body["token"].as_str().unwrap().to_string());
+    }
+    let user_id = user_ids[0];
+
+    notification_repository::create_notification(
+        "Aviso pessoal",
+        "Mensagem só para você",
+        ObjCodeType::User,
+        Some(user_id),
+        &app_state.db,
+    )
+    .await
+    .unwrap();
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/export/")
+        .insert_header(("Authorization", format!("Token {}", tokens[0])))
+        .to_request();
+    let export: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(export["user"]["email"], email);
+    assert!(export["user"].get("password").is_none());
+    assert_eq!(export["profile"]["phone"], "+5511999998888");
+    let notifications = export["notifications"].as_array().unwrap();
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0]["title"], "Aviso pessoal");
+    assert!(export.get("logs").is_some());
+
+    for token in &tokens {
+        let req = test::TestRequest::delete()
+            .uri("/api/v1/users/")
+            .insert_header(("Authorization", format!("Token {}", token)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({ "email": email, "password": "Senha123456" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // dentro do prazo a conta volta com a senha correta
+    let req = test::TestRequest::post()
+        .uri("/api/v1/restore-account/")
+        .set_json(serde_json::json!({ "email": email, "password": "SenhaErrada1" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/restore-account/")
+        .set_json(serde_json::json!({ "email": email, "password": "Senha123456" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({ "email": email, "password": "Senha123456" }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let token = body["token"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::delete()
+        .uri("/api/v1/users/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    // o curso do autor precisa continuar apontando para a conta anonimizada
+    let course_id = uuid::Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO courses (id, name, price, month_duration, author_id, dt_start) \
+         VALUES ($1, 'Curso do autor excluído', 10.0, 1, $2, CURRENT_DATE)",
+    )
+    .bind(course_id)
+    .bind(user_id)
+    .execute(&app_state.db)
+    .await
+    .unwrap();
+
+    // fim do prazo de carência (30 dias por padrão)
+    sqlx::query("UPDATE users SET dt_deleted = NOW() - INTERVAL '31 days' WHERE id = $1")
+        .bind(user_id)
+        .execute(&app_state.db)
+        .await
+        .unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/restore-account/")
+        .set_json(serde_json::json!({ "email": email, "password": "Senha123456" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let purged = account_deletion_service::purge_expired_deletions(
+        30,
+        &app_state.db,
+        &app_state.mongo,
+        app_state.blobs.as_ref(),
+    )
+    .await
+    .unwrap();
+    assert!(purged >= 1);
+
+    let (anonymized_email, first_name, anonymized): (String, String, bool) = sqlx::query_as(
+        "SELECT email, first_name, dt_anonymized IS NOT NULL FROM users WHERE id = $1",
+    )
+    .bind(user_id)
+    .fetch_one(&app_state.db)
+    .await
+    .unwrap();
+    assert_ne!(anonymized_email, email);
+    assert!(anonymized_email.ends_with("@anonimizado.invalid"));
+    assert_eq!(first_name, "Usuário");
+    assert!(anonymized);
+
+    let (phone, bio): (Option<String>, Option<String>) =
+        sqlx::query_as("SELECT phone, bio FROM profiles WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&app_state.db)
+            .await
+            .unwrap();
+    assert_eq!((phone, bio), (None, None));
+
+    let (leftovers,): (i64,) = sqlx::query_as(
+        "SELECT (SELECT COUNT(*) FROM user_sessions WHERE user_id = $1) \
+              + (SELECT COUNT(*) FROM password_history WHERE user_id = $1) \
+              + (SELECT COUNT(*) FROM notifications WHERE obj_id = $1)",
+    )
+    .bind(user_id)
+    .fetch_one(&app_state.db)
+    .await
+    .unwrap();
+    assert_eq!(leftovers, 0);
+
+    let (author_id,): (uuid::Uuid,) = sqlx::query_as("SELECT author_id FROM courses WHERE id = $1")
+        .bind(course_id)
+        .fetch_one(&app_state.db)
+        .await
+        .unwrap();
+    assert_eq!(author_id, user_id);
+
+    // a conta excluída há pouco ainda está no prazo
+    let (recent_anonymized,): (bool,) =
+        sqlx::query_as("SELECT dt_anonymized IS NOT NULL FROM users WHERE id = $1")
+            .bind(user_ids[1])
+            .fetch_one(&app_state.db)
+            .await
+            .unwrap();
+    assert!(!recent_anonymized);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/restore-account/")
+        .set_json(serde_json::json!({ "email": email, "password": "Senha123456" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // o e-mail ficou livre para um novo cadastro
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": email,
+            "password": "Senha123456",
+            "first_name": "Nova",
+            "last_name": "Conta"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // durante o prazo o e-mail também pode ser usado em um novo cadastro, e aí a conta
+    // excluída não pode mais ser restaurada
+    let reused = "exclusao.reuso@exemplo.com";
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": reused,
+            "password": "Senha123456",
+            "first_name": "Antiga",
+            "last_name": "Conta"
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::delete()
+        .uri("/api/v1/users/")
+        .insert_header((
+            "Authorization",
+            format!("Token {}", body["token"].as_str().unwrap()),
+        ))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": reused,
+            "password": "OutraSenha123",
+            "first_name": "Nova",
+            "last_name": "Conta"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/restore-account/")
+        .set_json(serde_json::json!({ "email": reused, "password": "Senha123456" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+}
+
+#[actix_web::test]
+async fn test_admin_user_management_and_suspension() {
+    init();
+
+    let mailer = Arc::new(InMemoryEmailSender::new());
+    let app_state = setup_app_state_with(|state| state.mailer = mailer.clone()).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let admin_email = "gestao.admin@exemplo.com";
+    let target_email = "gestao.alvo@exemplo.com";
+    let mut ids = Vec::new();
+    let mut tokens = Vec::new();
+    for (email, last_name) in [(admin_email, "Admin"), (target_email, "Alvo")] {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/users/")
+            .set_json(serde_json::json!({
+                "email": email,
+                "password": "Senha123456",
+                "first_name": "Gestao",
+                "last_name": last_name
+            }))
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        ids.push(
+            body["user"]["id"]
+                .as_str()
+                .unwrap()
+                .parse::<uuid::Uuid>()
+                .unwrap(),
+        );
+        tokens.push(body["token"].as_str().unwrap().to_string());
+    }
+    let (admin_id, target_id) = (ids[0], ids[1]);
+    let target_token = tokens[1].clone();
+    deliver_queued_emails(target_email, mailer.as_ref(), &app_state.db).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/admin/users/")
+        .insert_header(("Authorization", format!("Token {}", target_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    // a listagem com e-mails também é só para administradores
+    let req = test::TestRequest::get()
+        .uri("/api/v1/users/")
+        .insert_header(("Authorization", format!("Token {}", target_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    role_repository::assign_role(admin_id, "admin", &app_state.db)
+        .await
+        .unwrap();
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({ "email": admin_email, "password": "Senha123456" }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let admin_token = body["token"].as_str().unwrap().to_string();
+
+    let search = |query: &str| {
+        test::TestRequest::get()
+            .uri(&format!("/api/v1/admin/users/?{}", query))
+            .insert_header(("Authorization", format!("Token {}", admin_token)))
+            .to_request()
+    };
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/users/?limit=1")
+        .insert_header(("Authorization", format!("Token {}", admin_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let body: serde_json::Value =
+        test::call_and_read_body_json(&app, search("email=gestao.alvo")).await;
+    assert_eq!(body["count"], 1);
+    assert_eq!(body["results"][0]["email"], target_email);
+    assert_eq!(body["results"][0]["confirm_email"], false);
+
+    let body: serde_json::Value =
+        test::call_and_read_body_json(&app, search("name=gestao%20alvo&confirmed=false")).await;
+    assert_eq!(body["count"], 1);
+    let body: serde_json::Value =
+        test::call_and_read_body_json(&app, search("email=gestao.&confirmed=true")).await;
+    assert_eq!(body["count"], 0);
+    let body: serde_json::Value =
+        test::call_and_read_body_json(&app, search("email=gestao.&deleted=false&limit=1")).await;
+    assert_eq!(body["count"], 2);
+    assert_eq!(body["results"].as_array().unwrap().len(), 1);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/admin/users/{}/", target_id))
+        .insert_header(("Authorization", format!("Token {}", admin_token)))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["email"], target_email);
+    assert_eq!(body["roles"], serde_json::json!(["user"]));
+
+    // a busca filtra por papel e traz os papéis de cada usuário
+    let body: serde_json::Value =
+        test::call_and_read_body_json(&app, search("email=gestao.&role=admin")).await;
+    assert_eq!(body["count"], 1);
+    assert_eq!(body["results"][0]["id"], admin_id.to_string());
+    assert_eq!(
+        body["results"][0]["roles"],
+        serde_json::json!(["admin", "user"])
+    );
+    let resp = test::call_service(&app, search("access_level=admin")).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // o antigo nível de acesso não concede nada e é recusado
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/admin/users/{}/", target_id))
+        .insert_header(("Authorization", format!("Token {}", admin_token)))
+        .set_json(serde_json::json!({ "access_level": "admin" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/admin/users/{}/", target_id))
+        .insert_header(("Authorization", format!("Token {}", admin_token)))
+        .set_json(serde_json::json!({
+            "profile": { "bio": "Atualizada pelo admin" },
+            "password_reset": "send"
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["roles"], serde_json::json!(["user"]));
+    assert_eq!(body["profile"]["bio"], "Atualizada pelo admin");
+
+    deliver_queued_emails(target_email, mailer.as_ref(), &app_state.db).await;
+    let messages = mailer.messages_to(target_email);
+    let reset = messages.last().unwrap();
+    assert_eq!(reset.subject, "🔐 Redefina sua senha");
+    let text = reset.text.as_deref().unwrap();
+    let code = text
+        .split("token=")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .unwrap()
+        .to_string();
+
+    // cancelar invalida o link enviado
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/admin/users/{}/", target_id))
+        .insert_header(("Authorization", format!("Token {}", admin_token)))
+        .set_json(serde_json::json!({ "password_reset": "cancel" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/change-password/")
+        .set_json(serde_json::json!({ "code": code, "password": "NovaSenha123456" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_client_error());
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/admin/users/{}/suspend/", admin_id))
+        .insert_header(("Authorization", format!("Token {}", admin_token)))
+        .set_json(serde_json::json!({}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/admin/users/{}/suspend/", target_id))
+        .insert_header(("Authorization", format!("Token {}", admin_token)))
+        .set_json(serde_json::json!({ "reason": "Uso indevido" }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert!(body["dt_suspended"].is_string());
+    assert_eq!(body["suspension_reason"], "Uso indevido");
+
+    let body: serde_json::Value =
+        test::call_and_read_body_json(&app, search("email=gestao.&suspended=true")).await;
+    assert_eq!(body["count"], 1);
+    assert_eq!(body["results"][0]["id"], target_id.to_string());
+
+    // as sessões abertas foram encerradas e o login é recusado
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/")
+        .insert_header(("Authorization", format!("Token {}", target_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let login = || {
+        test::TestRequest::post()
+            .uri("/api/v1/login/")
+            .set_json(serde_json::json!({ "email": target_email, "password": "Senha123456" }))
+            .to_request()
+    };
+    let resp = test::call_service(&app, login()).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/admin/users/{}/unsuspend/", target_id))
+        .insert_header(("Authorization", format!("Token {}", admin_token)))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert!(body["dt_suspended"].is_null());
+
+    let body: serde_json::Value = test::call_and_read_body_json(&app, login()).await;
+    let new_token = body["token"].as_str().unwrap().to_string();
+
+    // o middleware recusa um token ainda ativo de uma conta suspensa
+    sqlx::query("UPDATE users SET dt_suspended = NOW() WHERE id = $1")
+        .bind(target_id)
+        .execute(&app_state.db)
+        .await
+        .unwrap();
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/")
+        .insert_header(("Authorization", format!("Token {}", new_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+/// Corpo `multipart/form-data` com um único arquivo; retorna o `Content-Type` e o corpo
+fn multipart_file(field: &str, content_type: &str, data: &[u8]) -> (String, Vec<u8>) {
+    let boundary = "----limite-de-teste";
+    let mut body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"{field}\"; filename=\"avatar\"\r\nContent-Type: {content_type}\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(data);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    (format!("multipart/form-data; boundary={boundary}"), body)
+}
+
+fn encode_test_image(width: u32, height: u32, format: image::ImageFormat) -> Vec<u8> {
+    let image = image::RgbaImage::from_fn(width, height, |x, y| {
+        image::Rgba([
+            (x % 256) as u8,
+            (y % 256) as u8,
+            128,
+            if x < 10 { 0 } else { 255 },
+        ])
+    });
+    let image = match format {
+        image::ImageFormat::Jpeg => {
+            image::DynamicImage::ImageRgb8(image::DynamicImage::ImageRgba8(image).to_rgb8())
+        }
+        _ => image::DynamicImage::ImageRgba8(image),
+    };
+    let mut data = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut data, format).unwrap();
+    data.into_inner()
+}
+
+/// JPEG com um segmento APP1 (EXIF) contendo orientação e um texto que não pode vazar
+fn jpeg_with_exif(marker: &[u8]) -> Vec<u8> {
+    let jpeg = encode_test_image(80, 40, image::ImageFormat::Jpeg);
+
+    let mut tiff = b"MM\x00\x2a\x00\x00\x00\x08".to_vec();
+    tiff.extend_from_slice(&[0x00, 0x01]); // uma entrada no IFD
+    tiff.extend_from_slice(&[0x01, 0x12, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01]); // Orientation
+    tiff.extend_from_slice(&[0x00, 0x06, 0x00, 0x00]); // girar 90°
+    tiff.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]); // sem próximo IFD
+    tiff.extend_from_slice(marker);
+
+    let mut app1 = b"Exif\x00\x00".to_vec();
+    app1.extend_from_slice(&tiff);
+    let length = (app1.len() + 2) as u16;
+
+    let mut data = jpeg[..2].to_vec();
+    data.extend_from_slice(&[0xFF, 0xE1]);
+    data.extend_from_slice(&length.to_be_bytes());
+    data.extend_from_slice(&app1);
+    data.extend_from_slice(&jpeg[2..]);
+    data
+}
+
+fn contains_bytes(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+#[actix_web::test]
+async fn test_avatar_upload_thumbnails_and_validation() {
+    init();
+
+    let blobs = Arc::new(InMemoryBlobStore::new());
+    let app_state = setup_app_state_with(|state| state.blobs = blobs.clone()).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": "avatar.usuario@exemplo.com",
+            "password": "Senha123456",
+            "first_name": "Avatar",
+            "last_name": "Usuario"
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let user_id = body["user"]["id"].as_str().unwrap().to_string();
+    let token = body["token"].as_str().unwrap().to_string();
+    let user_prefix = format!("avatars/{}/", user_id);
+    let user_keys = || {
+        blobs
+            .keys()
+            .into_iter()
+            .filter(|key| key.starts_with(&user_prefix))
+            .collect::<Vec<_>>()
+    };
+
+    let upload = |field: &str, content_type: &str, data: &[u8]| {
+        let (header, body) = multipart_file(field, content_type, data);
+        test::TestRequest::post()
+            .uri("/api/v1/me/avatar/")
+            .insert_header(("Authorization", format!("Token {}", token)))
+            .insert_header(("Content-Type", header))
+            .set_payload(body)
+            .to_request()
+    };
+
+    // PNG retangular com transparência vira miniaturas JPEG quadradas
+    let png = encode_test_image(300, 200, image::ImageFormat::Png);
+    let resp = test::call_service(&app, upload("avatar", "image/png", &png)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let thumbnails = body["thumbnails"].as_array().unwrap();
+    let sizes: Vec<u64> = thumbnails
+        .iter()
+        .map(|t| t["size"].as_u64().unwrap())
+        .collect();
+    assert_eq!(sizes, vec![512, 256, 64]);
+    assert_eq!(body["avatar"], thumbnails[0]["url"]);
+    let first_avatar = body["avatar"].as_str().unwrap().to_string();
+
+    for thumbnail in thumbnails {
+        let url = thumbnail["url"].as_str().unwrap();
+        let key = blobs.key_from_url(url).unwrap();
+        assert!(key.starts_with(&user_prefix));
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/media/{}", key))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get("Content-Type").unwrap(), "image/jpeg");
+        let data = test::read_body(resp).await;
+        let decoded = image::load_from_memory(&data).unwrap();
+        let size = thumbnail["size"].as_u64().unwrap() as u32;
+        assert_eq!((decoded.width(), decoded.height()), (size, size));
+    }
+    assert_eq!(user_keys().len(), 3);
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["user"]["profile"]["avatar"], first_avatar.as_str());
+
+    // EXIF (com GPS, câmera...) não chega ao armazenamento e o avatar anterior é apagado
+    let marker = b"SEGREDO-GPS-123";
+    let jpeg = jpeg_with_exif(marker);
+    assert!(contains_bytes(&jpeg, marker));
+    let resp = test::call_service(&app, upload("avatar", "image/jpeg", &jpeg)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_ne!(body["avatar"], first_avatar.as_str());
+
+    let keys = user_keys();
+    assert_eq!(keys.len(), 3);
+    assert!(
+        blobs
+            .key_from_url(&first_avatar)
+            .is_some_and(|key| !keys.contains(&key))
+    );
+    for key in &keys {
+        let blob = blobs.get(key).await.unwrap().unwrap();
+        assert!(!contains_bytes(&blob.data, marker));
+        assert!(!contains_bytes(&blob.data, b"Exif\x00\x00"));
+    }
+
+    // tipo não aceito, conteúdo que não bate com o tipo e imagem corrompida
+    let resp = test::call_service(&app, upload("avatar", "text/plain", b"ola")).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = test::call_service(&app, upload("avatar", "image/png", &jpeg)).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = test::call_service(&app, upload("avatar", "image/gif", b"GIF89a")).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let mut corrupted = png[..64].to_vec();
+    corrupted.extend_from_slice(&[0u8; 64]);
+    let resp = test::call_service(&app, upload("avatar", "image/png", &corrupted)).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = test::call_service(&app, upload("arquivo", "image/png", &png)).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // acima de AVATAR_MAX_BYTES (5 MiB por padrão)
+    let oversized = vec![0u8; 5 * 1024 * 1024 + 1];
+    let resp = test::call_service(&app, upload("avatar", "image/png", &oversized)).await;
+    assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(user_keys().len(), 3);
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/media/avatars/nao-existe/512.jpg")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let req = test::TestRequest::get()
+        .uri("/api/v1/media/avatars/..%2F..%2Fetc/passwd")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::delete()
+        .uri("/api/v1/me/avatar/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    assert!(user_keys().is_empty());
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert!(body["user"]["profile"]["avatar"].is_null());
+}
+
+type MockS3Objects = Arc<std::sync::Mutex<std::collections::HashMap<String, (String, Vec<u8>)>>>;
+
+/// Servidor mínimo compatível com S3 (PUT/GET/DELETE em estilo de caminho), no papel do MinIO
+async fn start_mock_s3(bucket: &'static str) -> (String, MockS3Objects) {
+    let objects: MockS3Objects = Arc::default();
+    let state = objects.clone();
+
+    let server = HttpServer::new(move || {
+        let objects = state.clone();
+        App::new()
+            .app_data(web::PayloadConfig::new(10 * 1024 * 1024))
+            .app_data(web::Data::new(objects))
+            .route(
+                "/{bucket}/{key:.*}",
+                web::route().to(
+                    move |req: actix_web::HttpRequest,
+                          path: web::Path<(String, String)>,
+                          body: web::Bytes,
+                          objects: web::Data<MockS3Objects>| async move {
+                        let (request_bucket, key) = path.into_inner();
+                        if request_bucket != bucket || !req.headers().contains_key("Authorization")
+                        {
+                            return HttpResponse::Forbidden().finish();
+                        }
+                        let mut objects = objects.lock().unwrap();
+                        match *req.method() {
+                            actix_web::http::Method::PUT => {
+                                let content_type = req
+                                    .headers()
+                                    .get("Content-Type")
+                                    .and_then(|h| h.to_str().ok())
+                                    .unwrap_or("application/octet-stream")
+                                    .to_string();
+                                objects.insert(key, (content_type, body.to_vec()));
+                                HttpResponse::Ok()
+                                    .insert_header(("ETag", "\"mock\""))
+                                    .finish()
+                            }
+                            actix_web::http::Method::GET => match objects.get(&key) {
+                                Some((content_type, data)) => HttpResponse::Ok()
+                                    .content_type(content_type.as_str())
+                                    .body(data.clone()),
+                                None => HttpResponse::NotFound()
+                                    .content_type("application/xml")
+                                    .body(
+                                        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+                                         <Error><Code>NoSuchKey</Code>\
+                                         <Message>The specified key does not exist.</Message>\
+                                         </Error>",
+                                    ),
+                            },
+                            actix_web::http::Method::DELETE => {
+                                objects.remove(&key);
+                                HttpResponse::NoContent().finish()
+                            }
+                            _ => HttpResponse::MethodNotAllowed().finish(),
+                        }
+                    },
+                ),
+            )
+    })
+    .workers(1)
+    .bind(("127.0.0.1", 0))
+    .unwrap();
+
+    let endpoint = format!("http://{}", server.addrs()[0]);
+    actix_web::rt::spawn(server.run());
+    (endpoint, objects)
+}
+
+#[actix_web::test]
+async fn test_s3_blob_store_with_compatible_server() {
+    init();
+
+    let (endpoint, objects) = start_mock_s3("avatares").await;
+    let store = Arc::new(
+        S3BlobStore::new(
+            &S3Settings {
+                bucket: "avatares".to_string(),
+                region: "us-east-1".to_string(),
+                endpoint: Some(endpoint.clone()),
+                access_key_id: Some("minioadmin".to_string()),
+                secret_access_key: Some("minioadmin".to_string()),
+                force_path_style: true,
+            },
+            None,
+        )
+        .unwrap(),
+    );
+
+    let key = "avatars/teste/512.jpg";
+    store
+        .put(key, "image/jpeg", b"conteudo".to_vec())
+        .await
+        .unwrap();
+    let blob = store.get(key).await.unwrap().unwrap();
+    assert_eq!(blob.content_type, "image/jpeg");
+    assert_eq!(blob.data, b"conteudo");
+
+    let url = store.url(key);
+    assert_eq!(url, format!("{}/avatares/{}", endpoint, key));
+    assert_eq!(store.key_from_url(&url).as_deref(), Some(key));
+    assert!(
+        store
+            .key_from_url("https://outro.exemplo.com/avatars/x.jpg")
+            .is_none()
+    );
+
+    store.delete(key).await.unwrap();
+    assert!(store.get(key).await.unwrap().is_none());
+    assert!(objects.lock().unwrap().is_empty());
+
+    // o envio de avatar funciona igual com o backend S3
+    let app_state = setup_app_state_with(|state| state.blobs = store.clone()).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": "avatar.s3@exemplo.com",
+            "password": "Senha123456",
+            "first_name": "Avatar",
+            "last_name": "S3"
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let token = body["token"].as_str().unwrap().to_string();
+
+    let webp = encode_test_image(120, 120, image::ImageFormat::WebP);
+    let (header, payload) = multipart_file("avatar", "image/webp", &webp);
+    let req = test::TestRequest::post()
+        .uri("/api/v1/me/avatar/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .insert_header(("Content-Type", header))
+        .set_payload(payload)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let avatar = body["avatar"].as_str().unwrap();
+    assert!(avatar.starts_with(&format!("{}/avatares/avatars/", endpoint)));
+
+    let objects = objects.lock().unwrap();
+    assert_eq!(objects.len(), 3);
+    assert!(
+        objects
+            .values()
+            .all(|(content_type, _)| content_type == "image/jpeg")
+    );
+}
+
+#[actix_web::test]
+async fn test_document_checksum_normalization_and_masking() {
+    init();
+
+    let cpf = parse_document("529.982.247-25").unwrap();
+    assert_eq!(cpf.kind, DocumentType::Cpf);
+    assert_eq!(cpf.number, "52998224725");
+    assert_eq!(parse_document("52998224725"), Some(cpf.clone()));
+    assert!(parse_document("529.982.247-26").is_none());
+    assert!(parse_document("111.111.111-11").is_none());
+    assert!(parse_document("529.982.247-2").is_none());
+    assert!(parse_document("529a982b247c25").is_none());
+
+    let cnpj = parse_document("11.222.333/0001-81").unwrap();
+    assert_eq!(cnpj.kind, DocumentType::Cnpj);
+    assert_eq!(cnpj.number, "11222333000181");
+    assert!(parse_document("11.222.333/0001-82").is_none());
+    assert!(parse_document("00.000.000/0000-00").is_none());
+
+    assert_eq!(
+        mask_document(DocumentType::Cpf, "52998224725"),
+        "***.982.247-**"
+    );
+    assert_eq!(
+        mask_document(DocumentType::Cnpj, "11222333000181"),
+        "**.222.333/0001-**"
+    );
+
+    let app_state = setup_app_state().await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    // CPF com dígito verificador errado é recusado no cadastro
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": "documento.invalido@exemplo.com",
+            "password": "Senha123456",
+            "first_name": "Documento",
+            "last_name": "Invalido",
+            "profile": { "document": "529.982.247-26" }
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // o documento é gravado só com dígitos, junto com o tipo
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": "documento.titular@exemplo.com",
+            "password": "Senha123456",
+            "first_name": "Documento",
+            "last_name": "Titular",
+            "profile": { "document": "529.982.247-25" }
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["user"]["profile"]["document"], "52998224725");
+    assert_eq!(body["user"]["profile"]["document_type"], "cpf");
+    let owner_id: uuid::Uuid = body["user"]["id"].as_str().unwrap().parse().unwrap();
+    let token = body["token"].as_str().unwrap().to_string();
+
+    let stored = profile_repository::find_profile_by_user_id(owner_id, &app_state.db)
+        .await
+        .unwrap();
+    assert_eq!(stored.document.as_deref(), Some("52998224725"));
+    assert_eq!(stored.document_type.as_deref(), Some("cpf"));
+
+    // documento sem tipo (como os legados fora de 11/14 dígitos) não pode mais ser gravado
+    let untyped = sqlx::query("UPDATE profiles SET document_type = NULL WHERE user_id = $1")
+        .bind(owner_id)
+        .execute(&app_state.db)
+        .await;
+    assert!(untyped.is_err());
+
+    // CNPJ também é aceito na atualização do perfil
+    let req = test::TestRequest::post()
+        .uri("/api/v1/profiles/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({ "document": "11.222.333/0001-82" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/profiles/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .set_json(serde_json::json!({ "document": "11.222.333/0001-81" }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["user"]["profile"]["document"], "11222333000181");
+    assert_eq!(body["user"]["profile"]["document_type"], "cnpj");
+
+    // unicidade: sem ACCOUNT_UNIQUE_DOCUMENT (padrão) o mesmo documento pode se repetir
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": "documento.repetido@exemplo.com",
+            "password": "Senha123456",
+            "first_name": "Documento",
+            "last_name": "Repetido",
+            "profile": { "document": "11222333000181" }
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let other_id: uuid::Uuid = body["user"]["id"].as_str().unwrap().parse().unwrap();
+
+    let err =
+        profile_service::prepare_document("11.222.333/0001-81", other_id, true, &app_state.db)
+            .await
+            .unwrap_err();
+    assert!(matches!(err, AppError::Conflict(_)));
+
+    // a trava do documento segura uma segunda transação até a primeira terminar
+    let mut first = app_state.db.begin().await.unwrap();
+    let in_use = profile_repository::lock_document_in_tx("11222333000181", owner_id, &mut first)
+        .await
+        .unwrap();
+    assert!(in_use);
+    let mut second = app_state.db.begin().await.unwrap();
+    let waiting = tokio::time::timeout(
+        std::time::Duration::from_millis(200),
+        profile_repository::lock_document_in_tx("11222333000181", other_id, &mut second),
+    )
+    .await;
+    assert!(waiting.is_err());
+    first.rollback().await.unwrap();
+    second.rollback().await.unwrap();
+
+    // o titular e quem administra usuários veem o documento completo pela API
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["user"]["profile"]["document"], "11222333000181");
+
+    let admin_email = "documento.admin@exemplo.com";
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": admin_email,
+            "password": "Senha123456",
+            "first_name": "Documento",
+            "last_name": "Admin"
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let admin_id: uuid::Uuid = body["user"]["id"].as_str().unwrap().parse().unwrap();
+    role_repository::assign_role(admin_id, "admin", &app_state.db)
+        .await
+        .unwrap();
+    let req = test::TestRequest::post()
+        .uri("/api/v1/login/")
+        .set_json(serde_json::json!({ "email": admin_email, "password": "Senha123456" }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let admin_token = body["token"].as_str().unwrap().to_string();
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/admin/users/{}/", other_id))
+        .insert_header(("Authorization", format!("Token {}", admin_token)))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["profile"]["document"], "11222333000181");
+
+    let req = test::TestRequest::delete()
+        .uri("/api/v1/users/")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    // contas excluídas não bloqueiam o documento
+    let document =
+        profile_service::prepare_document("11.222.333/0001-81", other_id, true, &app_state.db)
+            .await
+            .unwrap();
+    assert_eq!(document.number, "11222333000181");
+
+    // máscara para quem não é o titular nem administrador
+    let mut profile = profile_repository::find_profile_by_user_id(other_id, &app_state.db)
+        .await
+        .unwrap();
+    let mut visible = profile.clone();
+    visible.apply_viewer_visibility(Some(other_id), false);
+    assert_eq!(visible.document.as_deref(), Some("11222333000181"));
+    let mut visible = profile.clone();
+    visible.apply_viewer_visibility(Some(owner_id), true);
+    assert_eq!(visible.document.as_deref(), Some("11222333000181"));
+    profile.apply_viewer_visibility(Some(owner_id), false);
+    assert_eq!(profile.document.as_deref(), Some("**.222.333/0001-**"));
+}
+
+fn sms_code(body: &str) -> String {
+    body.split(|c: char| !c.is_ascii_digit())
+        .find(|part| part.len() == 6)
+        .expect("SMS sem código")
+        .to_string()
+}
+
+#[actix_web::test]
+async fn test_phone_normalization_and_sms_verification() {
+    init();
+
+    assert_eq!(
+        normalize_phone("(11) 98765-4321", "BR").as_deref(),
+        Some("+5511987654321")
+    );
+    assert_eq!(
+        normalize_phone("+1 650-253-0000", "BR").as_deref(),
+        Some("+16502530000")
+    );
+    assert!(normalize_phone("12345", "BR").is_none());
+
+    let sms = Arc::new(InMemorySmsSender::new());
+    let app_state = setup_app_state_with(|state| state.sms = sms.clone()).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": "telefone.invalido@exemplo.com",
+            "password": "Senha123456",
+            "first_name": "Telefone",
+            "last_name": "Invalido",
+            "profile": { "phone": "12345" }
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // sem DDI o número é lido como brasileiro (PHONE_DEFAULT_REGION)
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": "telefone.titular@exemplo.com",
+            "password": "Senha123456",
+            "first_name": "Telefone",
+            "last_name": "Titular",
+            "profile": { "phone": "(11) 98765-4321" }
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["user"]["profile"]["phone"], "+5511987654321");
+    assert_eq!(body["user"]["profile"]["phone_verified"], false);
+    let token = body["token"].as_str().unwrap().to_string();
+    let auth = ("Authorization", format!("Token {}", token));
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/me/phone/verification/")
+        .insert_header(auth.clone())
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["phone"], "+5511987654321");
+    assert_eq!(body["expires_in"], 600);
+
+    // reenvio imediato para o mesmo número
+    let req = test::TestRequest::post()
+        .uri("/api/v1/me/phone/verification/")
+        .insert_header(auth.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let messages = sms.messages_to("+5511987654321");
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].body.contains("expira em 10 minutos"));
+    let code = sms_code(&messages[0].body);
+    let wrong = if code == "000000" { "111111" } else { "000000" };
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/me/phone/verification/confirm/")
+        .insert_header(auth.clone())
+        .set_json(serde_json::json!({ "code": wrong }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/me/phone/verification/confirm/")
+        .insert_header(auth.clone())
+        .set_json(serde_json::json!({ "code": code }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/")
+        .insert_header(auth.clone())
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["user"]["profile"]["phone_verified"], true);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/me/phone/verification/")
+        .insert_header(auth.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    // trocar o telefone desfaz a verificação
+    let req = test::TestRequest::post()
+        .uri("/api/v1/profiles/")
+        .insert_header(auth.clone())
+        .set_json(serde_json::json!({ "phone": "123" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/profiles/")
+        .insert_header(auth.clone())
+        .set_json(serde_json::json!({ "phone": "+55 21 99876-5432" }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["user"]["profile"]["phone"], "+5521998765432");
+    assert_eq!(body["user"]["profile"]["phone_verified"], false);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/me/phone/verification/")
+        .insert_header(auth.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let code = sms_code(&sms.messages_to("+5521998765432")[0].body);
+    let wrong = if code == "000000" { "111111" } else { "000000" };
+
+    // esgotadas as tentativas, nem o código certo vale mais
+    for _ in 0..5 {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/me/phone/verification/confirm/")
+            .insert_header(auth.clone())
+            .set_json(serde_json::json!({ "code": wrong }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+    let req = test::TestRequest::post()
+        .uri("/api/v1/me/phone/verification/confirm/")
+        .insert_header(auth.clone())
+        .set_json(serde_json::json!({ "code": code }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_unique_usernames_change_and_public_profile() {
+    init();
+
+    assert_eq!(
+        generate_username_from_email("Maria.Souza@exemplo.com"),
+        "maria_souza"
+    );
+    assert_eq!(generate_username_from_email("a@exemplo.com"), "user_a");
+    assert!(username_violation("ab").is_some());
+    assert_eq!(
+        username_violation("admin").as_deref(),
+        Some("Nome de usuário reservado")
+    );
+    assert!(username_violation("nome_valido_123").is_none());
+
+    let app_state = setup_app_state().await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::from(app_state.clone()))
+            .service(api_v1_scope()),
+    )
+    .await;
+
+    // e-mails com o mesmo prefixo ganham sufixos em vez de falhar no índice único
+    let mut usernames = Vec::new();
+    let mut tokens = Vec::new();
+    let mut user_ids = Vec::new();
+    for domain in ["exemplo.com", "outro.com", "terceiro.com"] {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/users/")
+            .set_json(serde_json::json!({
+                "email": format!("Perfil.Publico@{}", domain),
+                "password": "Senha123456",
+                "first_name": "Perfil",
+                "last_name": "Sobrenome Privado",
+                "profile": {
+                    "bio": "Ensino Rust",
+                    "profession": "Desenvolvedora",
+                    "phone": "(11) 98765-4321"
+                }
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        usernames.push(body["user"]["username"].as_str().unwrap().to_string());
+        tokens.push(body["token"].as_str().unwrap().to_string());
+        user_ids.push(
+            body["user"]["id"]
+                .as_str()
+                .unwrap()
+                .parse::<uuid::Uuid>()
+                .unwrap(),
+        );
+    }
+    assert_eq!(
+        usernames,
+        ["perfil_publico", "perfil_publico_2", "perfil_publico_3"]
+    );
+
+    // nomes reservados também recebem sufixo
+    let req = test::TestRequest::post()
+        .uri("/api/v1/users/")
+        .set_json(serde_json::json!({
+            "email": "support@perfil-publico.com",
+            "password": "Senha123456",
+            "first_name": "Suporte",
+            "last_name": "Conta"
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert!(
+        body["user"]["username"]
+            .as_str()
+            .unwrap()
+            .starts_with("support_")
+    );
+
+    let change = |token: &str, username: &str| {
+        test::TestRequest::patch()
+            .uri("/api/v1/me/username/")
+            .insert_header(("Authorization", format!("Token {}", token)))
+            .set_json(serde_json::json!({ "username": username }))
+            .to_request()
+    };
+
+    for invalid in ["ab", "Admin", "nome com espaço", "_nome", "joão"] {
+        let resp = test::call_service(&app, change(&tokens[0], invalid)).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{invalid}");
+    }
+    let resp = test::call_service(&app, change(&tokens[0], "Perfil_Publico_2")).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let body: serde_json::Value =
+        test::call_and_read_body_json(&app, change(&tokens[0], "@Autora_Publica")).await;
+    assert_eq!(body["username"], "autora_publica");
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/me/")
+        .insert_header(("Authorization", format!("Token {}", tokens[0])))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["user"]["username"], "autora_publica");
+
+    // o username antigo fica livre
+    let resp = test::call_service(&app, change(&tokens[1], "perfil_publico")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // quem passa pela checagem prévia numa corrida ainda esbarra no índice único,
+    // que não diferencia maiúsculas
+    let result =
+        user_repository::update_username(user_ids[2], "Autora_Publica", &app_state.db).await;
+    assert!(matches!(result, Err(AppError::Conflict(_))));
+
+    for (name, active, deleted) in [
+        ("Rust do zero", true, false),
+        ("Rascunho", false, false),
+        ("Curso removido", true, true),
+    ] {
+        sqlx::query(
+            "INSERT INTO courses (id, name, description, is_active, price, month_duration, \
+             author_id, dt_start, dt_deleted) \
+             VALUES ($1, $2, 'Descrição', $3, 99.9, 3, $4, '2025-08-01', \
+             CASE WHEN $5 THEN NOW() END)",
+        )
+        .bind(uuid::Uuid::new_v4())
+        .bind(name)
+        .bind(active)
+        .bind(user_ids[0])
+        .bind(deleted)
+        .execute(&app_state.db)
+        .await
+        .unwrap();
+    }
+
+    // público, sem autenticação e sem diferenciar maiúsculas
+    let req = test::TestRequest::get()
+        .uri("/api/v1/users/Autora_Publica/")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["username"], "autora_publica");
+    assert_eq!(body["first_name"], "Perfil");
+    assert_eq!(body["bio"], "Ensino Rust");
+    assert_eq!(body["profession"], "Desenvolvedora");
+    assert!(body["avatar"].is_null());
+    assert!(body["member_since"].is_string());
+    let courses = body["courses"].as_array().unwrap();
+    assert_eq!(courses.len(), 1);
+    assert_eq!(courses[0]["name"], "Rust do zero");
+    for private in ["id", "email", "last_name", "phone", "document", "profile"] {
+        assert!(body.get(private).is_none(), "{private}");
+    }
+    assert!(!body.to_string().contains("Sobrenome Privado"));
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/users/nao_existe_ninguem/")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // contas suspensas ou excluídas não têm perfil público
+    sqlx::query("UPDATE users SET dt_suspended = NOW() WHERE id = $1")
+        .bind(user_ids[0])
+        .execute(&app_state.db)
+        .await
+        .unwrap();
+    let req = test::TestRequest::get()
+        .uri("/api/v1/users/autora_publica/")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::delete()
+        .uri("/api/v1/users/")
+        .insert_header(("Authorization", format!("Token {}", tokens[1])))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    let req = test::TestRequest::get()
+        .uri("/api/v1/users/perfil_publico/")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
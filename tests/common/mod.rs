@@ -0,0 +1,49 @@
+//! Fixtures compartilhadas pelos testes de integração
+#![allow(dead_code)]
+
+use actix::Actor;
+use rust_usecases::{
+    config::{app_state::AppState, init_settings},
+    db::{elasticsearch::get_elastic_client, mongo::init_mongodb, test_db::setup_test_db},
+    mailer::InMemoryEmailSender,
+    sms::InMemorySmsSender,
+    storage::InMemoryBlobStore,
+    stores::{
+        login_attempt_store::InMemoryLoginAttemptStore, rate_limit_store::InMemoryRateLimitStore,
+    },
+    websocket::server::WsServer,
+};
+use std::sync::{Arc, Once};
+
+static INIT: Once = Once::new();
+
+pub fn init() {
+    INIT.call_once(|| {
+        dotenvy::dotenv().ok();
+        init_settings().expect("Falha ao inicializar settings");
+    });
+}
+
+/// Estado da aplicação sobre o banco de testes, com os demais backends em memória
+pub async fn setup_app_state() -> Arc<AppState> {
+    setup_app_state_with(|_| {}).await
+}
+
+/// Como `setup_app_state`, trocando dependências (e-mail, storage, SMS...) antes de
+/// montar o estado, para que o teste guarde uma referência e inspecione o que foi usado
+pub async fn setup_app_state_with(customize: impl FnOnce(&mut AppState)) -> Arc<AppState> {
+    let mut state = AppState {
+        db: setup_test_db().await,
+        mongo: init_mongodb().await.unwrap(),
+        es: get_elastic_client().unwrap(),
+        ws_server: WsServer::new().start(),
+        login_attempts: Arc::new(InMemoryLoginAttemptStore::new()),
+        rate_limits: Arc::new(InMemoryRateLimitStore::new()),
+        mailer: Arc::new(InMemoryEmailSender::new()),
+        blobs: Arc::new(InMemoryBlobStore::new()),
+        sms: Arc::new(InMemorySmsSender::new()),
+    };
+    customize(&mut state);
+
+    Arc::new(state)
+}